@@ -0,0 +1,163 @@
+use alloc::vec::Vec;
+
+use crate::aesp::cipher::Cipher;
+use crate::aesp::error::{Error, Result};
+use crate::aesp::modes::{check_lengths, compute_tag_j0, derive_j0, gcm_siv_decrypt, gcm_siv_encrypt, gctr};
+
+/// Authenticated encryption with associated data, for code that is generic over the AEAD mode.
+///
+/// Unlike [encrypt_gcm](crate::Cipher::encrypt_gcm), the nonce and AAD are supplied by the caller and are not
+/// included in the output, which is formatted as `Ciphertext || Tag (16 bytes)` (as in RFC 5116).
+/// The caller is responsible for transmitting the nonce and AAD, and for never reusing a nonce with the same key.
+/// Nonces must be 12 bytes, otherwise an [InvalidNonceLength](crate::Error::InvalidNonceLength) error is returned.
+///
+/// Implemented by [Gcm] and [GcmSiv].
+///
+/// ## Examples
+/// ```
+/// # fn main() -> aesp::Result<()> {
+/// use aesp::{Aead, Cipher, Gcm, GcmSiv, Key};
+///
+/// fn round_trip<A: Aead>(aead: &A) -> aesp::Result<()> {
+///     let nonce = [0x01; 12];
+///     let sealed = aead.encrypt(&nonce, b"header", b"Hello, World!")?;
+///     assert_eq!(aead.decrypt(&nonce, b"header", &sealed)?, b"Hello, World!");
+///     Ok(())
+/// }
+///
+/// let key = Key::rand_key_256()?;
+/// round_trip(&Gcm(Cipher::new(&key)))?;
+/// round_trip(&GcmSiv(Cipher::new(&key)))?;
+/// # Ok(())
+/// # }
+/// ```
+pub trait Aead {
+    /// Encrypts `plaintext` and authenticates it along with `aad`. Returns `Ciphertext || Tag (16 bytes)`.
+    fn encrypt(&self, nonce: &[u8], aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>>;
+
+    /// Verifies and decrypts `Ciphertext || Tag (16 bytes)`, as output by [encrypt](Aead::encrypt).
+    /// Returns an [AuthFailed](crate::Error::AuthFailed) error if the tag does not match.
+    fn decrypt(&self, nonce: &[u8], aad: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>>;
+}
+
+/// Checks that the nonce is 12 bytes.
+fn nonce_12(nonce: &[u8]) -> Result<&[u8; 12]> {
+    nonce.try_into().map_err(|_| Error::InvalidNonceLength { len: nonce.len() })
+}
+
+/// Splits `Ciphertext || Tag` into its parts.
+fn split_tag(ciphertext: &[u8]) -> Result<(&[u8], &[u8; 16])> {
+    ciphertext.split_last_chunk::<16>().ok_or(Error::InvalidCiphertext {
+        len: ciphertext.len(),
+        context: "AEAD: missing 16-byte tag",
+    })
+}
+
+/// **Galois/counter mode** through the [Aead] trait. See [encrypt_gcm](crate::Cipher::encrypt_gcm).
+#[derive(Clone, Debug)]
+pub struct Gcm(pub Cipher);
+
+impl Aead for Gcm {
+    fn encrypt(&self, nonce: &[u8], aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>> {
+        let nonce = nonce_12(nonce)?;
+        check_lengths(plaintext.len(), aad.len())?;
+        let round_keys = self.0.round_keys();
+        let j0 = derive_j0(self.0.ghash_key(), nonce);
+        let mut out = gctr(plaintext, round_keys, &j0, self.0.parallel_threshold())?;
+        let threshold = self.0.parallel_threshold();
+        let tag = compute_tag_j0(&out, round_keys, &j0, aad, self.0.ghash_key(), threshold)?;
+        out.extend_from_slice(&tag);
+        Ok(out)
+    }
+
+    fn decrypt(&self, nonce: &[u8], aad: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>> {
+        let nonce = nonce_12(nonce)?;
+        let (ciphertext, tag) = split_tag(ciphertext)?;
+        self.0.decrypt_gcm_with_aad(nonce, ciphertext, tag, aad)
+    }
+}
+
+/// **AES-GCM-SIV** through the [Aead] trait. See [encrypt_gcm_siv](crate::Cipher::encrypt_gcm_siv).
+/// Only 128 and 256-bit keys are supported.
+#[derive(Clone, Debug)]
+pub struct GcmSiv(pub Cipher);
+
+impl Aead for GcmSiv {
+    fn encrypt(&self, nonce: &[u8], aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>> {
+        let nonce = nonce_12(nonce)?;
+        let (mut out, tag) =
+            gcm_siv_encrypt(plaintext, aad, self.0.round_keys(), nonce, self.0.parallel_threshold())?;
+        out.extend_from_slice(&tag);
+        Ok(out)
+    }
+
+    fn decrypt(&self, nonce: &[u8], aad: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>> {
+        let nonce = nonce_12(nonce)?;
+        let (ciphertext, tag) = split_tag(ciphertext)?;
+        gcm_siv_decrypt(ciphertext, tag, aad, self.0.round_keys(), nonce, self.0.parallel_threshold())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aesp::modes::parse_gcm;
+    use crate::Key;
+
+    /// Exercises an AEAD only through the trait.
+    fn check_aead<A: Aead>(aead: &A) -> Result<()> {
+        let nonce = [0x24; 12];
+        for (aad, plaintext) in [(&b""[..], &b""[..]), (b"header", b"Hello, World!"), (b"", &[0x5a; 1000])] {
+            let sealed = aead.encrypt(&nonce, aad, plaintext)?;
+            assert_eq!(sealed.len(), plaintext.len() + 16);
+            assert_eq!(aead.decrypt(&nonce, aad, &sealed)?, plaintext);
+
+            // any change to the ciphertext, tag, AAD or nonce fails authentication
+            let mut bad = sealed.clone();
+            bad[0] ^= 1;
+            assert!(matches!(aead.decrypt(&nonce, aad, &bad), Err(Error::AuthFailed)));
+            assert!(matches!(aead.decrypt(&nonce, b"other", &sealed), Err(Error::AuthFailed)));
+            assert!(matches!(aead.decrypt(&[0x25; 12], aad, &sealed), Err(Error::AuthFailed)));
+        }
+        assert!(matches!(aead.decrypt(&nonce, b"", &[0u8; 15]), Err(Error::InvalidCiphertext { .. })));
+        Ok(())
+    }
+
+    #[test]
+    fn aead_trait_round_trip() -> Result<()> {
+        for key in [Key::rand_key_128()?, Key::rand_key_256()?] {
+            check_aead(&Gcm(Cipher::new(&key)))?;
+            check_aead(&GcmSiv(Cipher::new(&key)))?;
+        }
+        check_aead(&Gcm(Cipher::new(&Key::rand_key_192()?)))
+    }
+
+    #[test]
+    fn rejects_wrong_nonce_length() -> Result<()> {
+        let key = Key::rand_key_256()?;
+        let aeads: [&dyn Aead; 2] = [&Gcm(Cipher::new(&key)), &GcmSiv(Cipher::new(&key))];
+        for aead in aeads {
+            let sealed = aead.encrypt(&[0u8; 12], b"", b"Hello, World!")?;
+            for len in [0, 11, 13] {
+                let nonce = vec![0u8; len];
+                let err = aead.encrypt(&nonce, b"", b"Hello, World!");
+                assert!(matches!(err, Err(Error::InvalidNonceLength { len: l }) if l == len));
+                let err = aead.decrypt(&nonce, b"", &sealed);
+                assert!(matches!(err, Err(Error::InvalidNonceLength { len: l }) if l == len));
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn gcm_matches_framed_output() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_256()?);
+        let nonce = [0x07; 12];
+        let framed = parse_gcm(&cipher.encrypt_gcm_with_iv(b"Hello, World!", Some(b"header"), &nonce)?)?;
+
+        let sealed = Gcm(cipher).encrypt(&nonce, b"header", b"Hello, World!")?;
+        assert_eq!(sealed[..13], framed.ciphertext);
+        assert_eq!(sealed[13..], framed.tag);
+        Ok(())
+    }
+}
@@ -1,12 +1,37 @@
-use crate::aesp::core::constants::{RCON, SBOX};
+use crate::aesp::core::constants::RCON;
+use crate::aesp::envelope;
 use crate::aesp::error::{Error, Result};
 use crate::aesp::key::Key;
-use crate::aesp::util::{random_iv, pad, unpad};
+use crate::aesp::core::{
+    decrypt_block_eq, encrypt_block, encrypt_block_trace, hardware_accelerated, inverse_round_keys, sub_byte,
+};
+use crate::aesp::util::{ct_eq, zeroize};
+#[cfg(feature = "std")]
+use crate::aesp::util::{random_bytes, random_iv};
+#[cfg(feature = "base64")]
+use crate::aesp::util::decode_b64;
 
 use crate::aesp::modes::*;
 
-/// Provides encryption and decryption functions for AES in modes [ECB](crate::Cipher::encrypt_ecb), [CTR](crate::Cipher::encrypt_ctr), and [GCM](crate::Cipher::encrypt_gcm).
-/// Instantiated with an AES [Key], which is expanded into round keys and stored in the instance.
+#[cfg(all(feature = "base64", feature = "std"))]
+use crate::aesp::util::encode_b64;
+#[cfg(all(feature = "base64", feature = "std"))]
+use alloc::string::String;
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::collections::HashSet;
+#[cfg(feature = "std")]
+use std::sync::{Arc, Mutex, OnceLock};
+// without std there is no OnceLock, so a spin-based one stands in, keeping Cipher Send + Sync either way
+#[cfg(not(feature = "std"))]
+use crate::aesp::util::OnceLock;
+
+/// Provides encryption and decryption functions for AES in modes [ECB](crate::Cipher::encrypt_ecb), [CBC-CTS](crate::Cipher::encrypt_cbc_cts), [CTR](crate::Cipher::encrypt_ctr), and [GCM](crate::Cipher::encrypt_gcm).
+/// Instantiated with an AES [Key], which is expanded into round keys on first use and cached in the instance.
+/// Cloning copies the round keys (and any cached derived keys) rather than re-running the key
+/// schedule, so a configured cipher can be cheaply handed to each worker thread.
 /// 
 /// ## Examples
 /// ```
@@ -21,22 +46,397 @@ use crate::aesp::modes::*;
 /// # Ok(())
 /// # }
 /// ```
-#[derive(Clone, Eq, PartialEq, Debug)]
+#[derive(Clone)]
 pub struct Cipher {
-    round_keys: Vec<[u8; 16]>,
+    key: Key,
+    /// key schedule, computed on first use
+    round_keys: OnceLock<Vec<[u8; 16]>>,
+    /// GHASH key for GCM, computed on first use
+    ghash_key: OnceLock<GHashKey>,
+    /// round keys for the equivalent inverse cipher, computed on first ECB or CBC decryption
+    inv_round_keys: OnceLock<Vec<[u8; 16]>>,
+    /// inputs longer than this many bytes are processed in parallel
+    parallel_threshold: usize,
+    /// if set, explicit-IV encryption refuses all-zero and blocklisted IVs
+    reject_weak_ivs: bool,
+    weak_iv_blocklist: Vec<[u8; 12]>,
+    /// IVs used for encryption so far, if nonce tracking is enabled. Shared between clones
+    #[cfg(feature = "std")]
+    used_nonces: Option<Arc<Mutex<HashSet<Vec<u8>>>>>,
+    /// second, independent cipher for split-key modes (XTS, SIV), see [from_two_keys](Cipher::from_two_keys)
+    associated: Option<Box<Cipher>>,
 }
 
+// a Cipher is shared across threads (e.g. behind an Arc) with and without std; fail the build if a cached
+// field ever makes it !Send or !Sync
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Cipher>();
+};
+
 impl Cipher {
-    /// Generates round keys from provided key and stores in the returned instance.
+    /// Stores a copy of the provided key in the returned instance. The key schedule is run on first use, and
+    /// the inverse schedule only once something is decrypted with ECB or CBC, so constructing ciphers that are
+    /// never used costs nothing beyond the copy.
     pub fn new(key: &Key) -> Self {
         Self {
-            round_keys: Self::expand_key(key),
+            key: key.clone(),
+            round_keys: OnceLock::new(),
+            ghash_key: OnceLock::new(),
+            inv_round_keys: OnceLock::new(),
+            parallel_threshold: PARALLEL_THRESHOLD,
+            reject_weak_ivs: false,
+            weak_iv_blocklist: Vec::new(),
+            #[cfg(feature = "std")]
+            used_nonces: None,
+            associated: None,
+        }
+    }
+
+    /// Validates raw key bytes and builds a cipher from them in one step. Equivalent to
+    /// [Key::try_from_slice] followed by [new](crate::Cipher::new).
+    ///
+    /// Returns an [InvalidKeyLength](crate::Error::InvalidKeyLength) error if `key` is not 16, 24, or 32 bytes.
+    ///
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// use aesp::Cipher;
+    ///
+    /// # let key_bytes = [0x2b; 32];
+    /// // instantiate a cipher object from raw key bytes, e.g. read from a key store.
+    /// let cipher = Cipher::new_from_bytes(&key_bytes)?;
+    ///
+    /// // encrypt and decrypt using AES-256-CTR.
+    /// let plaintext = ("Hello, World!").as_bytes();
+    /// let ciphertext = cipher.encrypt_ctr(&plaintext)?;
+    /// assert_eq!(cipher.decrypt_ctr(&ciphertext)?, plaintext);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn new_from_bytes(key: &[u8]) -> Result<Self> {
+        Ok(Self::new(&Key::try_from_slice(key)?))
+    }
+
+    /// Generates round keys from two independent keys, for split-key modes such as XTS and SIV that
+    /// run AES under a second key (e.g. one for the tweak or MAC, one for encryption).
+    ///
+    /// `k1` is the cipher's primary key, used by every other mode exactly as if built with
+    /// [new](crate::Cipher::new). `k2` is expanded into a second schedule, available through
+    /// [associated](crate::Cipher::associated). The keys may be of different sizes.
+    ///
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// use aesp::{Key, Cipher};
+    ///
+    /// let (k1, k2) = (Key::rand_key_256()?, Key::rand_key_256()?);
+    /// let cipher = Cipher::from_two_keys(&k1, &k2);
+    ///
+    /// assert_eq!(cipher.round_keys(), Cipher::new(&k1).round_keys());
+    /// assert_eq!(cipher.associated(), Some(&Cipher::new(&k2)));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_two_keys(k1: &Key, k2: &Key) -> Self {
+        let mut cipher = Self::new(k1);
+        cipher.associated = Some(Box::new(Self::new(k2)));
+        cipher
+    }
+
+    /// Getter for the cipher built from the second key given to [from_two_keys](crate::Cipher::from_two_keys).
+    /// `None` for ciphers built from a single key.
+    pub fn associated(&self) -> Option<&Cipher> {
+        self.associated.as_deref()
+    }
+
+    /// Replaces the key, reusing the existing round key allocation, for servers that rotate keys often.
+    ///
+    /// Afterwards the cipher behaves exactly as if built with [new](crate::Cipher::new) from `key`. The old round
+    /// keys and any cached GHASH key and inverse round keys are wiped. Settings such as the parallel threshold
+    /// and weak IV rejection are kept, as is the second key of a [two-key](crate::Cipher::from_two_keys) cipher.
+    ///
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// use aesp::{Key, Cipher};
+    ///
+    /// let mut cipher = Cipher::new(&Key::rand_key_128()?);
+    /// let new_key = Key::rand_key_256()?;
+    /// cipher.rekey(&new_key);
+    /// assert_eq!(cipher, Cipher::new(&new_key));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn rekey(&mut self, key: &Key) {
+        self.wipe();
+        self.ghash_key = OnceLock::new();
+        self.inv_round_keys = OnceLock::new();
+        self.key = key.clone();
+        // nonces used under the old key may be used again under the new one
+        #[cfg(feature = "std")]
+        if self.used_nonces.is_some() {
+            self.used_nonces = Some(Arc::default());
+        }
+
+        let mut round_keys = self.round_keys.take().unwrap_or_default();
+        Self::expand_key_into(key, &mut round_keys);
+        self.round_keys = OnceLock::from(round_keys);
+    }
+
+    /// Sets the input size, in bytes, above which ECB, CTR and GCM encryption and decryption are
+    /// split across the rayon thread pool. Defaults to 4 KiB. Pass `usize::MAX` to always run serially.
+    /// Has no effect without the `std` feature, where processing is always serial.
+    ///
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// use aesp::{Key, Cipher};
+    ///
+    /// // never spawn parallel work
+    /// let cipher = Cipher::new(&Key::rand_key_256()?).with_parallel_threshold(usize::MAX);
+    /// assert_eq!(cipher.parallel_threshold(), usize::MAX);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_parallel_threshold(mut self, bytes: usize) -> Self {
+        self.parallel_threshold = bytes;
+        self
+    }
+
+    /// Sets the parallel threshold in place. See [with_parallel_threshold](crate::Cipher::with_parallel_threshold).
+    pub fn set_parallel_threshold(&mut self, bytes: usize) {
+        self.parallel_threshold = bytes;
+    }
+
+    /// Getter for the parallel threshold, in bytes.
+    pub fn parallel_threshold(&self) -> usize {
+        self.parallel_threshold
+    }
+
+    /// Refuses obviously broken IVs in encryption, as a defence against IVs taken from uninitialised or
+    /// zeroed memory. Off by default.
+    ///
+    /// When enabled, the CTR and GCM encryption functions return a [WeakNonce](crate::Error::WeakNonce) error
+    /// if the IV is all zeros, or is one of the IVs given to
+    /// [with_weak_iv_blocklist](crate::Cipher::with_weak_iv_blocklist). Random IVs are generated by the OS RNG
+    /// and will not be rejected in practice. Decryption is unaffected.
+    ///
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// use aesp::{Key, Cipher, Error};
+    ///
+    /// let cipher = Cipher::new(&Key::rand_key_256()?).reject_weak_ivs(true);
+    /// let result = cipher.encrypt_gcm_with_iv(b"Hello, World!", None, &[0u8; 12]);
+    /// assert!(matches!(result, Err(Error::WeakNonce)));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn reject_weak_ivs(mut self, reject: bool) -> Self {
+        self.reject_weak_ivs = reject;
+        self
+    }
+
+    /// Additional 12-byte IVs to refuse when [reject_weak_ivs](crate::Cipher::reject_weak_ivs) is enabled,
+    /// e.g. known test vector IVs that must never reach production. Replaces any previous blocklist.
+    pub fn with_weak_iv_blocklist(mut self, ivs: &[[u8; 12]]) -> Self {
+        self.weak_iv_blocklist = ivs.to_vec();
+        self
+    }
+
+    /// Refuses to encrypt under an IV that this cipher has already used, to catch nonce reuse, the most damaging
+    /// misuse of GCM and CTR. Off by default.
+    ///
+    /// When enabled, every IV used by CTR and GCM encryption, whether supplied by the caller or generated
+    /// randomly, is recorded, and encrypting under a recorded IV again returns a
+    /// [NonceReused](crate::Error::NonceReused) error. Clones of the cipher share the record, so IVs are tracked
+    /// across every thread the cipher is handed to; [rekey](crate::Cipher::rekey) clears it. Decryption is
+    /// unaffected.
+    ///
+    /// The record grows by one entry for each message encrypted, and is never pruned, so this suits ciphers with a
+    /// bounded lifetime rather than long-running servers. It only protects within this process and this cipher:
+    /// other instances built from the same key, and other processes, are not checked. Requires the `std` feature.
+    ///
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// use aesp::{Key, Cipher, Error};
+    ///
+    /// let cipher = Cipher::new(&Key::rand_key_256()?).with_nonce_tracking();
+    /// cipher.encrypt_gcm_with_iv(b"first", None, &[0x01; 12])?;
+    /// let result = cipher.encrypt_gcm_with_iv(b"second", None, &[0x01; 12]);
+    /// assert!(matches!(result, Err(Error::NonceReused)));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn with_nonce_tracking(mut self) -> Self {
+        self.used_nonces = Some(Arc::default());
+        self
+    }
+
+    /// Returns a WeakNonce error if weak IV rejection is enabled and `iv` is all zeros or blocklisted, and a
+    /// NonceReused error if nonce tracking is enabled and `iv` has been used before (recording it otherwise).
+    fn check_iv(&self, iv: &[u8]) -> Result<()> {
+        // empty IVs are left to fail with InvalidNonceLength
+        if iv.is_empty() {
+            return Ok(());
+        }
+        if self.reject_weak_ivs
+            && (iv.iter().all(|&b| b == 0) || self.weak_iv_blocklist.iter().any(|weak| weak[..] == *iv))
+        {
+            return Err(Error::WeakNonce);
+        }
+
+        #[cfg(feature = "std")]
+        if let Some(used) = &self.used_nonces {
+            // a panic while holding the lock cannot leave the set inconsistent, so poisoning is ignored
+            let mut used = used.lock().unwrap_or_else(|e| e.into_inner());
+            if !used.insert(iv.to_vec()) {
+                return Err(Error::NonceReused);
+            }
         }
+        Ok(())
+    }
+
+    /// GHASH key for GCM, computed from the round keys on first use and cached for later calls.
+    pub(crate) fn ghash_key(&self) -> &GHashKey {
+        self.ghash_key.get_or_init(|| GHashKey::for_round_keys(self.round_keys()))
+    }
+
+    /// Round keys for the equivalent inverse cipher, used by ECB and CBC decryption. Computed from the round keys on
+    /// first use and cached for later calls.
+    pub(crate) fn inv_round_keys(&self) -> &[[u8; 16]] {
+        self.inv_round_keys.get_or_init(|| inverse_round_keys(self.round_keys()))
     }
 
     /// Getter for internal round keys. Returned as a slice of 16-byte arrays.
+    /// Runs the key schedule if it has not been run yet.
     pub fn round_keys(&self) -> &[[u8; 16]] {
-        &self.round_keys
+        self.round_keys.get_or_init(|| Self::expand_key(&self.key))
+    }
+
+    /// Encrypts a single block, tracing the AES state round-by-round. Intended for teaching and debugging.
+    ///
+    /// Returns `(trace, ciphertext)`, where `trace[0]` is the state after the initial AddRoundKey and
+    /// `trace[r]` is the state after round `r` (SubBytes, ShiftRows, MixColumns and AddRoundKey, with
+    /// MixColumns omitted in the final round). `trace[r]` matches the "start of round `r + 1`" column of
+    /// FIPS-197 Appendix B, and the last entry equals the ciphertext.
+    ///
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// use aesp::{Key, Cipher};
+    ///
+    /// let cipher = Cipher::new(&Key::rand_key_128()?);
+    /// let (trace, ciphertext) = cipher.encrypt_block_trace(&[0u8; 16]);
+    /// assert_eq!(trace.len(), 11); // initial AddRoundKey + 10 rounds
+    /// assert_eq!(trace[10], ciphertext);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn encrypt_block_trace(&self, block: &[u8; 16]) -> (Vec<[u8; 16]>, [u8; 16]) {
+        encrypt_block_trace(block, self.round_keys())
+    }
+
+    /// Returns true if the AES-NI hardware backend is used for block encryption and decryption on this CPU, or
+    /// false if the portable software implementation is used.
+    ///
+    /// With the `std` feature this reflects runtime CPU feature detection, so it catches e.g. virtual machines
+    /// or containers that hide the `aes` CPU flag. Without `std`, it reports whether the `aes` target feature
+    /// was enabled at compile time. Always false on architectures other than x86-64.
+    ///
+    /// ## Examples
+    /// ```
+    /// use aesp::Cipher;
+    ///
+    /// if !Cipher::supports_hardware_acceleration() {
+    ///     eprintln!("warning: AES-NI unavailable, using the software implementation");
+    /// }
+    /// ```
+    pub fn supports_hardware_acceleration() -> bool {
+        hardware_accelerated()
+    }
+
+    /// Key check value (KCV), as used in ANSI X9.24 key management.
+    ///
+    /// Returns the first 3 bytes of an all-zero block encrypted under the key. Allows a loaded key to be
+    /// identified and compared against an expected value without revealing the key itself.
+    ///
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// use aesp::{Key, Cipher};
+    ///
+    /// let cipher = Cipher::new(&Key::try_from_slice(&[0u8; 16])?);
+    /// assert_eq!(cipher.key_check_value(), [0x66, 0xe9, 0x4b]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn key_check_value(&self) -> [u8; 3] {
+        let block = encrypt_block(&[0u8; 16], self.round_keys());
+        [block[0], block[1], block[2]]
+    }
+
+    /// Verifies that the key matches an expected [key check value](crate::Cipher::key_check_value).
+    /// The comparison is performed in constant time.
+    pub fn verify_kcv(&self, kcv: &[u8; 3]) -> bool {
+        ct_eq(&self.key_check_value(), kcv)
+    }
+
+    /// Encrypts a single 16-byte block with the raw AES block cipher, for building other modes of operation.
+    ///
+    /// This is ECB on one block: equal blocks always encrypt to equal outputs, and nothing is authenticated.
+    /// Prefer a mode such as [GCM](crate::Cipher::encrypt_gcm) for encrypting data.
+    ///
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// # use aesp::{Key, Cipher};
+    /// # let cipher = Cipher::new(&Key::rand_key_256()?);
+    /// let block = [0x42; 16];
+    /// let encrypted = cipher.encrypt_block(&block);
+    /// assert_eq!(cipher.decrypt_block(&encrypted), block);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn encrypt_block(&self, block: &[u8; 16]) -> [u8; 16] {
+        encrypt_block(block, self.round_keys())
+    }
+
+    /// Decrypts a single 16-byte block with the raw AES block cipher. Inverse of
+    /// [encrypt_block](crate::Cipher::encrypt_block), with the same caveats.
+    pub fn decrypt_block(&self, block: &[u8; 16]) -> [u8; 16] {
+        decrypt_block_eq(block, self.inv_round_keys())
+    }
+
+    /// **Electronic codebook** encryption of blocks from an iterator, for pipelines that already produce data
+    /// block by block. No padding is applied, and blocks are encrypted serially.
+    ///
+    /// Equivalent to mapping [encrypt_block](crate::Cipher::encrypt_block) over `blocks`, with the same caveats.
+    ///
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// # use aesp::{Key, Cipher};
+    /// # let cipher = Cipher::new(&Key::rand_key_256()?);
+    /// let blocks = (0..4u8).map(|i| [i; 16]);
+    /// let encrypted = cipher.encrypt_ecb_blocks(blocks);
+    /// assert_eq!(encrypted[0], cipher.encrypt_block(&[0; 16]));
+    /// assert_eq!(cipher.decrypt_ecb_blocks(encrypted)[3], [3; 16]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn encrypt_ecb_blocks<I: IntoIterator<Item = [u8; 16]>>(&self, blocks: I) -> Vec<[u8; 16]> {
+        blocks.into_iter().map(|block| encrypt_block(&block, self.round_keys())).collect()
+    }
+
+    /// **Electronic codebook** decryption of blocks from an iterator. Inverse of
+    /// [encrypt_ecb_blocks](crate::Cipher::encrypt_ecb_blocks).
+    pub fn decrypt_ecb_blocks<I: IntoIterator<Item = [u8; 16]>>(&self, blocks: I) -> Vec<[u8; 16]> {
+        let inv_round_keys = self.inv_round_keys();
+        blocks.into_iter().map(|block| decrypt_block_eq(&block, inv_round_keys)).collect()
     }
 
     /// **Electronic codebook** encryption.
@@ -57,7 +457,28 @@ impl Cipher {
     /// # }
     /// ```
     pub fn encrypt_ecb(&self, plaintext: &[u8]) -> Vec<u8> {
-        ecb_core_enc(&pad(plaintext), &self.round_keys).unwrap() // safe unwrap, input is always padded
+        self.encrypt_ecb_with_padding(plaintext, Padding::Pkcs7)
+    }
+
+    /// **Electronic codebook** encryption with a choice of [Padding] scheme, for interoperability with
+    /// systems that do not use PKCS#7. [encrypt_ecb](crate::Cipher::encrypt_ecb) is equivalent to
+    /// `encrypt_ecb_with_padding(plaintext, Padding::Pkcs7)`.
+    ///
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// # use aesp::{Key, Cipher, Padding};
+    /// # let rk_256 = Key::rand_key_256()?;
+    /// # let cipher = Cipher::new(&rk_256);
+    /// let plaintext = ("Hello, World!").as_bytes();
+    /// let ciphertext = cipher.encrypt_ecb_with_padding(&plaintext, Padding::Iso7816);
+    /// let decrypted = cipher.decrypt_ecb_with_padding(&ciphertext, Padding::Iso7816)?;
+    /// assert_eq!(decrypted, plaintext);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn encrypt_ecb_with_padding(&self, plaintext: &[u8], padding: Padding) -> Vec<u8> {
+        ecb_core_enc_padded(plaintext, self.round_keys(), self.parallel_threshold, padding)
     }
 
     /// **Electronic codebook** decryption.
@@ -79,392 +500,2736 @@ impl Cipher {
     /// # }
     /// ```
     pub fn decrypt_ecb(&self, ciphertext: &[u8]) -> Result<Vec<u8>> {
-        let mut ct = ecb_core_dec(ciphertext, &self.round_keys)?;
-        unpad(&mut ct)?;
-        Ok(ct)
+        self.decrypt_ecb_with_padding(ciphertext, Padding::Pkcs7)
     }
 
-    /// **Counter mode** encryption.
+    /// **Electronic codebook** decryption with a choice of [Padding] scheme.
     ///
-    /// Generates a random 12-byte initialisation vector (IV).
-    /// For each 16-byte block of plaintext:
-    /// 1. 4-byte counter is incremented (starts at zero).
-    /// 2. Counter is appended to 12-byte IV to form a 16-byte block.
-    /// 3. The `IV || Counter` block is encrypted using the round keys.
-    /// 4. The plaintext block is `XOR`'d with the encrypted counter block.
+    /// Throws error if the padding is malformed for the given scheme or input is not a multiple of 16 bytes.
+    /// [Padding::Zero] cannot be validated, and strips every trailing zero byte of the plaintext.
+    pub fn decrypt_ecb_with_padding(&self, ciphertext: &[u8], padding: Padding) -> Result<Vec<u8>> {
+        ecb_core_dec_padded(ciphertext, self.inv_round_keys(), self.parallel_threshold, padding)
+    }
+
+    /// **Electronic codebook** decryption without removing or validating padding, **for recovering data** from
+    /// systems that used non-standard or corrupt padding.
     ///
-    /// **Important**: the same IV must never be reused with the same key. 96 bits is
-    /// sufficiently large to assume uniqueness when randomly generated.
+    /// Decrypts every block and returns the full padded plaintext, including the final block, leaving the caller
+    /// to remove the padding. Throws error only if the input is not a multiple of 16 bytes. Without padding
+    /// validation, truncated or tampered ciphertext is not detected at all, so use
+    /// [decrypt_ecb](crate::Cipher::decrypt_ecb) or [decrypt_ecb_with_padding](crate::Cipher::decrypt_ecb_with_padding)
+    /// for anything other than recovery.
     ///
-    /// Output is formatted as `IV (12 bytes) || Ciphertext`
-    /// 
     /// ## Examples
     /// ```
     /// # fn main() -> aesp::Result<()> {
     /// # use aesp::{Key, Cipher};
-    /// # let rk_256 = Key::rand_key_256()?;
-    /// # let cipher = Cipher::new(&rk_256);
-    /// let plaintext = ("Hello, World!").as_bytes();
-    /// let ciphertext = cipher.encrypt_ctr(&plaintext)?;
+    /// # let cipher = Cipher::new(&Key::rand_key_256()?);
+    /// let ciphertext = cipher.encrypt_ecb(b"Hello, World!");
+    /// let padded = cipher.decrypt_ecb_lenient(&ciphertext)?;
+    /// assert_eq!(padded, b"Hello, World!\x03\x03\x03");
     /// # Ok(())
     /// # }
     /// ```
-    pub fn encrypt_ctr(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
-        // generate IV and prepend to ciphertext
-        let iv = random_iv()?;
-        let mut ciphertext = Vec::with_capacity(12 + plaintext.len());
-        ciphertext.extend_from_slice(&iv);
-        ciphertext.append(&mut ctr_core(plaintext, &self.round_keys, &iv, 0)?);
-        Ok(ciphertext)
+    pub fn decrypt_ecb_lenient(&self, ciphertext: &[u8]) -> Result<Vec<u8>> {
+        ecb_core_dec(ciphertext, self.inv_round_keys(), self.parallel_threshold)
     }
 
-    /// **Counter mode** decryption.
+    /// ECB diagnostic: decrypts without removing padding, returning the padded plaintext and its final byte.
+    /// Intended for finding out why [decrypt_ecb](crate::Cipher::decrypt_ecb) rejects the padding of data from
+    /// another implementation. Only available with the `debug` feature.
+    ///
+    /// Under PKCS#7 the final byte is the padding length, from 1 to 16. A plausible plaintext ending in some
+    /// other value points to a different [Padding] scheme (`0x00` for zero padding, or `0x80` followed by zeros
+    /// for ISO/IEC 7816-4), while a final block of random-looking bytes points to the wrong key. Throws error if
+    /// the input is empty or not a multiple of 16 bytes.
     ///
-    /// Assumes format matches output of encryption: `IV (12 bytes) || Ciphertext`
-    /// 
     /// ## Examples
     /// ```
     /// # fn main() -> aesp::Result<()> {
-    /// # use aesp::{Key, Cipher};
-    /// # let rk_256 = Key::rand_key_256()?;
-    /// # let cipher = Cipher::new(&rk_256);
-    /// let plaintext = ("Hello, World!").as_bytes();
-    /// let ciphertext = cipher.encrypt_ctr(&plaintext)?;
-    /// let decrypted = cipher.decrypt_ctr(&ciphertext)?;
-    /// assert_eq!(decrypted, plaintext);
+    /// # use aesp::{Key, Cipher, Padding};
+    /// # let cipher = Cipher::new(&Key::rand_key_256()?);
+    /// let ciphertext = cipher.encrypt_ecb_with_padding(b"Hello, World!", Padding::Iso7816);
+    /// assert!(cipher.decrypt_ecb(&ciphertext).is_err());
+    ///
+    /// let (padded, last) = cipher.decrypt_ecb_debug(&ciphertext)?;
+    /// assert_eq!(padded, b"Hello, World!\x80\x00\x00");
+    /// assert_eq!(last, 0x00);
     /// # Ok(())
     /// # }
     /// ```
-    pub fn decrypt_ctr(&self, ciphertext: &[u8]) -> Result<Vec<u8>> {
-        // extract and remove IV from ciphertext
-        if ciphertext.len() < 12 {
-            return Err(Error::InvalidCiphertext {
-                len: ciphertext.len(),
-                context: "CTR: missing 12-byte IV",
-            });
-        }
-
-        let (iv_bytes, ciphertext) = ciphertext.split_at(12);
-        let mut iv = [0u8; 12];
-        iv.copy_from_slice(iv_bytes);
-
-        ctr_core(ciphertext, &self.round_keys, &iv, 0)
+    #[cfg(feature = "debug")]
+    pub fn decrypt_ecb_debug(&self, ciphertext: &[u8]) -> Result<(Vec<u8>, u8)> {
+        let plaintext = self.decrypt_ecb_lenient(ciphertext)?;
+        let last = *plaintext.last().ok_or(Error::InvalidECBInput { len: 0 })?;
+        Ok((plaintext, last))
     }
 
-    /// **Galois/counter mode** encryption.
+    /// **Cipher block chaining** encryption with a caller-supplied IV.
     ///
-    /// Encrypts using counter mode and generates a cryptographic tag to verify the
-    /// message has not been modified.
+    /// Each plaintext block is `XOR`'d with the previous ciphertext block (the IV, for the first block) before
+    /// it is encrypted. The input is padded to a multiple of 16 bytes using PKCS#7 padding, as in
+    /// [encrypt_ecb](crate::Cipher::encrypt_ecb). The IV is **not** included in the output: the caller is
+    /// responsible for transmitting it, e.g. alongside the ciphertext or as part of a protocol.
     ///
-    /// Also accepts optional additional authenticated data (AAD), which is included in the computation of the
-    /// tag but **not encrypted**.
+    /// **Important**: CBC is only secure against chosen-plaintext attacks if the IV is unpredictable to an
+    /// attacker, not merely unique. Never use a counter or timestamp as the IV; generate it randomly. CBC also
+    /// provides no integrity: authenticate the ciphertext separately (e.g. with [cmac](crate::Cipher::cmac)),
+    /// or decryption failures may act as a padding oracle.
     ///
-    /// Output is formatted as `IV (12 bytes) || AAD length (4 bytes) || AAD || Ciphertext || Tag (16 bytes)`
-    /// 
     /// ## Examples
     /// ```
     /// # fn main() -> aesp::Result<()> {
     /// # use aesp::{Key, Cipher};
-    /// # let rk_256 = Key::rand_key_256()?;
-    /// # let cipher = Cipher::new(&rk_256);
-    /// let plaintext = ("Hello, World!").as_bytes();
-    /// let aad = ("Some data to be authenticated but not encrypted").as_bytes();
-    ///
-    /// let ciphertext_with_aad = cipher.encrypt_gcm(plaintext, Some(aad))?;
-    /// let ciphertext_no_aad = cipher.encrypt_gcm(plaintext, None)?;
+    /// # let cipher = Cipher::new(&Key::rand_key_256()?);
+    /// let iv = [0x3c; 16]; // in practice, random
+    /// let ciphertext = cipher.encrypt_cbc_with_iv(b"Hello, World!", &iv)?;
+    /// assert_eq!(ciphertext.len(), 16);
+    /// assert_eq!(cipher.decrypt_cbc_with_iv(&ciphertext, &iv)?, b"Hello, World!");
     /// # Ok(())
     /// # }
     /// ```
-    pub fn encrypt_gcm(&self, plaintext: &[u8], aad: Option<&[u8]>) -> Result<Vec<u8>> {
-        // generate random IV
-        let iv = random_iv()?;
-
-        // calculate AAD size
-        let aad_bytes = aad.unwrap_or(&[]);
-        let aad_len: u32 = aad_bytes
-            .len()
-            .try_into()
-            .expect("AAD size cannot exceed 2^32 bytes");
-
-        // initialise output vector
-        let mut out = Vec::with_capacity(12 + 4 + aad_bytes.len() + plaintext.len() + 16);
-
-        // build output: IV (12 bytes) || AAD length (4 bytes) || AAD || Ciphertext || Tag (16 bytes)
-        out.extend_from_slice(&iv);
-        out.extend_from_slice(&aad_len.to_be_bytes());
-        out.extend_from_slice(aad_bytes);
+    pub fn encrypt_cbc_with_iv(&self, plaintext: &[u8], iv: &[u8; 16]) -> Result<Vec<u8>> {
+        Ok(cbc_encrypt_padded(plaintext, self.round_keys(), iv))
+    }
 
-        let mut ct = ctr_core(plaintext, &self.round_keys, &iv, 2)?;
-        let tag = compute_tag(&ct, &self.round_keys, &iv, aad_bytes)?;
+    /// **Cipher block chaining** decryption of a bare ciphertext, with the IV managed by the caller.
+    ///
+    /// Inverse of [encrypt_cbc_with_iv](crate::Cipher::encrypt_cbc_with_iv): decrypts, then removes and
+    /// validates PKCS#7 padding. Throws error if the ciphertext is empty, is not a multiple of 16 bytes, or the
+    /// padding is malformed.
+    pub fn decrypt_cbc_with_iv(&self, ciphertext: &[u8], iv: &[u8; 16]) -> Result<Vec<u8>> {
+        cbc_decrypt_padded(ciphertext, self.inv_round_keys(), iv, self.parallel_threshold)
+    }
 
-        out.append(&mut ct);
-        out.extend_from_slice(&tag);
-        Ok(out)
+    /// **Cipher block chaining** decryption without removing or validating padding, **for recovering data** from
+    /// systems that used non-standard or corrupt padding.
+    ///
+    /// Decrypts every block and returns the full padded plaintext, leaving the caller to remove the padding.
+    /// Throws error only if the ciphertext is empty or not a multiple of 16 bytes. Use
+    /// [decrypt_cbc_with_iv](crate::Cipher::decrypt_cbc_with_iv) for anything other than recovery.
+    pub fn decrypt_cbc_lenient(&self, ciphertext: &[u8], iv: &[u8; 16]) -> Result<Vec<u8>> {
+        cbc_decrypt_unpadded(ciphertext, self.inv_round_keys(), iv, self.parallel_threshold)
     }
 
-    /// **Galois/counter mode** decryption.
+    /// **Cipher block chaining** encryption with ciphertext stealing (CBC-CS3, as used by Kerberos in RFC 3962).
     ///
-    /// Assumes input follows the same format as [encryption](crate::Cipher::encrypt_gcm):
-    /// `IV (12 bytes) || AAD length (4 bytes) || AAD || Ciphertext || Tag (16 bytes)`
+    /// Generates a random 16-byte initialisation vector (IV). Rather than padding, the final partial block
+    /// "steals" bytes from the previous ciphertext block, so the ciphertext is exactly as long as the
+    /// plaintext. The last two ciphertext blocks are always swapped, as specified for CS3.
+    ///
+    /// Returns an [InputTooShort](crate::Error::InputTooShort) error if the plaintext is shorter than 16 bytes.
+    ///
+    /// Output is formatted as `IV (16 bytes) || Ciphertext`
     ///
-    /// Returns:
-    /// - `(plaintext, AAD)` if tag was authenticated and decryption was successful.
-    /// - [AuthFailed](crate::Error::AuthFailed) error if computed tag did not match input tag.
-    /// - [CounterOverflow](crate::Error::CounterOverflow) error if more than 2^32 blocks were provided.
-    /// - [InvalidCiphertext](crate::Error::InvalidCiphertext) error if ciphertext does not match expected format.
-    /// 
     /// ## Examples
     /// ```
     /// # fn main() -> aesp::Result<()> {
     /// # use aesp::{Key, Cipher};
-    /// # let rk_256 = Key::rand_key_256()?;
-    /// # let cipher = Cipher::new(&rk_256);
-    /// let plaintext = ("Hello, World!").as_bytes();
-    /// let aad = ("Some data to be authenticated but not encrypted").as_bytes();
-    ///
-    /// // Decryption with AAD
-    /// let ciphertext = cipher.encrypt_gcm(plaintext, Some(aad))?;
-    /// let (decrypted, returned_aad) = cipher.decrypt_gcm(&ciphertext)?;
+    /// # let cipher = Cipher::new(&Key::rand_key_256()?);
+    /// let plaintext = ("Hello, World! Hello, World!").as_bytes();
+    /// let ciphertext = cipher.encrypt_cbc_cts(&plaintext)?;
+    /// assert_eq!(ciphertext.len(), 16 + plaintext.len());
+    /// assert_eq!(cipher.decrypt_cbc_cts(&ciphertext)?, plaintext);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn encrypt_cbc_cts(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        self.encrypt_cbc_cts_with_iv(plaintext, &random_bytes()?)
+    }
+
+    /// **CBC-CS3** encryption with a caller-supplied IV.
     ///
-    /// assert_eq!(decrypted, plaintext);
-    /// assert_eq!(returned_aad, Some(aad.to_vec()));
+    /// Identical to [encrypt_cbc_cts](crate::Cipher::encrypt_cbc_cts), except the 16-byte IV is provided by the
+    /// caller. CBC requires an IV that is unpredictable to an attacker, not merely unique.
+    pub fn encrypt_cbc_cts_with_iv(&self, plaintext: &[u8], iv: &[u8; 16]) -> Result<Vec<u8>> {
+        // prepend IV to ciphertext
+        let mut ciphertext = Vec::with_capacity(16 + plaintext.len());
+        ciphertext.extend_from_slice(iv);
+        ciphertext.append(&mut cbc_cts_encrypt(plaintext, self.round_keys(), iv)?);
+        Ok(ciphertext)
+    }
+
+    /// **CBC-CS3** decryption.
     ///
-    /// // Decryption without AAD
-    /// let ciphertext = cipher.encrypt_gcm(plaintext, None)?;
-    /// let (_, returned_aad) = cipher.decrypt_gcm(&ciphertext)?;
-    /// assert!(returned_aad.is_none());
+    /// Assumes format matches output of encryption: `IV (16 bytes) || Ciphertext`. Throws error if the
+    /// ciphertext (excluding the IV) is shorter than 16 bytes.
+    pub fn decrypt_cbc_cts(&self, ciphertext: &[u8]) -> Result<Vec<u8>> {
+        if ciphertext.len() < 32 {
+            return Err(Error::InvalidCiphertext {
+                len: ciphertext.len(),
+                context: "insufficient bytes for valid CBC-CTS",
+            });
+        }
+
+        let (iv, ciphertext) = ciphertext.split_first_chunk::<16>().unwrap(); // length checked above
+        cbc_cts_decrypt(ciphertext, self.inv_round_keys(), iv, self.parallel_threshold)
+    }
+
+    /// **Counter mode** encryption.
+    ///
+    /// Generates a random 12-byte initialisation vector (IV).
+    /// For each 16-byte block of plaintext:
+    /// 1. 4-byte counter is incremented (starts at zero).
+    /// 2. Counter is appended to 12-byte IV to form a 16-byte block.
+    /// 3. The `IV || Counter` block is encrypted using the round keys.
+    /// 4. The plaintext block is `XOR`'d with the encrypted counter block.
+    ///
+    /// **Important**: the same IV must never be reused with the same key. 96 bits is
+    /// sufficiently large to assume uniqueness when randomly generated.
+    ///
+    /// Output is formatted as `IV (12 bytes) || Ciphertext`
+    /// 
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// # use aesp::{Key, Cipher};
+    /// # let rk_256 = Key::rand_key_256()?;
+    /// # let cipher = Cipher::new(&rk_256);
+    /// let plaintext = ("Hello, World!").as_bytes();
+    /// let ciphertext = cipher.encrypt_ctr(&plaintext)?;
     /// # Ok(())
     /// # }
     /// ```
-    pub fn decrypt_gcm(&self, ciphertext: &[u8]) -> Result<(Vec<u8>, Option<Vec<u8>>)> {
-        // minimum size is 32 bytes -> 12 (iv) + 4 (aad_len) + 16 (tag)
-        if ciphertext.len() < 32 {
+    #[cfg(feature = "std")]
+    pub fn encrypt_ctr(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let iv = random_iv()?;
+
+        // prepend IV to ciphertext
+        let mut ciphertext = Vec::with_capacity(12 + plaintext.len());
+        ciphertext.extend_from_slice(&iv);
+        ciphertext.append(&mut self.encrypt_ctr_with_iv(plaintext, &iv)?);
+        Ok(ciphertext)
+    }
+
+    /// **Counter mode** encryption of a bare ciphertext, with the IV managed by the caller.
+    ///
+    /// Unlike [encrypt_ctr](crate::Cipher::encrypt_ctr), the 12-byte IV is provided by the caller (e.g. from
+    /// [generate_iv_with_rng](crate::generate_iv_with_rng)) and is not included in the output, which is only
+    /// the ciphertext. The counter starts at 0. Decrypt with
+    /// [decrypt_ctr_with_iv](crate::Cipher::decrypt_ctr_with_iv) under the same IV.
+    ///
+    /// **Important**: reusing an IV with the same key reveals the `XOR` of the two plaintexts.
+    ///
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// # use aesp::{Key, Cipher};
+    /// # let cipher = Cipher::new(&Key::rand_key_256()?);
+    /// let iv = [0x01; 12]; // must never be reused with this key
+    /// let ciphertext = cipher.encrypt_ctr_with_iv(b"Hello, World!", &iv)?;
+    /// assert_eq!(ciphertext.len(), 13);
+    /// assert_eq!(cipher.decrypt_ctr_with_iv(&ciphertext, &iv)?, b"Hello, World!");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn encrypt_ctr_with_iv(&self, plaintext: &[u8], iv: &[u8; 12]) -> Result<Vec<u8>> {
+        self.check_iv(iv)?;
+        ctr_core(plaintext, self.round_keys(), iv, 0, self.parallel_threshold)
+    }
+
+    /// **Counter mode** decryption of a bare ciphertext, with the IV managed by the caller.
+    ///
+    /// The inverse of [encrypt_ctr_with_iv](crate::Cipher::encrypt_ctr_with_iv): the input is only the
+    /// ciphertext, and no IV is read from the front of it as [decrypt_ctr](crate::Cipher::decrypt_ctr) does.
+    /// The counter starts at 0, so external test vectors with another initial counter (such as the
+    /// `f0f1...feff` counter block of SP 800-38A) need [ctr_with_counter](crate::Cipher::ctr_with_counter).
+    ///
+    /// **Important**: reusing an IV with the same key reveals the `XOR` of the two plaintexts.
+    ///
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// # use aesp::{Key, Cipher};
+    /// # let cipher = Cipher::new(&Key::rand_key_256()?);
+    /// let iv = [0x01; 12]; // must never be reused with this key, and is transmitted separately
+    /// let ciphertext = cipher.encrypt_ctr_with_iv(b"Hello, World!", &iv)?;
+    /// assert_eq!(cipher.decrypt_ctr_with_iv(&ciphertext, &iv)?, b"Hello, World!");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn decrypt_ctr_with_iv(&self, ciphertext: &[u8], iv: &[u8; 12]) -> Result<Vec<u8>> {
+        ctr_core(ciphertext, self.round_keys(), iv, 0, self.parallel_threshold)
+    }
+
+    /// **Counter mode** keystream applied from an arbitrary initial counter, for matching external test
+    /// vectors and protocols whose counter does not start at 0. CTR is symmetric, so this both encrypts and
+    /// decrypts. Neither the IV nor the counter is included in the output.
+    ///
+    /// Counter blocks are `IV (12 bytes) || Counter (4 bytes, big-endian)`, starting from `ctr_start`.
+    /// Returns a [CounterOverflow](crate::Error::CounterOverflow) error if the counter would wrap.
+    pub fn ctr_with_counter(&self, input: &[u8], iv: &[u8; 12], ctr_start: u32) -> Result<Vec<u8>> {
+        ctr_core(input, self.round_keys(), iv, ctr_start, self.parallel_threshold)
+    }
+
+    /// **Counter mode** decryption of a slice taken from an arbitrary byte offset into a ciphertext, for
+    /// random access without processing everything before it (e.g. seeking in an encrypted file).
+    ///
+    /// `ciphertext` is the bare ciphertext starting `start_offset` bytes in (not counting any IV prefix), and
+    /// `iv` is the IV the whole message was encrypted under. Decryption starts at counter `start_offset / 16`,
+    /// skipping the first `start_offset % 16` keystream bytes. CTR is symmetric, so this also encrypts.
+    /// Returns a [CounterOverflow](crate::Error::CounterOverflow) error if the range extends past 2^32 blocks.
+    ///
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// # use aesp::{Key, Cipher};
+    /// # let cipher = Cipher::new(&Key::rand_key_256()?);
+    /// let iv = [0x01; 12];
+    /// let ciphertext = cipher.encrypt_ctr_with_iv(b"Hello, World!", &iv)?;
+    ///
+    /// // decrypt just "World"
+    /// assert_eq!(cipher.decrypt_ctr_range(&ciphertext[7..12], &iv, 7)?, b"World");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn decrypt_ctr_range(&self, ciphertext: &[u8], iv: &[u8; 12], start_offset: usize) -> Result<Vec<u8>> {
+        let ctr_start = u32::try_from(start_offset / 16).map_err(|_| Error::CounterOverflow)?;
+        let skip = start_offset % 16;
+
+        // align to the start of the block, then drop the keystream bytes before the offset
+        let mut buf = Vec::with_capacity(skip + ciphertext.len());
+        buf.resize(skip, 0);
+        buf.extend_from_slice(ciphertext);
+        ctr_core_in_place(&mut buf, self.round_keys(), iv, ctr_start, self.parallel_threshold)?;
+        zeroize(&mut buf[..skip]);
+        Ok(buf.split_off(skip))
+    }
+
+    /// **Counter mode** decryption.
+    ///
+    /// Assumes format matches output of encryption: `IV (12 bytes) || Ciphertext`
+    /// 
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// # use aesp::{Key, Cipher};
+    /// # let rk_256 = Key::rand_key_256()?;
+    /// # let cipher = Cipher::new(&rk_256);
+    /// let plaintext = ("Hello, World!").as_bytes();
+    /// let ciphertext = cipher.encrypt_ctr(&plaintext)?;
+    /// let decrypted = cipher.decrypt_ctr(&ciphertext)?;
+    /// assert_eq!(decrypted, plaintext);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn decrypt_ctr(&self, ciphertext: &[u8]) -> Result<Vec<u8>> {
+        // extract and remove IV from ciphertext
+        if ciphertext.len() < 12 {
             return Err(Error::InvalidCiphertext {
                 len: ciphertext.len(),
-                context: "insufficient bytes for valid GCM",
+                context: "CTR: missing 12-byte IV",
+            });
+        }
+
+        let (iv_bytes, ciphertext) = ciphertext.split_at(12);
+        let mut iv = [0u8; 12];
+        iv.copy_from_slice(iv_bytes);
+
+        ctr_core(ciphertext, self.round_keys(), &iv, 0, self.parallel_threshold)
+    }
+
+    /// **Counter mode** decryption in place, without allocating.
+    ///
+    /// Takes a buffer in the format output by [encrypt_ctr](crate::Cipher::encrypt_ctr):
+    /// `IV (12 bytes) || Ciphertext`. The ciphertext portion is decrypted in place, and a slice
+    /// containing just the plaintext (excluding the IV prefix) is returned.
+    ///
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// # use aesp::{Key, Cipher};
+    /// # let rk_256 = Key::rand_key_256()?;
+    /// # let cipher = Cipher::new(&rk_256);
+    /// let plaintext = ("Hello, World!").as_bytes();
+    /// let mut buf = cipher.encrypt_ctr(&plaintext)?;
+    /// let decrypted = cipher.decrypt_ctr_in_place(&mut buf)?;
+    /// assert_eq!(decrypted, plaintext);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn decrypt_ctr_in_place<'a>(&self, buf: &'a mut [u8]) -> Result<&'a mut [u8]> {
+        if buf.len() < 12 {
+            return Err(Error::InvalidCiphertext {
+                len: buf.len(),
+                context: "CTR: missing 12-byte IV",
             });
         }
 
-        // extract IV
-        let (iv_bytes, ciphertext) = ciphertext.split_at(12);
-        let mut iv = [0u8; 12];
-        iv.copy_from_slice(iv_bytes);
+        let (iv_bytes, ciphertext) = buf.split_at_mut(12);
+        let mut iv = [0u8; 12];
+        iv.copy_from_slice(iv_bytes);
+
+        ctr_core_in_place(ciphertext, self.round_keys(), &iv, 0, self.parallel_threshold)?;
+        Ok(ciphertext)
+    }
+
+    /// **Counter mode** encryption in place, using a caller-supplied IV.
+    ///
+    /// `XOR`s the keystream directly into `buf`, producing the same ciphertext as
+    /// [encrypt_ctr](crate::Cipher::encrypt_ctr) under the same IV but without allocating. Unlike
+    /// `encrypt_ctr`, the IV is **not** prepended: the caller is responsible for transmitting it, and for
+    /// never reusing an IV under the same key.
+    ///
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// # use aesp::{Key, Cipher};
+    /// # let rk_256 = Key::rand_key_256()?;
+    /// # let cipher = Cipher::new(&rk_256);
+    /// let iv = [0x24; 12];
+    /// let mut buf = *b"Hello, World!";
+    /// cipher.encrypt_ctr_in_place(&iv, &mut buf)?;
+    /// cipher.decrypt_ctr_in_place_with_iv(&iv, &mut buf)?;
+    /// assert_eq!(&buf, b"Hello, World!");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn encrypt_ctr_in_place(&self, iv: &[u8; 12], buf: &mut [u8]) -> Result<()> {
+        self.check_iv(iv)?;
+        ctr_core_in_place(buf, self.round_keys(), iv, 0, self.parallel_threshold)
+    }
+
+    /// **Counter mode** decryption in place, using a caller-supplied IV. `buf` holds only the ciphertext.
+    ///
+    /// Inverse of [encrypt_ctr_in_place](crate::Cipher::encrypt_ctr_in_place). CTR is symmetric, so this
+    /// applies the same keystream.
+    pub fn decrypt_ctr_in_place_with_iv(&self, iv: &[u8; 12], buf: &mut [u8]) -> Result<()> {
+        ctr_core_in_place(buf, self.round_keys(), iv, 0, self.parallel_threshold)
+    }
+
+    /// **Counter mode** encryption with a 64-bit counter, for single streams longer than the 2^32 blocks
+    /// (64 GiB) supported by [encrypt_ctr](crate::Cipher::encrypt_ctr).
+    ///
+    /// Generates a random 8-byte IV, and forms each counter block as `IV (8 bytes) || Counter (8 bytes)`.
+    /// The shorter IV makes random IVs more likely to collide, so prefer [encrypt_ctr](crate::Cipher::encrypt_ctr)
+    /// unless the extra length is needed.
+    ///
+    /// Output is formatted as `IV (8 bytes) || Ciphertext`, which is **not** compatible with
+    /// [decrypt_ctr](crate::Cipher::decrypt_ctr).
+    ///
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// # use aesp::{Key, Cipher};
+    /// # let cipher = Cipher::new(&Key::rand_key_256()?);
+    /// let plaintext = ("Hello, World!").as_bytes();
+    /// let ciphertext = cipher.encrypt_ctr64(&plaintext)?;
+    /// assert_eq!(ciphertext.len(), 8 + plaintext.len());
+    /// assert_eq!(cipher.decrypt_ctr64(&ciphertext)?, plaintext);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn encrypt_ctr64(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        self.encrypt_ctr64_with_iv(plaintext, &random_bytes()?)
+    }
+
+    /// **Counter mode** encryption with a 64-bit counter and a caller-supplied 8-byte IV.
+    /// See [encrypt_ctr64](crate::Cipher::encrypt_ctr64).
+    pub fn encrypt_ctr64_with_iv(&self, plaintext: &[u8], iv: &[u8; 8]) -> Result<Vec<u8>> {
+        self.check_iv(iv)?;
+
+        // prepend IV to ciphertext
+        let mut ciphertext = Vec::with_capacity(8 + plaintext.len());
+        ciphertext.extend_from_slice(iv);
+        ciphertext.append(&mut ctr_core_64(plaintext, self.round_keys(), iv, 0, self.parallel_threshold)?);
+        Ok(ciphertext)
+    }
+
+    /// **Counter mode** decryption with a 64-bit counter.
+    ///
+    /// Assumes format matches output of [encrypt_ctr64](crate::Cipher::encrypt_ctr64): `IV (8 bytes) || Ciphertext`
+    pub fn decrypt_ctr64(&self, ciphertext: &[u8]) -> Result<Vec<u8>> {
+        let Some((iv, ciphertext)) = ciphertext.split_first_chunk::<8>() else {
+            return Err(Error::InvalidCiphertext {
+                len: ciphertext.len(),
+                context: "CTR64: missing 8-byte IV",
+            });
+        };
+        ctr_core_64(ciphertext, self.round_keys(), iv, 0, self.parallel_threshold)
+    }
+
+    /// **Counter mode** encryption with text-safe output. Identical to [encrypt_ctr](crate::Cipher::encrypt_ctr),
+    /// but the full `IV || Ciphertext` output is base64 encoded (see [encode_b64](crate::encode_b64)).
+    /// Requires the `base64` feature.
+    ///
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// # use aesp::{Key, Cipher};
+    /// # let cipher = Cipher::new(&Key::rand_key_256()?);
+    /// let encoded = cipher.encrypt_ctr_b64(b"Hello, World!")?;
+    /// assert_eq!(cipher.decrypt_ctr_b64(&encoded)?, b"Hello, World!");
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(all(feature = "base64", feature = "std"))]
+    pub fn encrypt_ctr_b64(&self, plaintext: &[u8]) -> Result<String> {
+        Ok(encode_b64(&self.encrypt_ctr(plaintext)?))
+    }
+
+    /// **Counter mode** decryption of the output of [encrypt_ctr_b64](crate::Cipher::encrypt_ctr_b64).
+    /// Returns an InvalidBase64 error if the input is not valid base64. Requires the `base64` feature.
+    #[cfg(feature = "base64")]
+    pub fn decrypt_ctr_b64(&self, encoded: &str) -> Result<Vec<u8>> {
+        self.decrypt_ctr(&decode_b64(encoded)?)
+    }
+
+    /// **Galois/counter mode** encryption.
+    ///
+    /// Encrypts using counter mode and generates a cryptographic tag to verify the
+    /// message has not been modified.
+    ///
+    /// Also accepts optional additional authenticated data (AAD), which is included in the computation of the
+    /// tag but **not encrypted**.
+    ///
+    /// Output is formatted as `IV (12 bytes) || AAD length (8 bytes) || AAD || Ciphertext || Tag (16 bytes)`
+    ///
+    /// Returns an [InputTooLong](crate::Error::InputTooLong) error if the plaintext exceeds 2^39 - 256 bits
+    /// (2^32 - 2 blocks, about 64 GiB) or the AAD exceeds 2^64 - 1 bits, the limits set by NIST SP 800-38D.
+    /// 
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// # use aesp::{Key, Cipher};
+    /// # let rk_256 = Key::rand_key_256()?;
+    /// # let cipher = Cipher::new(&rk_256);
+    /// let plaintext = ("Hello, World!").as_bytes();
+    /// let aad = ("Some data to be authenticated but not encrypted").as_bytes();
+    ///
+    /// let ciphertext_with_aad = cipher.encrypt_gcm(plaintext, Some(aad))?;
+    /// let ciphertext_no_aad = cipher.encrypt_gcm(plaintext, None)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn encrypt_gcm(&self, plaintext: &[u8], aad: Option<&[u8]>) -> Result<Vec<u8>> {
+        self.encrypt_gcm_with_iv(plaintext, aad, &random_iv()?)
+    }
+
+    /// **Galois/counter mode** encryption with a caller-supplied IV.
+    ///
+    /// Identical to [encrypt_gcm](crate::Cipher::encrypt_gcm), except the 12-byte IV is provided by the
+    /// caller rather than generated randomly. Useful for matching fixed test vectors, or for protocols
+    /// that construct nonces deterministically.
+    ///
+    /// **Important**: reusing an IV with the same key is catastrophic for GCM. It reveals the `XOR` of
+    /// the two plaintexts and allows an attacker to recover the authentication key and forge tags.
+    /// Prefer [encrypt_gcm](crate::Cipher::encrypt_gcm) unless IV uniqueness can be guaranteed.
+    ///
+    /// Output is formatted as `IV (12 bytes) || AAD length (8 bytes) || AAD || Ciphertext || Tag (16 bytes)`
+    ///
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// # use aesp::{Key, Cipher};
+    /// # let rk_256 = Key::rand_key_256()?;
+    /// # let cipher = Cipher::new(&rk_256);
+    /// let plaintext = ("Hello, World!").as_bytes();
+    /// let iv = [0x01; 12]; // must never be reused with this key
+    ///
+    /// let ciphertext = cipher.encrypt_gcm_with_iv(plaintext, None, &iv)?;
+    /// assert_eq!(&ciphertext[..12], &iv);
+    ///
+    /// let (decrypted, _) = cipher.decrypt_gcm(&ciphertext)?;
+    /// assert_eq!(decrypted, plaintext);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn encrypt_gcm_with_iv(
+        &self,
+        plaintext: &[u8],
+        aad: Option<&[u8]>,
+        iv: &[u8; 12],
+    ) -> Result<Vec<u8>> {
+        self.encrypt_gcm_with_iv_slice(plaintext, aad, iv)
+    }
+
+    /// **Galois/counter mode** encryption with a caller-supplied IV of any length.
+    ///
+    /// GCM is defined for any IV length, however 12 bytes is strongly recommended and is the only length
+    /// used by [encrypt_gcm](crate::Cipher::encrypt_gcm). Other lengths are supported for interoperability:
+    /// the initial counter block is derived by GHASHing the IV, as per NIST SP 800-38D.
+    ///
+    /// The same IV reuse warning as [encrypt_gcm_with_iv](crate::Cipher::encrypt_gcm_with_iv) applies.
+    ///
+    /// Output is formatted as `IV (iv.len() bytes) || AAD length (8 bytes) || AAD || Ciphertext || Tag (16 bytes)`,
+    /// and can be decrypted with [decrypt_gcm_with_iv_len](crate::Cipher::decrypt_gcm_with_iv_len).
+    /// Returns an [InvalidNonceLength](crate::Error::InvalidNonceLength) error if the IV is empty.
+    ///
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// # use aesp::{Key, Cipher};
+    /// # let rk_256 = Key::rand_key_256()?;
+    /// # let cipher = Cipher::new(&rk_256);
+    /// let plaintext = ("Hello, World!").as_bytes();
+    /// let iv = [0x01; 8];
+    ///
+    /// let ciphertext = cipher.encrypt_gcm_with_iv_slice(plaintext, None, &iv)?;
+    /// let (decrypted, _) = cipher.decrypt_gcm_with_iv_len(&ciphertext, iv.len())?;
+    /// assert_eq!(decrypted, plaintext);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn encrypt_gcm_with_iv_slice(
+        &self,
+        plaintext: &[u8],
+        aad: Option<&[u8]>,
+        iv: &[u8],
+    ) -> Result<Vec<u8>> {
+        self.check_iv(iv)?;
+        Ok(self.gcm_seal(plaintext, aad, iv)?.0)
+    }
+
+    /// **Galois/counter mode** encryption with a caller-supplied IV, into a caller-supplied buffer.
+    ///
+    /// Replaces the contents of `out` with exactly the output of
+    /// [encrypt_gcm_with_iv](crate::Cipher::encrypt_gcm_with_iv), growing it at most once. Reusing one buffer
+    /// across messages (for example, one per thread) avoids allocating for each message once the buffer has
+    /// grown to fit the largest. The same warnings about IV reuse apply.
+    ///
+    /// If an error is returned before encryption begins (an invalid IV or oversized input), `out` is left
+    /// untouched; otherwise its contents on error are unspecified.
+    ///
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// # use aesp::{Key, Cipher};
+    /// # let cipher = Cipher::new(&Key::rand_key_256()?);
+    /// let mut buf = Vec::new();
+    /// for (i, message) in [&b"first"[..], b"second"].into_iter().enumerate() {
+    ///     let iv = [i as u8 + 1; 12]; // must never be reused with this key
+    ///     cipher.encrypt_gcm_into(message, None, &iv, &mut buf)?;
+    ///     assert_eq!(cipher.decrypt_gcm(&buf)?.0, message);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn encrypt_gcm_into(
+        &self,
+        plaintext: &[u8],
+        aad: Option<&[u8]>,
+        iv: &[u8; 12],
+        out: &mut Vec<u8>,
+    ) -> Result<()> {
+        self.check_iv(iv)?;
+        self.gcm_seal_into(plaintext, aad, iv, out)?;
+        Ok(())
+    }
+
+    /// **Galois/counter mode** encryption with a truncated tag, for protocols that trade tag length for bandwidth.
+    ///
+    /// Identical to [encrypt_gcm_with_iv](crate::Cipher::encrypt_gcm_with_iv), except only the first `tag_bits`
+    /// bits of the tag are output, which must be one of the lengths approved by NIST SP 800-38D: 96, 104, 112,
+    /// 120 or 128. Each bit removed halves the work needed to forge a tag, so prefer the full 128 bits where
+    /// possible. The tag length is not recorded in the output, and must be supplied again to
+    /// [decrypt_gcm_with_tag_len](crate::Cipher::decrypt_gcm_with_tag_len).
+    ///
+    /// Output is formatted as `IV (12 bytes) || AAD length (8 bytes) || AAD || Ciphertext || Tag (tag_bits / 8 bytes)`.
+    /// Returns an [InvalidTagLength](crate::Error::InvalidTagLength) error for any other tag length.
+    ///
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// # use aesp::{Key, Cipher};
+    /// # let cipher = Cipher::new(&Key::rand_key_256()?);
+    /// let iv = [0x01; 12]; // must never be reused with this key
+    /// let ciphertext = cipher.encrypt_gcm_with_tag_len(b"Hello, World!", None, &iv, 96)?;
+    /// assert_eq!(ciphertext.len(), 12 + 8 + 13 + 12);
+    ///
+    /// let (decrypted, _) = cipher.decrypt_gcm_with_tag_len(&ciphertext, 96)?;
+    /// assert_eq!(decrypted, b"Hello, World!");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn encrypt_gcm_with_tag_len(
+        &self,
+        plaintext: &[u8],
+        aad: Option<&[u8]>,
+        iv: &[u8; 12],
+        tag_bits: usize,
+    ) -> Result<Vec<u8>> {
+        let tag_len = gcm_tag_len(tag_bits)?;
+        let mut out = self.encrypt_gcm_with_iv(plaintext, aad, iv)?;
+        out.truncate(out.len() - (16 - tag_len));
+        Ok(out)
+    }
+
+    /// **Galois/counter mode** decryption of the output of
+    /// [encrypt_gcm_with_tag_len](crate::Cipher::encrypt_gcm_with_tag_len). `tag_bits` must match the length
+    /// used to encrypt. Only that many bits of the computed tag are compared, in constant time.
+    ///
+    /// Returns the same results as [decrypt_gcm](crate::Cipher::decrypt_gcm), plus an
+    /// [InvalidTagLength](crate::Error::InvalidTagLength) error if `tag_bits` is not an approved length.
+    pub fn decrypt_gcm_with_tag_len(&self, ciphertext: &[u8], tag_bits: usize) -> Result<(Vec<u8>, Option<Vec<u8>>)> {
+        self.gcm_open_framed(&[], ciphertext, 12, gcm_tag_len(tag_bits)?)
+    }
+
+    /// **Galois/counter mode** encryption, also returning where each part lies in the output.
+    ///
+    /// Identical to [encrypt_gcm](crate::Cipher::encrypt_gcm), plus a [GcmFrame] holding the byte ranges of
+    /// the IV, AAD, ciphertext and tag. Useful for auditing or re-framing the output without
+    /// [parsing](crate::parse_gcm) it again.
+    ///
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// # use aesp::{Key, Cipher};
+    /// # let cipher = Cipher::new(&Key::rand_key_256()?);
+    /// let (ciphertext, frame) = cipher.encrypt_gcm_framed(b"Hello, World!", Some(b"header"))?;
+    /// assert_eq!(&ciphertext[frame.aad_range], b"header");
+    /// assert_eq!(frame.tag_range.end, ciphertext.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn encrypt_gcm_framed(&self, plaintext: &[u8], aad: Option<&[u8]>) -> Result<(Vec<u8>, GcmFrame)> {
+        self.gcm_seal(plaintext, aad, &random_iv()?)
+    }
+
+    /// GCM encryption with an IV of any length, recording the range of each part as the output is built.
+    fn gcm_seal(&self, plaintext: &[u8], aad: Option<&[u8]>, iv: &[u8]) -> Result<(Vec<u8>, GcmFrame)> {
+        let mut out = Vec::new();
+        let frame = self.gcm_seal_into(plaintext, aad, iv, &mut out)?;
+        Ok((out, frame))
+    }
+
+    /// [gcm_seal](Self::gcm_seal), replacing the contents of `out`. `out` is only modified once the input sizes
+    /// have been validated.
+    fn gcm_seal_into(&self, plaintext: &[u8], aad: Option<&[u8]>, iv: &[u8], out: &mut Vec<u8>) -> Result<GcmFrame> {
+        // validate sizes before doing any work. GCM is defined for any IV length from 1 bit upwards
+        if iv.is_empty() {
+            return Err(Error::InvalidNonceLength { len: 0 });
+        }
+        let aad_bytes = aad.unwrap_or(&[]);
+        check_lengths(plaintext.len(), aad_bytes.len())?;
+
+        // initialise output vector, reusing its allocation if it is already large enough
+        out.clear();
+        out.reserve(iv.len() + 8 + aad_bytes.len() + plaintext.len() + 16);
+
+        // build output: IV || AAD length (8 bytes) || AAD || Ciphertext || Tag (16 bytes)
+        out.extend_from_slice(iv);
+        let iv_range = 0..out.len();
+        out.extend_from_slice(&(aad_bytes.len() as u64).to_be_bytes());
+        out.extend_from_slice(aad_bytes);
+        let aad_range = out.len() - aad_bytes.len()..out.len();
+
+        // run ctr starting at inc32(J0), as per NIST spec, encrypting the plaintext in place in the output
+        out.extend_from_slice(plaintext);
+        let ct_range = aad_range.end..out.len();
+        let j0 = derive_j0(self.ghash_key(), iv);
+        gctr_in_place(&mut out[ct_range.clone()], self.round_keys(), &j0, self.parallel_threshold)?;
+        let (ct, gkey) = (&out[ct_range.clone()], self.ghash_key());
+        let tag = compute_tag_j0(ct, self.round_keys(), &j0, aad_bytes, gkey, self.parallel_threshold)?;
+
+        out.extend_from_slice(&tag);
+        let tag_range = ct_range.end..out.len();
+
+        Ok(GcmFrame { iv_range, aad_range, ct_range, tag_range })
+    }
+
+    /// **Galois/counter mode** encryption behind a cleartext header, such as a format version or key ID, that is
+    /// authenticated but neither encrypted nor counted in the AAD length field.
+    ///
+    /// Generates a random 12-byte IV. The tag is computed over `header || AAD`, so any change to the header is
+    /// detected on decryption. The header length is not stored: it must be fixed by the format, and is passed
+    /// to [decrypt_gcm_with_header](crate::Cipher::decrypt_gcm_with_header).
+    ///
+    /// Output is formatted as `Header || IV (12 bytes) || AAD length (8 bytes) || AAD || Ciphertext || Tag (16 bytes)`
+    ///
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// # use aesp::{Key, Cipher};
+    /// # let cipher = Cipher::new(&Key::rand_key_256()?);
+    /// let header = [0x01, 0x00, 0x2a]; // e.g. version 1, key ID 42
+    /// let blob = cipher.encrypt_gcm_with_header(&header, b"Hello, World!", None)?;
+    /// assert_eq!(&blob[..3], &header);
+    ///
+    /// let (plaintext, _) = cipher.decrypt_gcm_with_header(&blob, header.len())?;
+    /// assert_eq!(plaintext, b"Hello, World!");
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn encrypt_gcm_with_header(&self, header: &[u8], plaintext: &[u8], aad: Option<&[u8]>) -> Result<Vec<u8>> {
+        self.encrypt_gcm_with_header_and_iv(header, plaintext, aad, &random_iv()?)
+    }
+
+    /// **Galois/counter mode** encryption behind a cleartext header, with a caller-supplied IV. See
+    /// [encrypt_gcm_with_header](crate::Cipher::encrypt_gcm_with_header).
+    ///
+    /// **Important**: reusing an IV with the same key is catastrophic for GCM.
+    pub fn encrypt_gcm_with_header_and_iv(
+        &self,
+        header: &[u8],
+        plaintext: &[u8],
+        aad: Option<&[u8]>,
+        iv: &[u8; 12],
+    ) -> Result<Vec<u8>> {
+        self.check_iv(iv)?;
+        let aad_bytes = aad.unwrap_or(&[]);
+        // the header and AAD together are the AAD as far as GCM is concerned
+        check_lengths(plaintext.len(), header.len().saturating_add(aad_bytes.len()))?;
+
+        // Header || IV || AAD length (8 bytes) || AAD || Ciphertext || Tag (16 bytes)
+        let mut out = Vec::with_capacity(header.len() + 12 + 8 + aad_bytes.len() + plaintext.len() + 16);
+        out.extend_from_slice(header);
+        out.extend_from_slice(iv);
+        out.extend_from_slice(&(aad_bytes.len() as u64).to_be_bytes());
+        out.extend_from_slice(aad_bytes);
+
+        let j0 = derive_j0(self.ghash_key(), iv);
+        let mut ct = gctr(plaintext, self.round_keys(), &j0, self.parallel_threshold)?;
+        let auth_aad = [header, aad_bytes].concat();
+        let gkey = self.ghash_key();
+        let tag = compute_tag_j0(&ct, self.round_keys(), &j0, &auth_aad, gkey, self.parallel_threshold)?;
+
+        out.append(&mut ct);
+        out.extend_from_slice(&tag);
+        Ok(out)
+    }
+
+    /// **Galois/counter mode** decryption of the output of
+    /// [encrypt_gcm_with_header](crate::Cipher::encrypt_gcm_with_header), whose header is the first `header_len`
+    /// bytes. The header is re-authenticated along with the AAD and ciphertext.
+    ///
+    /// Returns `(plaintext, AAD)` as for [decrypt_gcm](crate::Cipher::decrypt_gcm). The header is not copied; it
+    /// is `&blob[..header_len]`, and may only be trusted once this returns successfully. Returns an
+    /// [AuthFailed](crate::Error::AuthFailed) error if the header, AAD or ciphertext has been modified, and an
+    /// [InvalidCiphertext](crate::Error::InvalidCiphertext) error if the input is too short.
+    pub fn decrypt_gcm_with_header(&self, blob: &[u8], header_len: usize) -> Result<(Vec<u8>, Option<Vec<u8>>)> {
+        if blob.len() < header_len {
+            return Err(Error::InvalidCiphertext {
+                len: blob.len(),
+                context: "GCM: missing header",
+            });
+        }
+
+        let (header, ciphertext) = blob.split_at(header_len);
+        self.gcm_open_framed(header, ciphertext, 12, 16)
+    }
+
+    /// **Galois/counter mode** decryption.
+    ///
+    /// Assumes input follows the same format as [encryption](crate::Cipher::encrypt_gcm):
+    /// `IV (12 bytes) || AAD length (8 bytes) || AAD || Ciphertext || Tag (16 bytes)`
+    ///
+    /// Returns:
+    /// - `(plaintext, AAD)` if tag was authenticated and decryption was successful.
+    /// - [AuthFailed](crate::Error::AuthFailed) error if computed tag did not match input tag.
+    /// - [InputTooLong](crate::Error::InputTooLong) error if the ciphertext exceeds the GCM limit of
+    ///   2^32 - 2 blocks.
+    /// - [InvalidCiphertext](crate::Error::InvalidCiphertext) error if ciphertext does not match expected format.
+    ///
+    /// The framing is validated before the tag is checked, so a truncated input or an AAD length field that
+    /// overruns the input is always reported as `InvalidCiphertext` (an [ErrorKind::Format](crate::ErrorKind)
+    /// error), and `AuthFailed` ([ErrorKind::Auth](crate::ErrorKind)) only ever means the input was well-formed
+    /// but did not authenticate. `AuthFailed` carries no detail about where the tags differed.
+    /// 
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// # use aesp::{Key, Cipher};
+    /// # let rk_256 = Key::rand_key_256()?;
+    /// # let cipher = Cipher::new(&rk_256);
+    /// let plaintext = ("Hello, World!").as_bytes();
+    /// let aad = ("Some data to be authenticated but not encrypted").as_bytes();
+    ///
+    /// // Decryption with AAD
+    /// let ciphertext = cipher.encrypt_gcm(plaintext, Some(aad))?;
+    /// let (decrypted, returned_aad) = cipher.decrypt_gcm(&ciphertext)?;
+    ///
+    /// assert_eq!(decrypted, plaintext);
+    /// assert_eq!(returned_aad, Some(aad.to_vec()));
+    ///
+    /// // Decryption without AAD
+    /// let ciphertext = cipher.encrypt_gcm(plaintext, None)?;
+    /// let (_, returned_aad) = cipher.decrypt_gcm(&ciphertext)?;
+    /// assert!(returned_aad.is_none());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn decrypt_gcm(&self, ciphertext: &[u8]) -> Result<(Vec<u8>, Option<Vec<u8>>)> {
+        self.decrypt_gcm_with_iv_len(ciphertext, 12)
+    }
+
+    /// **Galois/counter mode** decryption, reporting how much ciphertext was processed when authentication fails.
+    ///
+    /// Identical to [decrypt_gcm](crate::Cipher::decrypt_gcm), except that a tag mismatch is returned as
+    /// [AuthFailedAfter](crate::Error::AuthFailedAfter) rather than [AuthFailed](crate::Error::AuthFailed). Both
+    /// have [ErrorKind::Auth](crate::ErrorKind).
+    ///
+    /// The tag is computed over the whole ciphertext before any of it is decrypted, so `processed` is always the
+    /// full ciphertext length (excluding the IV, AAD and tag). GCM has no way to verify a prefix of a message: a
+    /// failed message must be fetched again in full, not resumed from part way through.
+    ///
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// # use aesp::{Key, Cipher, Error};
+    /// # let cipher = Cipher::new(&Key::rand_key_256()?);
+    /// let mut blob = cipher.encrypt_gcm(b"Hello, World!", Some(b"header"))?;
+    /// *blob.last_mut().unwrap() ^= 1;
+    ///
+    /// let result = cipher.decrypt_gcm_detailed(&blob);
+    /// assert!(matches!(result, Err(Error::AuthFailedAfter { processed: 13 })));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn decrypt_gcm_detailed(&self, ciphertext: &[u8]) -> Result<(Vec<u8>, Option<Vec<u8>>)> {
+        self.decrypt_gcm(ciphertext).map_err(|e| match e {
+            Error::AuthFailed => {
+                // the framing was validated before the tag was checked, so the AAD length field is in range
+                let aad_len = u64::from_be_bytes(ciphertext[12..20].try_into().unwrap()) as usize;
+                Error::AuthFailedAfter { processed: ciphertext.len() - (12 + 8 + 16) - aad_len }
+            }
+            e => e,
+        })
+    }
+
+    /// **Galois/counter mode** decryption for IVs of any length.
+    ///
+    /// Assumes input follows the format of [encrypt_gcm_with_iv_slice](crate::Cipher::encrypt_gcm_with_iv_slice),
+    /// where the IV prefix is `iv_len` bytes long:
+    /// `IV (iv_len bytes) || AAD length (8 bytes) || AAD || Ciphertext || Tag (16 bytes)`
+    ///
+    /// Returns the same results as [decrypt_gcm](crate::Cipher::decrypt_gcm).
+    pub fn decrypt_gcm_with_iv_len(
+        &self,
+        ciphertext: &[u8],
+        iv_len: usize,
+    ) -> Result<(Vec<u8>, Option<Vec<u8>>)> {
+        self.gcm_open_framed(&[], ciphertext, iv_len, 16)
+    }
+
+    /// Splits `IV || AAD length || AAD || Ciphertext || Tag` into its parts, then verifies and decrypts.
+    /// A non-empty `header` is authenticated as a prefix of the AAD, see
+    /// [encrypt_gcm_with_header](crate::Cipher::encrypt_gcm_with_header).
+    fn gcm_open_framed(
+        &self,
+        header: &[u8],
+        ciphertext: &[u8],
+        iv_len: usize,
+        tag_len: usize,
+    ) -> Result<(Vec<u8>, Option<Vec<u8>>)> {
+        // minimum size is iv_len + 8 (aad_len) + tag_len
+        if ciphertext.len() < iv_len.saturating_add(8 + tag_len) {
+            return Err(Error::InvalidCiphertext {
+                len: ciphertext.len(),
+                context: "insufficient bytes for valid GCM",
+            });
+        }
+
+        // extract IV
+        let (iv, ciphertext) = ciphertext.split_at(iv_len);
+
+        // extract AAD len and validate remaining size
+        let (aad_len, ciphertext) = ciphertext.split_at(8);
+        let aad_len = u64::from_be_bytes(aad_len.try_into().unwrap()); // safe unwrap, split at 8
+        // compare in u64, aad_len is untrusted and may not fit in usize
+        if aad_len > (ciphertext.len() - tag_len) as u64 {
+            return Err(Error::InvalidCiphertext {
+                len: ciphertext.len(),
+                context: "insufficient bytes given aad_len",
+            });
+        }
+
+        // extract aad and tag, at least tag_len bytes remain (checked above)
+        let (aad, ciphertext) = ciphertext.split_at(aad_len as usize);
+        let (ct, tag) = ciphertext.split_at(ciphertext.len() - tag_len);
+
+        let plaintext = if header.is_empty() {
+            self.gcm_open(iv, ct, tag, aad)?
+        } else {
+            self.gcm_open(iv, ct, tag, &[header, aad].concat())?
+        };
+
+        // wrap AAD in option
+        let aad = if !aad.is_empty() { Some(aad.to_vec()) } else { None };
+        Ok((plaintext, aad))
+    }
+
+    /// **Galois/counter mode** decryption with the IV, tag, and AAD supplied separately.
+    ///
+    /// For interoperating with GCM implementations that output `IV || Ciphertext || Tag` and transmit the AAD
+    /// out of band, rather than the framing used by [encrypt_gcm](crate::Cipher::encrypt_gcm). The tag is
+    /// verified over the given AAD and ciphertext before anything is decrypted.
+    ///
+    /// Returns:
+    /// - the plaintext if the tag was authenticated.
+    /// - [AuthFailed](crate::Error::AuthFailed) error if the computed tag did not match `tag`.
+    /// - [InputTooLong](crate::Error::InputTooLong) error if the ciphertext or AAD exceeds the GCM limits.
+    ///
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// # use aesp::{Key, Cipher, parse_gcm};
+    /// # let rk_256 = Key::rand_key_256()?;
+    /// # let cipher = Cipher::new(&rk_256);
+    /// let aad = ("Sent separately").as_bytes();
+    /// let blob = cipher.encrypt_gcm(("Hello, World!").as_bytes(), Some(aad))?;
+    ///
+    /// // e.g. received from another implementation as separate fields
+    /// let parts = parse_gcm(&blob)?;
+    /// let plaintext = cipher.decrypt_gcm_with_aad(&parts.iv, &parts.ciphertext, &parts.tag, aad)?;
+    /// assert_eq!(plaintext, b"Hello, World!");
+    ///
+    /// assert!(cipher.decrypt_gcm_with_aad(&parts.iv, &parts.ciphertext, &parts.tag, b"other").is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn decrypt_gcm_with_aad(
+        &self,
+        iv: &[u8; 12],
+        ciphertext: &[u8],
+        tag: &[u8; 16],
+        aad: &[u8],
+    ) -> Result<Vec<u8>> {
+        self.gcm_open(iv, ciphertext, tag, aad)
+    }
+
+    /// Verifies the tag over `aad` and `ciphertext`, then decrypts. A tag shorter than 16 bytes is compared
+    /// against the same prefix of the computed tag.
+    fn gcm_open(&self, iv: &[u8], ciphertext: &[u8], tag: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
+        // GCM is defined for any IV length from 1 bit upwards
+        if iv.is_empty() {
+            return Err(Error::InvalidNonceLength { len: 0 });
+        }
+
+        // compute and compare tag. `!=` on arrays exits at the first differing byte, so its timing reveals
+        // how many leading bytes of a forged tag are correct, allowing a tag to be guessed byte-by-byte.
+        // ct_eq always inspects all 16 bytes.
+        let j0 = derive_j0(self.ghash_key(), iv);
+        let computed_tag =
+            compute_tag_j0(ciphertext, self.round_keys(), &j0, aad, self.ghash_key(), self.parallel_threshold)?;
+        if !ct_eq(tag, &computed_tag[..tag.len()]) {
+            return Err(Error::AuthFailed);
+        }
+
+        // run ctr starting at inc32(J0), as per NIST spec
+        gctr(ciphertext, self.round_keys(), &j0, self.parallel_threshold)
+    }
+
+    /// **AES-GCM-SIV** encryption (RFC 8452), a nonce-misuse-resistant variant of GCM.
+    ///
+    /// The tag is computed over the plaintext and then used as the CTR IV (a synthetic IV), so reusing a
+    /// nonce only reveals whether the same plaintext and AAD were encrypted twice, rather than breaking
+    /// confidentiality and authenticity as it does for [GCM](crate::Cipher::encrypt_gcm). Per-nonce keys are
+    /// derived from the cipher's key, so only 128 and 256-bit keys are supported.
+    ///
+    /// Output is formatted as `Nonce (12 bytes) || AAD length (8 bytes) || AAD || Ciphertext || Tag (16 bytes)`,
+    /// matching [encrypt_gcm](crate::Cipher::encrypt_gcm).
+    ///
+    /// Returns an [UnsupportedKeyLength](crate::Error::UnsupportedKeyLength) error for 192-bit keys, and an
+    /// [InputTooLong](crate::Error::InputTooLong) error if the plaintext or AAD exceeds 2^36 bytes.
+    ///
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// # use aesp::{Key, Cipher};
+    /// # let rk_256 = Key::rand_key_256()?;
+    /// # let cipher = Cipher::new(&rk_256);
+    /// let plaintext = ("Hello, World!").as_bytes();
+    /// let aad = ("Some data to be authenticated but not encrypted").as_bytes();
+    ///
+    /// let ciphertext = cipher.encrypt_gcm_siv(plaintext, Some(aad))?;
+    /// let (decrypted, returned_aad) = cipher.decrypt_gcm_siv(&ciphertext)?;
+    ///
+    /// assert_eq!(decrypted, plaintext);
+    /// assert_eq!(returned_aad, Some(aad.to_vec()));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn encrypt_gcm_siv(&self, plaintext: &[u8], aad: Option<&[u8]>) -> Result<Vec<u8>> {
+        self.encrypt_gcm_siv_with_nonce(plaintext, aad, &random_iv()?)
+    }
+
+    /// **AES-GCM-SIV** encryption with a caller-supplied nonce.
+    ///
+    /// Identical to [encrypt_gcm_siv](crate::Cipher::encrypt_gcm_siv), except the 12-byte nonce is provided
+    /// by the caller. Nonces should still be unique, but a repeated nonce only reveals repeated messages.
+    pub fn encrypt_gcm_siv_with_nonce(
+        &self,
+        plaintext: &[u8],
+        aad: Option<&[u8]>,
+        nonce: &[u8; 12],
+    ) -> Result<Vec<u8>> {
+        let aad_bytes = aad.unwrap_or(&[]);
+        let (mut ct, tag) = gcm_siv_encrypt(plaintext, aad_bytes, self.round_keys(), nonce, self.parallel_threshold)?;
+
+        // build output: Nonce || AAD length (8 bytes) || AAD || Ciphertext || Tag (16 bytes)
+        let mut out = Vec::with_capacity(12 + 8 + aad_bytes.len() + ct.len() + 16);
+        out.extend_from_slice(nonce);
+        out.extend_from_slice(&(aad_bytes.len() as u64).to_be_bytes());
+        out.extend_from_slice(aad_bytes);
+        out.append(&mut ct);
+        out.extend_from_slice(&tag);
+        Ok(out)
+    }
+
+    /// **AES-GCM-SIV** decryption.
+    ///
+    /// Assumes input follows the same format as [encryption](crate::Cipher::encrypt_gcm_siv):
+    /// `Nonce (12 bytes) || AAD length (8 bytes) || AAD || Ciphertext || Tag (16 bytes)`
+    ///
+    /// Returns the same results as [decrypt_gcm](crate::Cipher::decrypt_gcm), plus an
+    /// [UnsupportedKeyLength](crate::Error::UnsupportedKeyLength) error for 192-bit keys.
+    pub fn decrypt_gcm_siv(&self, ciphertext: &[u8]) -> Result<(Vec<u8>, Option<Vec<u8>>)> {
+        // minimum size is 12 (nonce) + 8 (aad_len) + 16 (tag)
+        if ciphertext.len() < 36 {
+            return Err(Error::InvalidCiphertext {
+                len: ciphertext.len(),
+                context: "insufficient bytes for valid GCM-SIV",
+            });
+        }
+
+        let (nonce, rest) = ciphertext.split_first_chunk::<12>().unwrap(); // length checked above
+        let (aad_len, rest) = rest.split_first_chunk::<8>().unwrap();
+        // compare in u64, aad_len is untrusted and may not fit in usize
+        let aad_len = u64::from_be_bytes(*aad_len);
+        if aad_len > (rest.len() - 16) as u64 {
+            return Err(Error::InvalidCiphertext {
+                len: ciphertext.len(),
+                context: "insufficient bytes given aad_len",
+            });
+        }
+
+        let (aad, rest) = rest.split_at(aad_len as usize);
+        let (ct, tag) = rest.split_last_chunk::<16>().unwrap();
+        let plaintext = gcm_siv_decrypt(ct, tag, aad, self.round_keys(), nonce, self.parallel_threshold)?;
+
+        let aad = if !aad.is_empty() { Some(aad.to_vec()) } else { None };
+        Ok((plaintext, aad))
+    }
+
+    /// GCM diagnostic: traces the GHASH accumulator while computing the tag for `aad` and `ciphertext` under
+    /// `iv`. Intended for localising interoperability problems (byte order, block padding, the length block)
+    /// when another implementation's tags do not match. Only available with the `debug-ghash` feature.
+    ///
+    /// Returns the accumulator after each absorbed block: every AAD block, every ciphertext block (both
+    /// zero-padded), then the length block. The final entry is the tag, i.e. the accumulator after the length
+    /// block `XOR`'d with `E(K, J0)`.
+    ///
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// # use aesp::{Key, Cipher};
+    /// # let cipher = Cipher::new(&Key::rand_key_256()?);
+    /// let iv = [0x07; 12];
+    /// let blob = cipher.encrypt_gcm_with_iv(b"Hello, World!", Some(b"header"), &iv)?;
+    /// let parts = aesp::parse_gcm(&blob)?;
+    ///
+    /// let trace = cipher.ghash_trace(&parts.aad, &parts.ciphertext, &iv);
+    /// assert_eq!(trace.len(), 4); // AAD block, ciphertext block, length block, tag
+    /// assert_eq!(trace[3], parts.tag);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "debug-ghash")]
+    pub fn ghash_trace(&self, aad: &[u8], ciphertext: &[u8], iv: &[u8; 12]) -> Vec<[u8; 16]> {
+        let j0 = derive_j0(self.ghash_key(), iv);
+        ghash_trace(self.ghash_key(), self.round_keys(), &j0, aad, ciphertext)
+    }
+
+    /// **GMAC**: authenticates `aad` without encrypting anything. Equivalent to the tag of
+    /// [encrypt_gcm_with_iv](crate::Cipher::encrypt_gcm_with_iv) with empty plaintext.
+    ///
+    /// As with GCM, never reuse an IV under the same key: doing so allows tags to be forged.
+    ///
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// # use aesp::{Key, Cipher};
+    /// # let cipher = Cipher::new(&Key::rand_key_256()?);
+    /// let iv = [0x07; 12];
+    /// let tag = cipher.gmac(b"header", &iv)?;
+    /// assert!(cipher.verify_gmac(b"header", &iv, &tag).is_ok());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn gmac(&self, aad: &[u8], iv: &[u8; 12]) -> Result<[u8; 16]> {
+        self.gcm_tag(&[], aad, iv)
+    }
+
+    /// Computes the GCM tag over `aad` and an already-encrypted `ciphertext` under `iv`, without encrypting or
+    /// decrypting anything. Useful when re-framing stored GCM ciphertext whose tag was lost or kept elsewhere.
+    ///
+    /// The result equals the tag appended by [encrypt_gcm_with_iv](crate::Cipher::encrypt_gcm_with_iv) for the
+    /// same key, IV and AAD. Tags for attacker-chosen ciphertext under a used IV amount to forgeries, so only
+    /// compute tags for ciphertext this key produced.
+    ///
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// # use aesp::{Key, Cipher};
+    /// # let cipher = Cipher::new(&Key::rand_key_256()?);
+    /// let iv = [0x07; 12];
+    /// let blob = cipher.encrypt_gcm_with_iv(b"Hello, World!", Some(b"header"), &iv)?;
+    /// let parts = aesp::parse_gcm(&blob)?;
+    ///
+    /// assert_eq!(cipher.gcm_tag(&parts.ciphertext, &parts.aad, &iv)?, parts.tag);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn gcm_tag(&self, ciphertext: &[u8], aad: &[u8], iv: &[u8; 12]) -> Result<[u8; 16]> {
+        let j0 = derive_j0(self.ghash_key(), iv);
+        compute_tag_j0(ciphertext, self.round_keys(), &j0, aad, self.ghash_key(), self.parallel_threshold)
+    }
+
+    /// Verifies a [GMAC](crate::Cipher::gmac) tag in constant time. Returns [AuthFailed](crate::Error::AuthFailed)
+    /// if the tag does not match.
+    pub fn verify_gmac(&self, aad: &[u8], iv: &[u8; 12], tag: &[u8; 16]) -> Result<()> {
+        let computed_tag = self.gmac(aad, iv)?;
+        if !ct_eq(tag, &computed_tag) {
+            return Err(Error::AuthFailed);
+        }
+        Ok(())
+    }
+
+    /// **AES-CMAC** (RFC 4493, NIST SP 800-38B). Computes a 16-byte message authentication code over
+    /// `message`. Unlike [GMAC](crate::Cipher::gmac), no IV is required.
+    ///
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// # use aesp::{Key, Cipher};
+    /// # let cipher = Cipher::new(&Key::rand_key_256()?);
+    /// let tag = cipher.cmac(b"message");
+    /// assert!(cipher.verify_cmac(b"message", &tag).is_ok());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn cmac(&self, message: &[u8]) -> [u8; 16] {
+        cmac_core(message, self.round_keys())
+    }
+
+    /// Verifies a [CMAC](crate::Cipher::cmac) tag in constant time. Returns [AuthFailed](crate::Error::AuthFailed)
+    /// if the tag does not match.
+    pub fn verify_cmac(&self, message: &[u8], tag: &[u8; 16]) -> Result<()> {
+        if !ct_eq(tag, &self.cmac(message)) {
+            return Err(Error::AuthFailed);
+        }
+        Ok(())
+    }
+
+    /// **EAX** authenticated encryption, composed from [CMAC](crate::Cipher::cmac) (for the nonce, header and
+    /// ciphertext) and counter mode (for the encryption itself).
+    ///
+    /// Unlike GCM, the nonce may be any length, and the header (associated data) is authenticated but not
+    /// encrypted. Neither is included in the output, which is formatted as `Ciphertext || Tag (16 bytes)`, so
+    /// the caller is responsible for transmitting them. The same nonce must never be reused with the same key.
+    ///
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// # use aesp::{Key, Cipher};
+    /// # let cipher = Cipher::new(&Key::rand_key_256()?);
+    /// let nonce = b"any length nonce";
+    /// let sealed = cipher.encrypt_eax(b"Hello, World!", nonce, b"header");
+    /// assert_eq!(cipher.decrypt_eax(&sealed, nonce, b"header")?, b"Hello, World!");
+    ///
+    /// assert!(cipher.decrypt_eax(&sealed, nonce, b"other").is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn encrypt_eax(&self, plaintext: &[u8], nonce: &[u8], header: &[u8]) -> Vec<u8> {
+        let (mut out, tag) = eax_encrypt(plaintext, nonce, header, self.round_keys(), self.parallel_threshold);
+        out.extend_from_slice(&tag);
+        out
+    }
+
+    /// **EAX** decryption of `Ciphertext || Tag (16 bytes)`, as output by [encrypt_eax](crate::Cipher::encrypt_eax).
+    ///
+    /// Returns:
+    /// - the plaintext if the tag was authenticated.
+    /// - [AuthFailed](crate::Error::AuthFailed) error if the tag does not match the ciphertext, nonce and header.
+    /// - [InvalidCiphertext](crate::Error::InvalidCiphertext) error if the input is shorter than a tag.
+    pub fn decrypt_eax(&self, ciphertext: &[u8], nonce: &[u8], header: &[u8]) -> Result<Vec<u8>> {
+        let (ciphertext, tag) = ciphertext.split_last_chunk::<16>().ok_or(Error::InvalidCiphertext {
+            len: ciphertext.len(),
+            context: "EAX: missing 16-byte tag",
+        })?;
+        eax_decrypt(ciphertext, tag, nonce, header, self.round_keys(), self.parallel_threshold)
+    }
+
+    /// **Counter with CBC-MAC** (CCM, RFC 3610 and NIST SP 800-38C) authenticated encryption, for
+    /// interoperating with constrained protocols that use it.
+    ///
+    /// The nonce must be 7 to 13 bytes. A shorter nonce leaves more room for the message length: a 13-byte
+    /// nonce limits messages to 2^16 - 1 bytes, a 12-byte nonce to 2^24 - 1 bytes. The tag length must be
+    /// even, from 4 to 16 bytes. The AAD is authenticated but not encrypted. Neither the nonce nor the AAD is
+    /// included in the output, which is formatted as `Ciphertext || Tag (tag_len bytes)`.
+    ///
+    /// Returns:
+    /// - [InvalidNonceLength](crate::Error::InvalidNonceLength) error if the nonce is not 7 to 13 bytes.
+    /// - [InvalidTagLength](crate::Error::InvalidTagLength) error if the tag length is not supported.
+    /// - [InputTooLong](crate::Error::InputTooLong) error if the plaintext length does not fit alongside the nonce.
+    ///
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// # use aesp::{Key, Cipher};
+    /// # let cipher = Cipher::new(&Key::rand_key_128()?);
+    /// let nonce = [0x01; 13];
+    /// let sealed = cipher.encrypt_ccm(b"Hello, World!", &nonce, b"header", 8)?;
+    /// assert_eq!(sealed.len(), 13 + 8);
+    /// assert_eq!(cipher.decrypt_ccm(&sealed, &nonce, b"header", 8)?, b"Hello, World!");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn encrypt_ccm(&self, plaintext: &[u8], nonce: &[u8], aad: &[u8], tag_len: usize) -> Result<Vec<u8>> {
+        ccm_encrypt(plaintext, nonce, aad, tag_len, self.round_keys())
+    }
+
+    /// **CCM** decryption of `Ciphertext || Tag (tag_len bytes)`, as output by
+    /// [encrypt_ccm](crate::Cipher::encrypt_ccm). The nonce, AAD and tag length must match those used to encrypt.
+    ///
+    /// Returns the same errors as encryption, plus [AuthFailed](crate::Error::AuthFailed) if the tag does not
+    /// match, and [InvalidCiphertext](crate::Error::InvalidCiphertext) if the input is shorter than the tag.
+    pub fn decrypt_ccm(&self, ciphertext: &[u8], nonce: &[u8], aad: &[u8], tag_len: usize) -> Result<Vec<u8>> {
+        ccm_decrypt(ciphertext, nonce, aad, tag_len, self.round_keys())
+    }
+
+    /// Starts incremental **Galois/counter mode** encryption under a caller-supplied 12-byte IV. See
+    /// [GcmEncryptor] for usage.
+    ///
+    /// The same IV reuse warning as [encrypt_gcm_with_iv](crate::Cipher::encrypt_gcm_with_iv) applies.
+    pub fn gcm_encryptor(&self, iv: &[u8; 12]) -> GcmEncryptor<'_> {
+        GcmEncryptor::new(self, iv)
+    }
+
+    /// Starts incremental **Galois/counter mode** decryption under a 12-byte IV. See [GcmDecryptor] for
+    /// usage.
+    pub fn gcm_decryptor(&self, iv: &[u8; 12]) -> GcmDecryptor<'_> {
+        GcmDecryptor::new(self, iv)
+    }
+
+    /// **Galois/counter mode** encryption into a fixed-size record.
+    ///
+    /// Produces a record of exactly `record_len` bytes, suitable for fixed-width storage such as a database
+    /// column. The plaintext is prefixed with its length (4 bytes) and zero-padded to fill the record before
+    /// encryption, so both the true length and the padding are authenticated by the tag.
+    ///
+    /// Output is formatted as `IV (12 bytes) || AAD length (8 bytes) || AAD || Ciphertext || Tag (16 bytes)`,
+    /// where the ciphertext encrypts `Plaintext length (4 bytes) || Plaintext || Zero padding`.
+    ///
+    /// Returns an [InputTooLong](crate::Error::InputTooLong) error if the plaintext and AAD do not fit in
+    /// `record_len` bytes (a record carries 40 bytes of overhead plus the AAD).
+    ///
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// # use aesp::{Key, Cipher};
+    /// # let rk_256 = Key::rand_key_256()?;
+    /// # let cipher = Cipher::new(&rk_256);
+    /// let short = cipher.seal_fixed_record(b"abc", None, 128)?;
+    /// let long = cipher.seal_fixed_record(b"a longer message", None, 128)?;
+    /// assert_eq!(short.len(), 128);
+    /// assert_eq!(long.len(), 128);
+    ///
+    /// let (plaintext, _) = cipher.open_fixed_record(&short)?;
+    /// assert_eq!(plaintext, b"abc");
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn seal_fixed_record(
+        &self,
+        plaintext: &[u8],
+        aad: Option<&[u8]>,
+        record_len: usize,
+    ) -> Result<Vec<u8>> {
+        // overhead -> 12 (iv) + 8 (aad_len) + aad + 16 (tag) + 4 (pt_len)
+        let overhead = 40 + aad.map_or(0, |a| a.len());
+        let max = record_len.saturating_sub(overhead);
+        if overhead > record_len || plaintext.len() > max {
+            return Err(Error::InputTooLong {
+                len: plaintext.len(),
+                max,
+            });
+        }
+        let pt_len: u32 = plaintext.len().try_into().map_err(|_| Error::InputTooLong {
+            len: plaintext.len(),
+            max: u32::MAX as usize,
+        })?;
+
+        // build body: pt_len (4 bytes) || plaintext || zero padding
+        let mut body = vec![0u8; 4 + max];
+        body[..4].copy_from_slice(&pt_len.to_be_bytes());
+        body[4..4 + plaintext.len()].copy_from_slice(plaintext);
+
+        self.encrypt_gcm(&body, aad)
+    }
+
+    /// **Galois/counter mode** decryption of a fixed-size record.
+    ///
+    /// Assumes input follows the format of [seal_fixed_record](crate::Cipher::seal_fixed_record). Returns
+    /// `(plaintext, AAD)` with the length prefix and padding removed, or the same errors as
+    /// [decrypt_gcm](crate::Cipher::decrypt_gcm).
+    pub fn open_fixed_record(&self, record: &[u8]) -> Result<(Vec<u8>, Option<Vec<u8>>)> {
+        let (mut body, aad) = self.decrypt_gcm(record)?;
+        if body.len() < 4 {
+            return Err(Error::InvalidCiphertext {
+                len: record.len(),
+                context: "fixed record: missing plaintext length",
+            });
+        }
+
+        let pt_len = u32::from_be_bytes([body[0], body[1], body[2], body[3]]) as usize;
+        if pt_len > body.len() - 4 {
+            return Err(Error::InvalidCiphertext {
+                len: record.len(),
+                context: "fixed record: plaintext length exceeds record",
+            });
+        }
+
+        body.truncate(4 + pt_len);
+        body.drain(..4);
+        Ok((body, aad))
+    }
+
+    /// Overwrites the round keys with zeros. Called on drop. The key wipes itself when dropped.
+    fn wipe(&mut self) {
+        if let Some(round_keys) = self.round_keys.get_mut() {
+            zeroize(round_keys.as_flattened_mut());
+        }
+        if let Some(ghash_key) = self.ghash_key.get_mut() {
+            ghash_key.wipe();
+        }
+        if let Some(inv_round_keys) = self.inv_round_keys.get_mut() {
+            zeroize(inv_round_keys.as_flattened_mut());
+        }
+    }
+
+    /// **Key wrap with padding** (RFC 5649). Wraps key material of any length under this cipher's key
+    /// (the key-encryption key).
+    ///
+    /// Key material is zero-padded to a multiple of 8 bytes, with its length bound into the integrity
+    /// check value, so the output is `8 + ceil(len / 8) * 8` bytes. No IV is required: key wrap is
+    /// deterministic, and should only be used to protect keys or other high-entropy secrets.
+    ///
+    /// Returns [InvalidKeyWrapInput](crate::Error::InvalidKeyWrapInput) if `key_data` is empty.
+    ///
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// use aesp::{Key, Cipher};
+    ///
+    /// let kek = Cipher::new(&Key::rand_key_256()?);
+    /// let data_key = Key::rand_key_128()?;
+    ///
+    /// let wrapped = kek.wrap_key_padded(data_key.as_bytes())?;
+    /// let unwrapped = kek.unwrap_key_padded(&wrapped)?;
+    /// assert_eq!(unwrapped, data_key.as_bytes());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn wrap_key_padded(&self, key_data: &[u8]) -> Result<Vec<u8>> {
+        kwp_wrap(key_data, self.round_keys())
+    }
+
+    /// **Key unwrap with padding** (RFC 5649). Reverses [wrap_key_padded](crate::Cipher::wrap_key_padded).
+    ///
+    /// Returns [AuthFailed](crate::Error::AuthFailed) if the integrity check value, embedded length, or
+    /// padding do not verify (wrong key-encryption key, or modified input), and
+    /// [InvalidCiphertext](crate::Error::InvalidCiphertext) if the input length is not a multiple of 8 bytes
+    /// or is less than 16 bytes.
+    pub fn unwrap_key_padded(&self, wrapped: &[u8]) -> Result<Vec<u8>> {
+        kwp_unwrap(wrapped, self.round_keys())
+    }
+
+    /// Encrypts using the [Mode] selected at runtime. Output matches the corresponding mode-specific
+    /// function (e.g. [encrypt_ctr](crate::Cipher::encrypt_ctr) for [Mode::Ctr]). GCM is used without AAD.
+    ///
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// # use aesp::{Key, Cipher, Mode};
+    /// # let rk_256 = Key::rand_key_256()?;
+    /// # let cipher = Cipher::new(&rk_256);
+    /// let plaintext = ("Hello, World!").as_bytes();
+    /// for mode in [Mode::Ecb, Mode::Ctr, Mode::Gcm] {
+    ///     let ciphertext = cipher.encrypt(plaintext, mode)?;
+    ///     assert_eq!(cipher.decrypt(&ciphertext, mode)?, plaintext);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn encrypt(&self, plaintext: &[u8], mode: Mode) -> Result<Vec<u8>> {
+        match mode {
+            Mode::Ecb => Ok(self.encrypt_ecb(plaintext)),
+            Mode::Ctr => self.encrypt_ctr(plaintext),
+            Mode::Gcm => self.encrypt_gcm(plaintext, None),
+            #[cfg(feature = "testing")]
+            #[allow(deprecated)]
+            Mode::Null => {
+                let mut out = Vec::with_capacity(12 + plaintext.len());
+                out.extend_from_slice(&[0u8; 12]);
+                out.extend_from_slice(plaintext);
+                Ok(out)
+            }
+        }
+    }
+
+    /// Decrypts using the [Mode] selected at runtime. Input must match the output of
+    /// [encrypt](crate::Cipher::encrypt) for the same mode. Any AAD present in GCM input is authenticated
+    /// but discarded.
+    pub fn decrypt(&self, ciphertext: &[u8], mode: Mode) -> Result<Vec<u8>> {
+        match mode {
+            Mode::Ecb => self.decrypt_ecb(ciphertext),
+            Mode::Ctr => self.decrypt_ctr(ciphertext),
+            Mode::Gcm => Ok(self.decrypt_gcm(ciphertext)?.0),
+            #[cfg(feature = "testing")]
+            #[allow(deprecated)]
+            Mode::Null => match ciphertext.get(12..) {
+                Some(plaintext) => Ok(plaintext.to_vec()),
+                None => Err(Error::InvalidCiphertext {
+                    len: ciphertext.len(),
+                    context: "Null: missing 12-byte IV",
+                }),
+            },
+        }
+    }
+
+    /// Encrypts into a versioned, self-describing envelope:
+    /// `MAGIC (4 bytes) || Version (1 byte) || Mode (1 byte) || Payload`, where the payload is laid out as
+    /// the output of [encrypt](crate::Cipher::encrypt). [open](crate::Cipher::open) reads the mode from the
+    /// header, so callers do not need to track it, and data written by a future format version is rejected
+    /// rather than misinterpreted.
+    ///
+    /// For [Mode::Gcm], the header is authenticated along with the ciphertext (as by
+    /// [encrypt_gcm_with_header](crate::Cipher::encrypt_gcm_with_header)), so it cannot be changed without the
+    /// envelope failing to open. ECB and CTR envelopes are not authenticated at all, and are only read by
+    /// [open_unauthenticated](crate::Cipher::open_unauthenticated).
+    ///
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// # use aesp::{Key, Cipher, Mode};
+    /// # let cipher = Cipher::new(&Key::rand_key_256()?);
+    /// let blob = cipher.seal(b"Hello, World!", Mode::Gcm)?;
+    /// assert_eq!(&blob[..4], b"AESP");
+    /// assert_eq!(cipher.open(&blob)?, b"Hello, World!");
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn seal(&self, plaintext: &[u8], mode: Mode) -> Result<Vec<u8>> {
+        let header = envelope::header(mode);
+        if mode == Mode::Gcm {
+            return self.encrypt_gcm_with_header(&header, plaintext, None);
+        }
+
+        let payload = self.encrypt(plaintext, mode)?;
+        let mut blob = Vec::with_capacity(envelope::HEADER_LEN + payload.len());
+        blob.extend_from_slice(&header);
+        blob.extend_from_slice(&payload);
+        Ok(blob)
+    }
+
+    /// Decrypts an authenticated envelope produced by [seal](crate::Cipher::seal), using the mode recorded in
+    /// its header.
+    ///
+    /// Returns an InvalidEnvelope, UnsupportedEnvelopeVersion, or UnknownEnvelopeMode error if the header is
+    /// not recognised, an [UnauthenticatedEnvelope](crate::Error::UnauthenticatedEnvelope) error if it names a
+    /// mode without authentication, otherwise any error from the mode-specific decryption. Refusing
+    /// unauthenticated modes stops an attacker from rewriting the mode byte of a GCM envelope to downgrade it
+    /// to CTR, which would decrypt to attacker-influenced plaintext rather than failing.
+    pub fn open(&self, blob: &[u8]) -> Result<Vec<u8>> {
+        match envelope::parse(blob)? {
+            (Mode::Gcm, _) => Ok(self.decrypt_gcm_with_header(blob, envelope::HEADER_LEN)?.0),
+            (mode, _) => Err(Error::UnauthenticatedEnvelope { mode }),
+        }
+    }
+
+    /// Decrypts an envelope produced by [seal](crate::Cipher::seal) in any mode, including the unauthenticated
+    /// ECB and CTR modes.
+    ///
+    /// **Important**: anyone able to modify the envelope can rewrite its mode byte, so this accepts a GCM
+    /// envelope downgraded to CTR and returns plaintext the attacker can manipulate. Only use it for data whose
+    /// integrity is protected some other way; otherwise use [open](crate::Cipher::open).
+    pub fn open_unauthenticated(&self, blob: &[u8]) -> Result<Vec<u8>> {
+        match envelope::parse(blob)? {
+            (Mode::Gcm, _) => self.open(blob),
+            (mode, payload) => self.decrypt(payload, mode),
+        }
+    }
+
+    /// AES key schedule. Returns a vector of 11, 13, or 15 round keys, corresponding with AES-128, AES-192,
+    /// and AES-256, respectively. The extra round key is the initial round key, which is not counted in most
+    /// documentation as it is simply the original key.
+    fn expand_key(key: &Key) -> Vec<[u8; 16]> {
+        let mut round_keys = Vec::new();
+        Self::expand_key_into(key, &mut round_keys);
+        round_keys
+    }
+
+    /// Key schedule, written into `round_keys`, which is resized to the number of round keys for this key size.
+    fn expand_key_into(key: &Key, round_keys: &mut Vec<[u8; 16]>) {
+        let key = key.as_bytes();
+
+        // Variable names match FIPS-197, NIST specification: https://doi.org/10.6028/NIST.FIPS.197-upd1
+        // Nk   The number of 32-bit words comprising the key
+        // Nr   The number of rounds. 10, 12, and 14 for AES-128, AES-192, and AES-256, respectively
+        // w    The result of the key schedule, an array of words that form round keys
+        // Nw   The total number of words generated by the key schedule (including initial key)
+        let nk = key.len() / 4; // key size (in 4-byte words)
+        let nr = nk + 6; // number of rounds = num of words in key + 6
+        let nw = (nr + 1) * 4; // total number of words resulting from expansion
+
+        // initialise w, vector comprising 4-byte words of round_keys
+        let mut w: Vec<[u8; 4]> = vec![[0u8; 4]; nw];
+
+        // first nk words of w are filled with the initial key
+        for i in 0..key.len() {
+            w[i / 4][i % 4] = key[i];
+        }
+
+        // initialise temp variable
+        let mut temp = w[nk - 1];
+        for i in nk..nw {
+            if i % nk == 0 {
+                // calculate rot_word, sub_word, and rcon on temp
+                temp = [
+                    sub_byte(temp[1]) ^ RCON[i / nk],
+                    sub_byte(temp[2]),
+                    sub_byte(temp[3]),
+                    sub_byte(temp[0]),
+                ];
+            } else if nk == 8 && i % nk == 4 {
+                // additional substitution on temp for AES-256 only
+                temp = [
+                    sub_byte(temp[0]),
+                    sub_byte(temp[1]),
+                    sub_byte(temp[2]),
+                    sub_byte(temp[3]),
+                ];
+            }
+
+            // w[i] = temp ⊕ w[i − Nk]
+            for b in 0..4 {
+                w[i][b] = temp[b] ^ w[i - nk][b];
+            }
+            
+            temp = w[i]; // update temp
+        }
+
+        // convert words vector into indexable round_keys vector
+        round_keys.resize(nr + 1, [0u8; 16]);
+        for (round, round_key) in round_keys.iter_mut().enumerate() {
+            let base = round * 4;
+            for col in 0..4 {
+                round_key[col * 4..col * 4 + 4].copy_from_slice(&w[base + col]);
+            }
+        }
+
+        // wipe intermediate key material
+        zeroize(w.as_flattened_mut());
+    }
+}
+
+/// Converts a GCM tag length in bits to bytes, accepting only the lengths approved by NIST SP 800-38D for
+/// general use. The 32 and 64-bit tags it allows for restricted applications are not supported.
+fn gcm_tag_len(tag_bits: usize) -> Result<usize> {
+    match tag_bits {
+        96 | 104 | 112 | 120 | 128 => Ok(tag_bits / 8),
+        _ => Err(Error::InvalidTagLength { bits: tag_bits }),
+    }
+}
+
+impl PartialEq for Cipher {
+    /// Ciphers are equal if they were built from the same key (or keys), and so have identical round keys. The
+    /// round keys, cached GHASH key and inverse round keys are all derived from the key, so they are not compared
+    /// (or computed), and neither are settings such as the parallel threshold.
+    ///
+    /// The keys are compared in constant time, so equality checks do not leak how much of a key matches.
+    fn eq(&self, other: &Self) -> bool {
+        // `&` rather than `&&`, so the second key is compared even if the first differs
+        ct_eq(self.key.as_bytes(), other.key.as_bytes()) & (self.associated == other.associated)
+    }
+}
+
+impl Eq for Cipher {}
+
+impl core::fmt::Debug for Cipher {
+    /// Prints the key size only. Round keys are redacted so they cannot leak into logs.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let size = self.key.as_bytes().len() * 8;
+        f.debug_struct("Cipher")
+            .field("size", &size)
+            .field("round_keys", &format_args!("<redacted>"))
+            .finish()
+    }
+}
+
+impl Drop for Cipher {
+    /// Wipes the round keys so expanded key material does not linger in freed heap memory.
+    fn drop(&mut self) {
+        self.wipe();
+    }
+}
+
+#[cfg(feature = "test-vectors")]
+impl Cipher {
+    /// Encrypt ECB with no padding. Input must be a multiple of 16 bytes.
+    /// Only compiled when test-vectors feature is enabled.
+    pub fn encrypt_ecb_raw(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        ecb_core_enc(plaintext, self.round_keys(), self.parallel_threshold)
+    }
+
+    /// Decrypt ECB with no padding. Input must be a multiple of 16 bytes.
+    /// Only compiled when test-vectors feature is enabled.
+    pub fn decrypt_ecb_raw(&self, ciphertext: &[u8]) -> Result<Vec<u8>> {
+        ecb_core_dec(ciphertext, self.inv_round_keys(), self.parallel_threshold)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aesp::error::ErrorKind;
+    use crate::aesp::util::decode_hex;
+
+    #[test]
+    fn key_schedule_128() -> Result<()> {
+        // run key schedule on 128 bit sample key from FIPS-197 Appendix A.1
+        let key_128: [u8; 16] = [
+            0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf,
+            0x4f, 0x3c,
+        ];
+
+        let key = Key::try_from_slice(&key_128)?;
+        let round_keys = Cipher::expand_key(&key);
+        let last = *round_keys.last().expect("round_keys should not be empty");
+
+        // compare with last round key of sample schedule in A.1
+        let expected: [u8; 16] = [
+            0xd0, 0x14, 0xf9, 0xa8, 0xc9, 0xee, 0x25, 0x89, 0xe1, 0x3f, 0x0c, 0xc8, 0xb6, 0x63,
+            0x0c, 0xa6,
+        ];
+
+        assert_eq!(last, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn key_schedule_192() -> Result<()> {
+        // run key schedule on 192 bit sample key from FIPS-197 Appendix A.2
+        let key_192: [u8; 24] = [
+            0x8e, 0x73, 0xb0, 0xf7, 0xda, 0x0e, 0x64, 0x52, 0xc8, 0x10, 0xf3, 0x2b, 0x80, 0x90,
+            0x79, 0xe5, 0x62, 0xf8, 0xea, 0xd2, 0x52, 0x2c, 0x6b, 0x7b,
+        ];
+
+        let key = Key::try_from_slice(&key_192)?;
+        let round_keys = Cipher::expand_key(&key);
+        let last = *round_keys.last().expect("round_keys should not be empty");
+
+        // compare with last round key of sample schedule in A.2
+        let expected: [u8; 16] = [
+            0xe9, 0x8b, 0xa0, 0x6f, 0x44, 0x8c, 0x77, 0x3c, 0x8e, 0xcc, 0x72, 0x04, 0x01, 0x00,
+            0x22, 0x02,
+        ];
+
+        assert_eq!(last, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn key_schedule_256() -> Result<()> {
+        // run key schedule on 256 bit sample key from FIPS-197 Appendix A.3
+        let key_256: [u8; 32] = [
+            0x60, 0x3d, 0xeb, 0x10, 0x15, 0xca, 0x71, 0xbe, 0x2b, 0x73, 0xae, 0xf0, 0x85, 0x7d,
+            0x77, 0x81, 0x1f, 0x35, 0x2c, 0x07, 0x3b, 0x61, 0x08, 0xd7, 0x2d, 0x98, 0x10, 0xa3,
+            0x09, 0x14, 0xdf, 0xf4,
+        ];
+
+        let key = Key::try_from_slice(&key_256)?;
+        let round_keys = Cipher::expand_key(&key);
+        let last = *round_keys.last().expect("round_keys should not be empty");
+
+        // compare with last round key of sample schedule in A.3
+        let expected: [u8; 16] = [
+            0xfe, 0x48, 0x90, 0xd1, 0xe6, 0x18, 0x8d, 0x0b, 0x04, 0x6d, 0xf3, 0x44, 0x70, 0x6c,
+            0x63, 0x1e,
+        ];
+
+        assert_eq!(last, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn rekey_matches_new_cipher() -> Result<()> {
+        let (old_key, new_key) = (Key::rand_key_256()?, Key::rand_key_128()?);
+        let mut cipher = Cipher::new(&old_key).with_parallel_threshold(0);
+
+        // populate the cached GHASH key and inverse round keys before rekeying
+        let old_gcm = cipher.encrypt_gcm(b"Hello, World!", None)?;
+        let old_ecb = cipher.encrypt_ecb(b"Hello, World!");
+        cipher.decrypt_ecb(&old_ecb)?;
+
+        cipher.rekey(&new_key);
+        let fresh = Cipher::new(&new_key);
+        assert_eq!(cipher.round_keys(), fresh.round_keys());
+        assert_eq!(cipher.parallel_threshold(), 0);
+
+        let iv = [0x42; 12];
+        assert_eq!(
+            cipher.encrypt_gcm_with_iv(b"Hello, World!", None, &iv)?,
+            fresh.encrypt_gcm_with_iv(b"Hello, World!", None, &iv)?
+        );
+        assert_eq!(cipher.decrypt_ecb(&fresh.encrypt_ecb(b"Hello, World!"))?, b"Hello, World!");
+
+        // ciphertext under the old key no longer decrypts
+        assert!(matches!(cipher.decrypt_gcm(&old_gcm), Err(Error::AuthFailed)));
+        Ok(())
+    }
+
+    #[test]
+    fn two_key_schedules() -> Result<()> {
+        let (k1, k2) = (Key::rand_key_256()?, Key::rand_key_128()?);
+        let cipher = Cipher::from_two_keys(&k1, &k2);
+        let associated = cipher.associated().unwrap();
+
+        assert_eq!(cipher.round_keys(), Cipher::new(&k1).round_keys());
+        assert_eq!(associated.round_keys(), Cipher::new(&k2).round_keys());
+        assert_ne!(cipher.round_keys(), associated.round_keys());
+        assert!(associated.associated().is_none());
+        assert!(Cipher::new(&k1).associated().is_none());
+
+        // the second key takes part in equality, and survives cloning
+        assert_ne!(cipher, Cipher::new(&k1));
+        assert_eq!(cipher.clone(), cipher);
+        Ok(())
+    }
+
+    #[test]
+    fn gcm_aad_length_field_is_u64() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_128()?);
+        let out = cipher.encrypt_gcm_with_iv(b"pt", Some(b"aad"), &[0u8; 12])?;
+        assert_eq!(out[12..20], 3u64.to_be_bytes());
+
+        // lengths beyond the remaining input, including ones that do not fit in usize, are rejected
+        for aad_len in [6, 1 << 32, u64::MAX] {
+            let mut tampered = out.clone();
+            tampered[12..20].copy_from_slice(&aad_len.to_be_bytes());
+            assert!(matches!(
+                cipher.decrypt_gcm(&tampered),
+                Err(Error::InvalidCiphertext { .. })
+            ));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn detailed_gcm_auth_failure_reports_processed_bytes() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_256()?);
+        for (len, aad) in [(0, None), (1, Some(&b"aad"[..])), (100, None), (4096, Some(&[0x5a; 40][..]))] {
+            let mut blob = cipher.encrypt_gcm(&vec![0x42; len], aad)?;
+            assert!(cipher.decrypt_gcm_detailed(&blob).is_ok());
+
+            // a flipped bit anywhere reports the whole ciphertext as processed
+            for i in [0, 20, blob.len() - 1] {
+                blob[i] ^= 1;
+                let err = cipher.decrypt_gcm_detailed(&blob).unwrap_err();
+                assert!(matches!(err, Error::AuthFailedAfter { processed } if processed == len));
+                assert_eq!(err.kind(), ErrorKind::Auth);
+                assert!(matches!(cipher.decrypt_gcm(&blob), Err(Error::AuthFailed)));
+                blob[i] ^= 1;
+            }
+        }
+
+        // format errors are passed through unchanged
+        let err = cipher.decrypt_gcm_detailed(&[0u8; 20]).unwrap_err();
+        assert!(matches!(err, Error::InvalidCiphertext { .. }));
+        Ok(())
+    }
+
+    #[test]
+    fn gcm_into_matches_allocating_path() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_256()?).with_parallel_threshold(64);
+        let iv = [0x42; 12];
+
+        // one buffer across messages of varying size, starting with stale contents
+        let mut buf = vec![0xff; 300];
+        for len in [0, 1, 16, 17, 200, 1000, 5] {
+            let plaintext = vec![0x5a; len];
+            for aad in [None, Some(&b"header"[..])] {
+                cipher.encrypt_gcm_into(&plaintext, aad, &iv, &mut buf)?;
+                assert_eq!(buf, cipher.encrypt_gcm_with_iv(&plaintext, aad, &iv)?);
+            }
+        }
+
+        // IV checks happen before the buffer is touched
+        let cipher = cipher.reject_weak_ivs(true);
+        let before = buf.clone();
+        assert!(matches!(cipher.encrypt_gcm_into(b"pt", None, &[0; 12], &mut buf), Err(Error::WeakNonce)));
+        assert_eq!(buf, before);
+        Ok(())
+    }
+
+    #[test]
+    fn nonce_tracking_rejects_reuse() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_128()?).with_nonce_tracking();
+        let iv = [0x01; 12];
+
+        cipher.encrypt_gcm_with_iv(b"first", None, &iv)?;
+        assert!(matches!(cipher.encrypt_gcm_with_iv(b"second", None, &iv), Err(Error::NonceReused)));
+        // CTR shares the record, as its counter blocks overlap GCM's under the same IV
+        assert!(matches!(cipher.encrypt_ctr_with_iv(b"second", &iv), Err(Error::NonceReused)));
+        cipher.encrypt_gcm_with_iv(b"second", None, &[0x02; 12])?;
+
+        // clones share the record; random IVs are recorded but never collide in practice
+        let clone = cipher.clone();
+        assert!(matches!(clone.encrypt_gcm_with_iv(b"third", None, &[0x02; 12]), Err(Error::NonceReused)));
+        for _ in 0..100 {
+            clone.encrypt_gcm(b"random", None)?;
+        }
+
+        // off by default, and cleared by rekeying
+        let untracked = Cipher::new(&Key::rand_key_128()?);
+        untracked.encrypt_gcm_with_iv(b"first", None, &iv)?;
+        untracked.encrypt_gcm_with_iv(b"second", None, &iv)?;
+        let mut cipher = cipher;
+        cipher.rekey(&Key::rand_key_128()?);
+        cipher.encrypt_gcm_with_iv(b"first", None, &iv)?;
+        assert!(matches!(cipher.encrypt_gcm_with_iv(b"second", None, &iv), Err(Error::NonceReused)));
+        Ok(())
+    }
+
+    #[test]
+    fn weak_iv_rejection_toggle() -> Result<()> {
+        let key = Key::rand_key_256()?;
+        let listed = [0x5a; 12];
+        let mut buf = [0u8; 4];
+
+        // off by default: weak IVs are accepted
+        let cipher = Cipher::new(&key).with_weak_iv_blocklist(&[listed]);
+        cipher.encrypt_gcm_with_iv(b"data", None, &[0; 12])?;
+        cipher.encrypt_ctr_with_iv(b"data", &listed)?;
+
+        let cipher = cipher.reject_weak_ivs(true);
+        for iv in [[0; 12], listed] {
+            assert!(matches!(cipher.encrypt_gcm_with_iv(b"data", None, &iv), Err(Error::WeakNonce)));
+            assert!(matches!(cipher.encrypt_ctr_with_iv(b"data", &iv), Err(Error::WeakNonce)));
+            assert!(matches!(cipher.encrypt_ctr_in_place(&iv, &mut buf), Err(Error::WeakNonce)));
+        }
+        assert!(matches!(cipher.encrypt_gcm_with_iv_slice(b"data", None, &[0; 8]), Err(Error::WeakNonce)));
+        assert!(matches!(cipher.encrypt_ctr64_with_iv(b"data", &[0; 8]), Err(Error::WeakNonce)));
+
+        // other IVs, random IVs, and decryption of existing ciphertext are unaffected
+        cipher.encrypt_gcm_with_iv(b"data", None, &[0x01; 12])?;
+        let ct = Cipher::new(&key).encrypt_gcm_with_iv(b"data", None, &[0; 12])?;
+        assert_eq!(cipher.decrypt_gcm(&ct)?.0, b"data");
+        assert_eq!(cipher.decrypt_gcm(&cipher.encrypt_gcm(b"data", None)?)?.0, b"data");
+
+        let cipher = cipher.reject_weak_ivs(false);
+        cipher.encrypt_gcm_with_iv(b"data", None, &[0; 12])?;
+        Ok(())
+    }
+
+    #[test]
+    fn gcm_rejects_empty_iv() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_128()?);
+        assert!(matches!(
+            cipher.encrypt_gcm_with_iv_slice(b"Hello, World!", None, &[]),
+            Err(Error::InvalidNonceLength { len: 0 })
+        ));
+
+        // valid framing with a zero-length IV, and a tag that would otherwise be checked
+        let blob = [0u8; 8 + 16];
+        assert!(matches!(cipher.decrypt_gcm_with_iv_len(&blob, 0), Err(Error::InvalidNonceLength { len: 0 })));
+        Ok(())
+    }
+
+    #[test]
+    fn gcm_frame_ranges_match_parts() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_256()?);
+        for (plaintext, aad) in [(&b"Hello, World!"[..], &b"header"[..]), (b"", b""), (&[0x42; 100], b"")] {
+            let (blob, frame) = cipher.encrypt_gcm_framed(plaintext, Some(aad))?;
+            let parts = parse_gcm(&blob)?;
+
+            assert_eq!(frame.iv_range, 0..12);
+            assert_eq!(blob[frame.iv_range.clone()], parts.iv);
+            assert_eq!(frame.aad_range.start, frame.iv_range.end + 8);
+            assert_eq!(blob[frame.aad_range.clone()], parts.aad);
+            assert_eq!(blob[frame.ct_range.clone()], parts.ciphertext);
+            assert_eq!(frame.ct_range.len(), plaintext.len());
+            assert_eq!(blob[frame.tag_range.clone()], parts.tag);
+            assert_eq!(frame.tag_range.end, blob.len());
+
+            let tag: &[u8; 16] = blob[frame.tag_range].try_into().unwrap();
+            let iv: &[u8; 12] = blob[frame.iv_range].try_into().unwrap();
+            let decrypted = cipher.decrypt_gcm_with_aad(iv, &blob[frame.ct_range], tag, &blob[frame.aad_range])?;
+            assert_eq!(decrypted, plaintext);
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "base64")]
+    #[test]
+    fn ctr_b64_round_trip() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_128()?);
+        let encoded = cipher.encrypt_ctr_b64(b"text-safe")?;
+        assert!(encoded.bytes().all(|b| b.is_ascii_alphanumeric() || b"+/=".contains(&b)));
+        assert_eq!(decode_b64(&encoded)?.len(), 12 + 9);
+        assert_eq!(cipher.decrypt_ctr_b64(&encoded)?, b"text-safe");
+
+        let mut mangled = encoded.clone();
+        mangled.insert(4, '*');
+        assert!(matches!(cipher.decrypt_ctr_b64(&mangled), Err(Error::InvalidBase64 { .. })));
+        Ok(())
+    }
+
+    #[test]
+    fn seeded_rng_gives_reproducible_ivs() -> Result<()> {
+        use crate::aesp::util::generate_iv_with_rng;
+        use crate::aesp::key::KeySize;
+        use rand_chacha::ChaCha20Rng;
+        use rand_chacha::rand_core::SeedableRng;
+
+        for seed in 0..16 {
+            let mut rng = ChaCha20Rng::seed_from_u64(seed);
+            let cipher = Cipher::new(&Key::generate_with_rng(&mut rng, KeySize::Bits128)?);
+            let (iv_a, iv_b) = (generate_iv_with_rng(&mut rng)?, generate_iv_with_rng(&mut rng)?);
+            assert_ne!(iv_a, iv_b);
+
+            let pt = vec![seed as u8; seed as usize * 7];
+            let gcm = cipher.encrypt_gcm_with_iv(&pt, Some(b"aad"), &iv_a)?;
+            let ctr = cipher.encrypt_ctr_with_iv(&pt, &iv_a)?;
+            assert_eq!(gcm[..12], iv_a);
+
+            // replaying the seed reproduces the key, IVs and ciphertext exactly
+            let mut replay = ChaCha20Rng::seed_from_u64(seed);
+            let replay_cipher = Cipher::new(&Key::generate_with_rng(&mut replay, KeySize::Bits128)?);
+            let replay_iv = generate_iv_with_rng(&mut replay)?;
+            assert_eq!(replay_cipher.encrypt_gcm_with_iv(&pt, Some(b"aad"), &replay_iv)?, gcm);
+            assert_eq!(replay_cipher.encrypt_ctr_with_iv(&pt, &replay_iv)?, ctr);
+
+            assert_eq!(cipher.decrypt_gcm(&gcm)?.0, pt);
+            assert_eq!(cipher.decrypt_ctr_with_iv(&ctr, &iv_a)?, pt);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn fixed_record_fits() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_128()?);
+        let aad = b"header";
+
+        // plaintext exactly filling the record (40 bytes overhead + 6 bytes aad)
+        let exact = [0xAB; 64 - 46];
+        for pt in [&b""[..], b"short", &exact] {
+            let record = cipher.seal_fixed_record(pt, Some(aad), 64)?;
+            assert_eq!(record.len(), 64);
+
+            let (decrypted, returned_aad) = cipher.open_fixed_record(&record)?;
+            assert_eq!(decrypted, pt);
+            assert_eq!(returned_aad, Some(aad.to_vec()));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn fixed_record_no_fit() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_128()?);
+
+        let res = cipher.seal_fixed_record(&[0u8; 25], None, 64);
+        assert!(matches!(res, Err(Error::InputTooLong { len: 25, max: 24 })));
+
+        // record too small to hold the overhead at all
+        let res = cipher.seal_fixed_record(&[], Some(b"aad"), 20);
+        assert!(matches!(res, Err(Error::InputTooLong { len: 0, max: 0 })));
+        Ok(())
+    }
+
+    #[test]
+    fn ctr_sp800_38a_through_public_api() -> Result<()> {
+        // SP 800-38A F.5.1 and F.5.5: initial counter block f0f1...feff
+        let plaintext = decode_hex(
+            "6bc1bee22e409f96e93d7e117393172a ae2d8a571e03ac9c9eb76fac45af8e51
+             30c81c46a35ce411e5fbc1191a0a52ef f69f2445df4f9b17ad2b417be66c3710",
+        )?;
+        let iv: [u8; 12] = decode_hex("f0f1f2f3f4f5f6f7f8f9fafb")?.try_into().unwrap();
+        let cases = [
+            (
+                "2b7e151628aed2a6abf7158809cf4f3c",
+                "874d6191b620e3261bef6864990db6ce 9806f66b7970fdff8617187bb9fffdff
+                 5ae4df3edbd5d35e5b4f09020db03eab 1e031dda2fbe03d1792170a0f3009cee",
+            ),
+            (
+                "603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4",
+                "601ec313775789a5b7a7f504bbf3d228 f443e3ca4d62b59aca84e990cacaf5c5
+                 2b0930daa23de94ce87017ba2d84988d dfc9c58db67aada613c2dd08457941a6",
+            ),
+        ];
+
+        for (key, expected) in cases {
+            let cipher = Cipher::new(&Key::try_from_slice(&decode_hex(key)?)?);
+            let expected = decode_hex(expected)?;
+            assert_eq!(cipher.ctr_with_counter(&plaintext, &iv, 0xfcfdfeff)?, expected);
+            assert_eq!(cipher.ctr_with_counter(&expected, &iv, 0xfcfdfeff)?, plaintext);
+        }
+
+        // the caller-managed IV pair starts at counter 0, so cannot reproduce these vectors directly, but is
+        // the same keystream as ctr_with_counter from 0, and round trips without an IV prefix
+        let cipher = Cipher::new(&Key::rand_key_128()?);
+        let ciphertext = cipher.encrypt_ctr_with_iv(&plaintext, &iv)?;
+        assert_eq!(ciphertext, cipher.ctr_with_counter(&plaintext, &iv, 0)?);
+        assert_eq!(cipher.decrypt_ctr_with_iv(&ciphertext, &iv)?, plaintext);
+        assert_eq!(cipher.decrypt_ctr_with_iv(&cipher.encrypt_ctr_with_iv(&[], &iv)?, &iv)?, b"");
+        Ok(())
+    }
+
+    #[test]
+    fn single_block_fips_197() -> Result<()> {
+        // FIPS-197 Appendix C.1: AES-128
+        let key: Vec<u8> = (0..16).collect();
+        let plaintext: [u8; 16] = [
+            0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, //
+            0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff, //
+        ];
+        let ciphertext: [u8; 16] = [
+            0x69, 0xc4, 0xe0, 0xd8, 0x6a, 0x7b, 0x04, 0x30, //
+            0xd8, 0xcd, 0xb7, 0x80, 0x70, 0xb4, 0xc5, 0x5a, //
+        ];
+
+        let cipher = Cipher::new(&Key::try_from_slice(&key)?);
+        assert_eq!(cipher.encrypt_block(&plaintext), ciphertext);
+        assert_eq!(cipher.decrypt_block(&ciphertext), plaintext);
+        Ok(())
+    }
+
+    #[test]
+    fn lenient_decryption_keeps_invalid_padding() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_128()?);
+        let iv = [0x0f; 16];
+
+        // a final block ending in 0x00 is never valid PKCS#7
+        let mut padded = b"recoverable data".to_vec();
+        padded.extend_from_slice(b"bad padding\x05\x05\x05\x05\x00");
+        let ecb = cipher.encrypt_ecb_blocks(padded.chunks_exact(16).map(|b| b.try_into().unwrap()));
+        let ecb = ecb.as_flattened();
+        assert!(cipher.decrypt_ecb(ecb).is_err());
+        assert_eq!(cipher.decrypt_ecb_lenient(ecb)?, padded);
+
+        // CBC, through the padded encryptor with its padding block cut off
+        let cbc = cipher.encrypt_cbc_with_iv(&padded, &iv)?;
+        let cbc = &cbc[..32];
+        assert!(cipher.decrypt_cbc_with_iv(cbc, &iv).is_err());
+        assert_eq!(cipher.decrypt_cbc_lenient(cbc, &iv)?, padded);
+
+        // valid ciphertext keeps its padding
+        assert_eq!(cipher.decrypt_ecb_lenient(&cipher.encrypt_ecb(&[]))?, [16u8; 16]);
+        assert!(matches!(cipher.decrypt_ecb_lenient(&[0u8; 15]), Err(Error::InvalidECBInput { len: 15 })));
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "debug")]
+    fn ecb_debug_reports_final_byte() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_256()?);
+
+        // a single block ending in 0x2a, which is not valid PKCS#7 padding
+        let block = *b"final byte is *\x2a";
+        let ciphertext = cipher.encrypt_ecb_blocks([block]);
+        let ciphertext = ciphertext.as_flattened();
+        assert!(cipher.decrypt_ecb(ciphertext).is_err());
+        assert_eq!(cipher.decrypt_ecb_debug(ciphertext)?, (block.to_vec(), 0x2a));
+
+        // valid PKCS#7 reports the padding length
+        assert_eq!(cipher.decrypt_ecb_debug(&cipher.encrypt_ecb(b"Hello, World!"))?.1, 3);
+        assert!(matches!(cipher.decrypt_ecb_debug(&[]), Err(Error::InvalidECBInput { len: 0 })));
+        Ok(())
+    }
+
+    #[test]
+    fn ecb_blocks_match_slice_path() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_192()?);
+        let plaintext: Vec<u8> = (0..160).map(|i| i as u8).collect();
+        // the padded slice path appends a block of PKCS#7 padding to the aligned input
+        let expected = &cipher.encrypt_ecb(&plaintext)[..160];
+
+        let blocks = plaintext.chunks_exact(16).map(|b| b.try_into().unwrap());
+        let encrypted = cipher.encrypt_ecb_blocks(blocks);
+        assert_eq!(encrypted.as_flattened(), expected);
+        assert_eq!(cipher.decrypt_ecb_blocks(encrypted).as_flattened(), plaintext);
+        assert!(cipher.encrypt_ecb_blocks([]).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn key_check_value() -> Result<()> {
+        // E(K, 0^128) for the FIPS-197 Appendix A.1 key is 7df76b0c1ab899b33e42f047b91b546f
+        let key: [u8; 16] = [
+            0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf,
+            0x4f, 0x3c,
+        ];
+        let cipher = Cipher::new(&Key::try_from_slice(&key)?);
 
-        // extract AAD len and validate remaining size
-        let (aad_len, ciphertext) = ciphertext.split_at(4);
-        let aad_len = u32::from_be_bytes([aad_len[0], aad_len[1], aad_len[2], aad_len[3]]);
-        if ciphertext.len() < aad_len as usize + 16 {
-            return Err(Error::InvalidCiphertext {
-                len: ciphertext.len(),
-                context: "insufficient bytes given aad_len",
-            });
+        assert_eq!(cipher.key_check_value(), [0x7d, 0xf7, 0x6b]);
+        assert!(cipher.verify_kcv(&[0x7d, 0xf7, 0x6b]));
+        assert!(!cipher.verify_kcv(&[0x7d, 0xf7, 0x6c]));
+        assert!(!cipher.verify_kcv(&[0x66, 0xe9, 0x4b]));
+        Ok(())
+    }
+
+    #[test]
+    fn reports_hardware_acceleration() {
+        let accelerated = Cipher::supports_hardware_acceleration();
+        #[cfg(target_arch = "x86_64")]
+        assert_eq!(accelerated, std::arch::is_x86_feature_detected!("aes"));
+        #[cfg(not(target_arch = "x86_64"))]
+        assert!(!accelerated);
+    }
+
+    #[test]
+    fn drop_wipes_round_keys() -> Result<()> {
+        let mut cipher = Cipher::new(&Key::rand_key_256()?);
+        assert!(cipher.round_keys().iter().any(|rk| rk != &[0u8; 16]));
+
+        // the round key vector is freed after drop, so run the wipe directly and inspect
+        cipher.wipe();
+        assert!(cipher.round_keys().iter().all(|rk| rk == &[0u8; 16]));
+        Ok(())
+    }
+
+    #[test]
+    fn gcm_rejects_any_tag_byte_modification() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_128()?);
+        let ciphertext = cipher.encrypt_gcm(b"Hello, World!", Some(b"aad"))?;
+
+        let tag_start = ciphertext.len() - 16;
+        for i in tag_start..ciphertext.len() {
+            let mut tampered = ciphertext.clone();
+            tampered[i] ^= 0x80;
+            assert!(matches!(cipher.decrypt_gcm(&tampered), Err(Error::AuthFailed)));
         }
+        Ok(())
+    }
 
-        // extract aad and save in vector
-        let (aad, ciphertext) = ciphertext.split_at(aad_len as usize);
-        let aad = aad.to_vec();
+    #[test]
+    fn gcm_header_is_authenticated() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_128()?);
+        let iv = [0x11; 12];
+        let header = b"v1|kid=7";
 
-        // extract tag and format as [u8; 16]
-        let mut received_tag = [0u8; 16];
-        let (ct, tag_bytes) = ciphertext.split_at(ciphertext.len() - 16);
-        received_tag.copy_from_slice(tag_bytes);
+        let blob = cipher.encrypt_gcm_with_header_and_iv(header, b"Hello, World!", Some(b"aad"), &iv)?;
+        assert_eq!(blob[..8], header[..]);
+        assert_eq!(blob[8..20], iv);
+        assert_eq!(blob[20..28], 3u64.to_be_bytes());
+        let (pt, aad) = cipher.decrypt_gcm_with_header(&blob, header.len())?;
+        assert_eq!((pt, aad), (b"Hello, World!".to_vec(), Some(b"aad".to_vec())));
 
-        // compute and compare tag
-        let computed_tag = compute_tag(ct, &self.round_keys, &iv, &aad)?;
-        if received_tag != computed_tag {
-            return Err(Error::AuthFailed);
+        // an empty header is plain framed GCM
+        let plain = cipher.encrypt_gcm_with_iv(b"Hello, World!", Some(b"aad"), &iv)?;
+        assert_eq!(cipher.encrypt_gcm_with_header_and_iv(&[], b"Hello, World!", Some(b"aad"), &iv)?, plain);
+
+        // flipping any header byte fails authentication
+        for i in 0..header.len() {
+            let mut tampered = blob.clone();
+            tampered[i] ^= 1;
+            assert!(matches!(cipher.decrypt_gcm_with_header(&tampered, header.len()), Err(Error::AuthFailed)));
         }
 
-        // wrap AAD in option
-        let aad = if !aad.is_empty() { Some(aad) } else { None };
+        // the header cannot be dropped or moved into the AAD
+        assert!(cipher.decrypt_gcm(&blob[header.len()..]).is_err());
+        assert!(cipher.decrypt_gcm_with_header(&blob, header.len() - 1).is_err());
+        assert!(matches!(
+            cipher.decrypt_gcm_with_header(&blob[..4], header.len()),
+            Err(Error::InvalidCiphertext { .. })
+        ));
 
-        // run ctr starting at 2, as per NIST spec
-        let plaintext = ctr_core(ct, &self.round_keys, &iv, 2)?;
-        Ok((plaintext, aad))
+        // random IV round trip
+        let blob = cipher.encrypt_gcm_with_header(header, b"Hello, World!", None)?;
+        assert_eq!(cipher.decrypt_gcm_with_header(&blob, header.len())?.0, b"Hello, World!");
+        Ok(())
     }
 
-    /// AES key schedule. Returns a vector of 11, 13, or 15 round keys, corresponding with AES-128, AES-192,
-    /// and AES-256, respectively. The extra round key is the initial round key, which is not counted in most
-    /// documentation as it is simply the original key.
-    fn expand_key(key: &Key) -> Vec<[u8; 16]> {
-        let key = key.as_bytes();
+    #[test]
+    fn gcm_format_errors_are_distinct_from_auth_failure() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_256()?);
+        let blob = cipher.encrypt_gcm(b"Hello, World!", Some(b"aad"))?;
 
-        // Variable names match FIPS-197, NIST specification: https://doi.org/10.6028/NIST.FIPS.197-upd1
-        // Nk   The number of 32-bit words comprising the key
-        // Nr   The number of rounds. 10, 12, and 14 for AES-128, AES-192, and AES-256, respectively
-        // w    The result of the key schedule, an array of words that form round keys
-        // Nw   The total number of words generated by the key schedule (including initial key)
-        let nk = key.len() / 4; // key size (in 4-byte words)
-        let nr = nk + 6; // number of rounds = num of words in key + 6
-        let nw = (nr + 1) * 4; // total number of words resulting from expansion
+        // truncated blob: shorter than IV + AAD length + tag
+        let err = cipher.decrypt_gcm(&blob[..35]).unwrap_err();
+        assert!(matches!(err, Error::InvalidCiphertext { .. }));
+        assert_eq!(err.kind(), ErrorKind::Format);
 
-        // initialise w, vector comprising 4-byte words of round_keys
-        let mut w: Vec<[u8; 4]> = vec![[0u8; 4]; nw];
+        // AAD length field larger than the rest of the blob
+        let mut oversized = blob.clone();
+        oversized[12..20].copy_from_slice(&(blob.len() as u64).to_be_bytes());
+        let err = cipher.decrypt_gcm(&oversized).unwrap_err();
+        assert!(matches!(err, Error::InvalidCiphertext { .. }));
+        assert_eq!(err.kind(), ErrorKind::Format);
 
-        // first nk words of w are filled with the initial key
-        for i in 0..key.len() {
-            w[i / 4][i % 4] = key[i];
+        // valid framing, flipped tag: the error is the same wherever the tag differs
+        let mut first = blob.clone();
+        first[blob.len() - 16] ^= 1;
+        let mut last = blob.clone();
+        last[blob.len() - 1] ^= 1;
+        let (first, last) = (cipher.decrypt_gcm(&first).unwrap_err(), cipher.decrypt_gcm(&last).unwrap_err());
+        assert!(matches!(first, Error::AuthFailed));
+        assert_eq!(first.kind(), ErrorKind::Auth);
+        assert_eq!(first.to_string(), last.to_string());
+        Ok(())
+    }
+
+    #[test]
+    fn ctr_in_place_matches_allocating() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_256()?);
+
+        // sizes either side of the parallel threshold and with partial final blocks
+        for len in [0, 1, 15, 16, 17, 4096, 4097, 10_000] {
+            let plaintext: Vec<u8> = (0..len).map(|i| i as u8).collect();
+            let ciphertext = cipher.encrypt_ctr(&plaintext)?;
+
+            let expected = cipher.decrypt_ctr(&ciphertext)?;
+            let mut buf = ciphertext.clone();
+            let decrypted = cipher.decrypt_ctr_in_place(&mut buf)?;
+
+            assert_eq!(decrypted, expected.as_slice());
+            assert_eq!(decrypted, plaintext.as_slice());
         }
 
-        // initialise temp variable
-        let mut temp = w[nk - 1];
-        for i in nk..nw {
-            if i % nk == 0 {
-                // calculate rot_word, sub_word, and rcon on temp
-                temp = [
-                    SBOX[temp[1] as usize] ^ RCON[i / nk],
-                    SBOX[temp[2] as usize],
-                    SBOX[temp[3] as usize],
-                    SBOX[temp[0] as usize],
-                ];
-            } else if nk == 8 && i % nk == 4 {
-                // additional substitution on temp for AES-256 only
-                temp = [
-                    SBOX[temp[0] as usize],
-                    SBOX[temp[1] as usize],
-                    SBOX[temp[2] as usize],
-                    SBOX[temp[3] as usize],
-                ];
-            }
+        assert!(cipher.decrypt_ctr_in_place(&mut [0u8; 11]).is_err());
+        Ok(())
+    }
 
-            // w[i] = temp ⊕ w[i − Nk]
-            for b in 0..4 {
-                w[i][b] = temp[b] ^ w[i - nk][b];
-            }
-            
-            temp = w[i]; // update temp
+    #[test]
+    fn cbc_sp800_38a_through_public_api() -> Result<()> {
+        // SP 800-38A F.2.1 and F.2.5: the four unpadded blocks, followed here by a block of PKCS#7 padding
+        let plaintext = decode_hex(
+            "6bc1bee22e409f96e93d7e117393172a ae2d8a571e03ac9c9eb76fac45af8e51
+             30c81c46a35ce411e5fbc1191a0a52ef f69f2445df4f9b17ad2b417be66c3710",
+        )?;
+        let iv: [u8; 16] = core::array::from_fn(|i| i as u8);
+        let cases = [
+            (
+                "2b7e151628aed2a6abf7158809cf4f3c",
+                "7649abac8119b246cee98e9b12e9197d 5086cb9b507219ee95db113a917678b2
+                 73bed6b8e3c1743b7116e69e22229516 3ff1caa1681fac09120eca307586e1a7",
+            ),
+            (
+                "603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4",
+                "f58c4c04d6e5f1ba779eabfb5f7bfbd6 9cfc4e967edb808d679f777bc6702c7d
+                 39f23369a9d9bacfa530e26304231461 b2eb05e2c39be9fcda6c19078c6a9d1b",
+            ),
+        ];
+
+        for (key, expected) in cases {
+            let cipher = Cipher::new(&Key::try_from_hex(key)?);
+            let ciphertext = cipher.encrypt_cbc_with_iv(&plaintext, &iv)?;
+            assert_eq!(ciphertext.len(), 80);
+            assert_eq!(ciphertext[..64], decode_hex(expected)?);
+            assert_eq!(cipher.decrypt_cbc_with_iv(&ciphertext, &iv)?, plaintext);
+
+            // the IV only affects the first block
+            let other = cipher.decrypt_cbc_with_iv(&ciphertext, &[0xff; 16])?;
+            assert_ne!(other[..16], plaintext[..16]);
+            assert_eq!(other[16..], plaintext[16..]);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn ctr_range_matches_full_decryption() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_256()?).with_parallel_threshold(64);
+        let iv = [0x5c; 12];
+        let plaintext: Vec<u8> = (0..1000).map(|i| i as u8).collect();
+        let ciphertext = cipher.encrypt_ctr_with_iv(&plaintext, &iv)?;
+
+        for (start, end) in [(0, 1000), (0, 5), (7, 9), (16, 32), (15, 17), (100, 333), (999, 1000), (500, 500)] {
+            assert_eq!(cipher.decrypt_ctr_range(&ciphertext[start..end], &iv, start)?, plaintext[start..end]);
         }
 
-        // convert words vector into indexable round_keys vector
-        let mut round_keys = vec![[0u8; 16]; nr + 1];
-        for round in 0..=nr {
-            let base = round * 4;
-            for col in 0..4 {
-                let word = w[base + col];
-                for row in 0..4 {
-                    round_keys[round][col * 4 + row] = word[row];
-                }
+        // a range starting past 2^32 blocks cannot be addressed by the 32-bit counter
+        let err = cipher.decrypt_ctr_range(b"x", &iv, (1usize << 32) * 16);
+        assert!(matches!(err, Err(Error::CounterOverflow)));
+        Ok(())
+    }
+
+    #[test]
+    fn empty_plaintext_ctr_gcm() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_256()?);
+
+        let ciphertext = cipher.encrypt_ctr(&[])?;
+        assert_eq!(ciphertext.len(), 12); // IV only
+        assert!(cipher.decrypt_ctr(&ciphertext)?.is_empty());
+
+        let ciphertext = cipher.encrypt_gcm(&[], None)?;
+        assert_eq!(ciphertext.len(), 36); // IV + AAD length + tag
+        let (plaintext, aad) = cipher.decrypt_gcm(&ciphertext)?;
+        assert!(plaintext.is_empty());
+        assert!(aad.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn ctr_iv_only_input_decrypts_to_empty() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_128()?);
+        let iv = [0x7e; 12];
+
+        // a 12-byte blob is an IV with an empty payload, through both the framed and explicit-IV decryptors
+        assert_eq!(cipher.decrypt_ctr(&iv)?, Vec::<u8>::new());
+        assert_eq!(cipher.decrypt_ctr_with_iv(&[], &iv)?, Vec::<u8>::new());
+        assert_eq!(cipher.decrypt_ctr_range(&[], &iv, 100)?, Vec::<u8>::new());
+        assert!(cipher.decrypt_ctr_in_place(&mut iv.clone())?.is_empty());
+        cipher.decrypt_ctr_in_place_with_iv(&iv, &mut [])?;
+        assert_eq!(cipher.decrypt_ctr64(&[0x7e; 8])?, Vec::<u8>::new());
+
+        // the same holds at the top of the counter range, where an underflow would report overflow
+        assert_eq!(cipher.ctr_with_counter(&[], &iv, u32::MAX)?, Vec::<u8>::new());
+
+        // anything shorter than the IV is still rejected
+        assert!(matches!(cipher.decrypt_ctr(&iv[..11]), Err(Error::InvalidCiphertext { len: 11, .. })));
+        Ok(())
+    }
+
+    #[test]
+    fn gcm_truncated_tags() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_128()?);
+        let iv = [0x5c; 12];
+        let full = cipher.encrypt_gcm_with_iv(b"Hello, World!", Some(b"header"), &iv)?;
+
+        for tag_bits in [96, 104, 112, 120, 128] {
+            let out = cipher.encrypt_gcm_with_tag_len(b"Hello, World!", Some(b"header"), &iv, tag_bits)?;
+            // the truncated tag is a prefix of the full tag
+            assert_eq!(out[..], full[..full.len() - 16 + tag_bits / 8]);
+            let (pt, aad) = cipher.decrypt_gcm_with_tag_len(&out, tag_bits)?;
+            assert_eq!((pt, aad), (b"Hello, World!".to_vec(), Some(b"header".to_vec())));
+
+            // every byte of the shortened tag is checked
+            let mut bad = out.clone();
+            *bad.last_mut().unwrap() ^= 0x80;
+            assert!(matches!(cipher.decrypt_gcm_with_tag_len(&bad, tag_bits), Err(Error::AuthFailed)));
+
+            // a tag of the wrong length does not authenticate
+            if tag_bits != 128 {
+                assert!(cipher.decrypt_gcm(&out).is_err());
             }
         }
 
-        round_keys
+        for tag_bits in [0, 32, 64, 95, 100, 127, 136] {
+            assert!(matches!(
+                cipher.encrypt_gcm_with_tag_len(b"", None, &iv, tag_bits),
+                Err(Error::InvalidTagLength { bits }) if bits == tag_bits
+            ));
+            assert!(matches!(
+                cipher.decrypt_gcm_with_tag_len(&full, tag_bits),
+                Err(Error::InvalidTagLength { bits }) if bits == tag_bits
+            ));
+        }
+
+        // too short to hold the tag
+        assert!(matches!(cipher.decrypt_gcm_with_tag_len(&[0u8; 31], 96), Err(Error::InvalidCiphertext { .. })));
+        Ok(())
     }
-}
 
-#[cfg(feature = "test-vectors")]
-impl Cipher {
-    /// Encrypt GCM with provided IV. 
-    /// Only compiled when test-vectors feature is enabled.
-    pub fn encrypt_gcm_with_iv(
-        &self,
-        plaintext: &[u8],
-        aad: Option<&[u8]>,
-        iv: &[u8; 12],
-    ) -> Result<Vec<u8>> {
-        let aad_bytes = aad.unwrap_or(&[]);
-        let aad_len_u32: u32 = aad_bytes
-            .len()
-            .try_into()
-            .expect("AAD size cannot exceed 2^32 bytes");
+    #[test]
+    fn gcm_empty_plaintext_with_aad() -> Result<()> {
+        // NIST CAVP gcmEncryptExtIV128: PTlen = 0, AADlen = 128, Count = 0
+        let cipher = Cipher::new(&Key::try_from_hex("77be63708971c4e240d1cb79e8d77feb")?);
+        let iv: [u8; 12] = decode_hex("e0e00f19fed7ba0136a797f3")?.try_into().unwrap();
+        let aad = decode_hex("7a43ec1d9c0a5a78a0b16533a6213cab")?;
+        let tag = decode_hex("209fcc8d3675ed938e9c7166709dd946")?;
 
-        let mut out = Vec::with_capacity(12 + 4 + aad_bytes.len() + plaintext.len() + 16);
+        // with no plaintext, GCM is a MAC over the AAD: IV || AAD length || AAD || Tag
+        let out = cipher.encrypt_gcm_with_iv(&[], Some(&aad), &iv)?;
+        assert_eq!(out.len(), 12 + 8 + 16 + 16);
+        assert_eq!(out[36..], tag);
+        assert_eq!(cipher.gmac(&aad, &iv)?[..], tag);
 
-        out.extend_from_slice(iv);
-        out.extend_from_slice(&aad_len_u32.to_be_bytes());
-        out.extend_from_slice(aad_bytes);
+        let (plaintext, returned_aad) = cipher.decrypt_gcm(&out)?;
+        assert!(plaintext.is_empty());
+        assert_eq!(returned_aad, Some(aad.clone()));
 
-        let mut ct = ctr_core(plaintext, &self.round_keys, iv, 2)?;
-        let tag = compute_tag(&ct, &self.round_keys, iv, aad_bytes)?;
+        // the AAD is still authenticated
+        let mut tampered = out.clone();
+        tampered[20] ^= 1;
+        assert!(matches!(cipher.decrypt_gcm(&tampered), Err(Error::AuthFailed)));
 
-        out.append(&mut ct);
-        out.extend_from_slice(&tag);
-        Ok(out)
+        // and through the random-IV entry point
+        let out = cipher.encrypt_gcm(&[], Some(&aad))?;
+        assert_eq!(cipher.decrypt_gcm(&out)?, (Vec::new(), Some(aad)));
+        Ok(())
     }
 
-    /// Encrypt ECB with no padding. Input must be a multiple of 16 bytes.
-    /// Only compiled when test-vectors feature is enabled.
-    pub fn encrypt_ecb_raw(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
-        ecb_core_enc(plaintext, &self.round_keys)
+    #[test]
+    fn runtime_mode_matches_mode_functions() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_128()?);
+        let plaintext = b"Hello, World!";
+
+        for mode in [Mode::Ecb, Mode::Ctr, Mode::Gcm] {
+            let ciphertext = cipher.encrypt(plaintext, mode)?;
+            assert_eq!(cipher.decrypt(&ciphertext, mode)?, plaintext);
+        }
+
+        let ecb = cipher.encrypt(plaintext, Mode::Ecb)?;
+        assert_eq!(ecb, cipher.encrypt_ecb(plaintext));
+        Ok(())
     }
 
-    /// Decrypt ECB with no padding. Input must be a multiple of 16 bytes.
-    /// Only compiled when test-vectors feature is enabled.
-    pub fn decrypt_ecb_raw(&self, ciphertext: &[u8]) -> Result<Vec<u8>> {
-        ecb_core_dec(ciphertext, &self.round_keys)
+    #[test]
+    #[cfg(feature = "testing")]
+    #[allow(deprecated)]
+    fn null_mode_round_trip() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_128()?);
+        let plaintext = b"Hello, World!";
+
+        let out = cipher.encrypt(plaintext, Mode::Null)?;
+        assert_eq!(out[..12], [0u8; 12]);
+        assert_eq!(&out[12..], plaintext);
+        assert_eq!(cipher.decrypt(&out, Mode::Null)?, plaintext);
+        assert!(cipher.decrypt(&out[..11], Mode::Null).is_err());
+        Ok(())
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    #[cfg(not(feature = "testing"))]
+    fn null_mode_unavailable_without_testing_feature() -> Result<()> {
+        // without the feature Mode has no Null variant, so every mode that can be selected encrypts
+        let cipher = Cipher::new(&Key::rand_key_128()?);
+        let plaintext = b"Hello, World!";
+        for mode in [Mode::Ecb, Mode::Ctr, Mode::Gcm] {
+            let out = match mode {
+                Mode::Ecb | Mode::Ctr | Mode::Gcm => cipher.encrypt(plaintext, mode)?,
+            };
+            assert!(!out.windows(plaintext.len()).any(|w| w == plaintext), "{mode:?}");
+        }
+
+        // and an envelope claiming the null mode byte is rejected rather than passed through
+        let mut blob = cipher.seal(plaintext, Mode::Ctr)?;
+        blob[5] = 0xff;
+        assert!(matches!(cipher.open_unauthenticated(&blob), Err(Error::UnknownEnvelopeMode { mode: 0xff })));
+        Ok(())
+    }
 
     #[test]
-    fn key_schedule_128() -> Result<()> {
-        // run key schedule on 128 bit sample key from FIPS-197 Appendix A.1
-        let key_128: [u8; 16] = [
-            0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf,
-            0x4f, 0x3c,
-        ];
+    fn ecb_padding_round_trip() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_128()?);
+        for padding in [Padding::Pkcs7, Padding::AnsiX923, Padding::Iso7816, Padding::Zero] {
+            for len in [1, 15, 16, 17, 32, 100] {
+                let pt: Vec<u8> = (1..=len).map(|i| i as u8).collect();
+                let ct = cipher.encrypt_ecb_with_padding(&pt, padding);
+                assert!(ct.len().is_multiple_of(16) && ct.len() >= pt.len());
+                assert_eq!(cipher.decrypt_ecb_with_padding(&ct, padding)?, pt, "{padding:?} length {len}");
+            }
+        }
 
-        let key = Key::try_from_slice(&key_128)?;
-        let round_keys = Cipher::expand_key(&key);
-        let last = *round_keys.last().expect("round_keys should not be empty");
+        // default scheme matches encrypt_ecb
+        let ct = cipher.encrypt_ecb(b"abc");
+        assert_eq!(ct, cipher.encrypt_ecb_with_padding(b"abc", Padding::default()));
 
-        // compare with last round key of sample schedule in A.1
-        let expected: [u8; 16] = [
-            0xd0, 0x14, 0xf9, 0xa8, 0xc9, 0xee, 0x25, 0x89, 0xe1, 0x3f, 0x0c, 0xc8, 0xb6, 0x63,
-            0x0c, 0xa6,
-        ];
+        // mismatched scheme is rejected
+        assert!(matches!(
+            cipher.decrypt_ecb_with_padding(&ct, Padding::Iso7816),
+            Err(Error::InvalidCiphertext { .. })
+        ));
+        Ok(())
+    }
 
-        assert_eq!(last, expected);
+    #[test]
+    fn ecb_zero_padding_is_ambiguous() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_128()?);
+
+        // trailing zeros of the plaintext cannot be told apart from padding
+        let pt = b"ends in zeros\0\0";
+        let ct = cipher.encrypt_ecb_with_padding(pt, Padding::Zero);
+        assert_eq!(ct.len(), 16);
+        assert_eq!(cipher.decrypt_ecb_with_padding(&ct, Padding::Zero)?, b"ends in zeros");
 
+        // aligned input is not padded at all, so an all-zero block decrypts to nothing
+        let ct = cipher.encrypt_ecb_with_padding(&[0u8; 16], Padding::Zero);
+        assert_eq!(ct.len(), 16);
+        assert!(cipher.decrypt_ecb_with_padding(&ct, Padding::Zero)?.is_empty());
+
+        // unambiguous schemes preserve them
+        for padding in [Padding::Pkcs7, Padding::AnsiX923, Padding::Iso7816] {
+            let ct = cipher.encrypt_ecb_with_padding(pt, padding);
+            assert_eq!(cipher.decrypt_ecb_with_padding(&ct, padding)?, pt);
+        }
         Ok(())
     }
 
     #[test]
-    fn key_schedule_192() -> Result<()> {
-        // run key schedule on 192 bit sample key from FIPS-197 Appendix A.2
-        let key_192: [u8; 24] = [
-            0x8e, 0x73, 0xb0, 0xf7, 0xda, 0x0e, 0x64, 0x52, 0xc8, 0x10, 0xf3, 0x2b, 0x80, 0x90,
-            0x79, 0xe5, 0x62, 0xf8, 0xea, 0xd2, 0x52, 0x2c, 0x6b, 0x7b,
-        ];
+    fn ctr_in_place_with_iv_matches_allocating() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_192()?);
+        for len in [0, 1, 15, 16, 17, 128, 1000, 5000] {
+            let pt: Vec<u8> = (0..len).map(|i| (i * 7) as u8).collect();
 
-        let key = Key::try_from_slice(&key_192)?;
-        let round_keys = Cipher::expand_key(&key);
-        let last = *round_keys.last().expect("round_keys should not be empty");
+            // encrypt_ctr output: IV || ciphertext
+            let expected = cipher.encrypt_ctr(&pt)?;
+            let iv: [u8; 12] = expected[..12].try_into().unwrap();
 
-        // compare with last round key of sample schedule in A.2
-        let expected: [u8; 16] = [
-            0xe9, 0x8b, 0xa0, 0x6f, 0x44, 0x8c, 0x77, 0x3c, 0x8e, 0xcc, 0x72, 0x04, 0x01, 0x00,
-            0x22, 0x02,
-        ];
+            let mut buf = pt.clone();
+            cipher.encrypt_ctr_in_place(&iv, &mut buf)?;
+            assert_eq!(buf, expected[12..], "length {len}");
 
-        assert_eq!(last, expected);
+            cipher.decrypt_ctr_in_place_with_iv(&iv, &mut buf)?;
+            assert_eq!(buf, pt, "length {len}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn cached_ghash_key_many_small_messages() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_256()?);
+        assert!(cipher.ghash_key.get().is_none(), "GHASH key computed before first GCM use");
 
+        for i in 0..200u32 {
+            let pt = i.to_be_bytes().repeat(i as usize % 7);
+            let iv = [i as u8; 12];
+            let out = cipher.encrypt_gcm_with_iv(&pt, Some(b"hdr"), &iv)?;
+
+            // output: IV (12) || AAD length (8) || AAD (3) || Ciphertext || Tag (16)
+            let (ct, tag) = out[23..].split_at(pt.len());
+            let uncached_key = GHashKey::for_round_keys(cipher.round_keys());
+            let j0 = derive_j0(&uncached_key, &iv);
+            let uncached = compute_tag_j0(ct, cipher.round_keys(), &j0, b"hdr", &uncached_key, usize::MAX)?;
+            assert_eq!(tag, uncached);
+            assert_eq!(cipher.decrypt_gcm(&out)?.0, pt);
+        }
+
+        // computed once, then reused
+        let cached = cipher.ghash_key.get().expect("GHASH key not cached");
+        assert!(std::ptr::eq(cached, cipher.ghash_key()));
         Ok(())
     }
 
     #[test]
-    fn key_schedule_256() -> Result<()> {
-        // run key schedule on 256 bit sample key from FIPS-197 Appendix A.3
-        let key_256: [u8; 32] = [
-            0x60, 0x3d, 0xeb, 0x10, 0x15, 0xca, 0x71, 0xbe, 0x2b, 0x73, 0xae, 0xf0, 0x85, 0x7d,
-            0x77, 0x81, 0x1f, 0x35, 0x2c, 0x07, 0x3b, 0x61, 0x08, 0xd7, 0x2d, 0x98, 0x10, 0xa3,
-            0x09, 0x14, 0xdf, 0xf4,
-        ];
+    fn serial_and_parallel_paths_match() -> Result<()> {
+        let key = Key::rand_key_128()?;
+        let serial = Cipher::new(&key).with_parallel_threshold(usize::MAX);
+        let mut parallel = Cipher::new(&key);
+        parallel.set_parallel_threshold(0);
+        let default = Cipher::new(&key);
+        assert_eq!(default.parallel_threshold(), PARALLEL_THRESHOLD);
 
-        let key = Key::try_from_slice(&key_256)?;
-        let round_keys = Cipher::expand_key(&key);
-        let last = *round_keys.last().expect("round_keys should not be empty");
+        let iv = [0x3c; 12];
+        for len in [0, 1, 16, 129, 4095, 4096, 4097, 4111, 4112, 10_000] {
+            let pt: Vec<u8> = (0..len).map(|i| (i * 31) as u8).collect();
+            for cipher in [&parallel, &default] {
+                assert_eq!(cipher.encrypt_ecb(&pt), serial.encrypt_ecb(&pt), "ECB length {len}");
+                assert_eq!(
+                    CtrStream::new(cipher, &iv).update(&pt)?,
+                    CtrStream::new(&serial, &iv).update(&pt)?,
+                    "CTR length {len}"
+                );
+                let ct = cipher.encrypt_gcm_with_iv(&pt, None, &iv)?;
+                assert_eq!(ct, serial.encrypt_gcm_with_iv(&pt, None, &iv)?, "GCM length {len}");
+                assert_eq!(serial.decrypt_gcm(&ct)?.0, pt);
+                assert_eq!(cipher.decrypt_ecb(&serial.encrypt_ecb(&pt))?, pt);
+            }
+        }
+        Ok(())
+    }
 
-        // compare with last round key of sample schedule in A.3
-        let expected: [u8; 16] = [
-            0xfe, 0x48, 0x90, 0xd1, 0xe6, 0x18, 0x8d, 0x0b, 0x04, 0x6d, 0xf3, 0x44, 0x70, 0x6c,
-            0x63, 0x1e,
-        ];
+    #[test]
+    fn debug_redacts_round_keys() -> Result<()> {
+        for (len, size) in [(16, 128), (24, 192), (32, 256)] {
+            let cipher = Cipher::new(&Key::try_from_slice(&vec![0xA5; len])?);
+            assert_eq!(
+                format!("{cipher:?}"),
+                format!("Cipher {{ size: {size}, round_keys: <redacted> }}")
+            );
+            assert!(!format!("{cipher:#?}").contains("165"));
+        }
+        Ok(())
+    }
 
-        assert_eq!(last, expected);
+    #[test]
+    fn new_from_bytes_validates_length() -> Result<()> {
+        for len in [16, 24, 32] {
+            let bytes: Vec<u8> = (0..len as u8).collect();
+            assert_eq!(Cipher::new_from_bytes(&bytes)?, Cipher::new(&Key::try_from_slice(&bytes)?));
+        }
+        for len in [0, 15, 17, 33] {
+            assert!(matches!(
+                Cipher::new_from_bytes(&vec![0u8; len]),
+                Err(Error::InvalidKeyLength { len: l }) if l == len
+            ));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn key_schedules_are_computed_lazily() -> Result<()> {
+        let key = Key::rand_key_256()?;
+        let eager = Cipher::new(&key);
+        eager.round_keys();
+
+        // construction, cloning, comparison and formatting do not run the key schedule
+        let cipher = Cipher::new(&key);
+        let clone = cipher.clone();
+        assert_eq!(cipher, eager);
+        assert_eq!(format!("{cipher:?}"), format!("{eager:?}"));
+        assert!(cipher.round_keys.get().is_none() && clone.round_keys.get().is_none());
 
+        // encryption runs only the forward schedule, and gives the same output as a fully expanded cipher
+        let ciphertext = cipher.encrypt_ecb(b"Hello, World!");
+        assert_eq!(ciphertext, eager.encrypt_ecb(b"Hello, World!"));
+        assert!(cipher.round_keys.get().is_some());
+        assert!(cipher.inv_round_keys.get().is_none());
+
+        // decryption runs the inverse schedule
+        assert_eq!(clone.decrypt_ecb(&ciphertext)?, b"Hello, World!");
+        assert!(clone.inv_round_keys.get().is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn equality_compares_expanded_keys() -> Result<()> {
+        let key = Key::rand_key_256()?;
+        let cipher = Cipher::new(&key);
+        assert_eq!(cipher.clone(), cipher);
+        assert_eq!(Cipher::new(&key).with_parallel_threshold(0), cipher);
+
+        // a single differing bit in the key, and a key of a different size
+        let mut bytes = *key.as_array_256().unwrap();
+        bytes[31] ^= 1;
+        assert_ne!(Cipher::new(&Key::from(bytes)), cipher);
+        assert_ne!(Cipher::new(&Key::from([0u8; 16])), Cipher::new(&Key::from([0u8; 32])));
+        Ok(())
+    }
+
+    #[test]
+    fn clone_produces_identical_ciphertext() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_256()?).with_parallel_threshold(64);
+        let iv = [0x3cu8; 12];
+        let plaintext = [0xa5u8; 1000];
+
+        // before and after the GHASH key is cached, so both the empty and filled cache are cloned
+        let fresh = cipher.clone();
+        let expected = cipher.encrypt_gcm_with_iv(&plaintext, Some(b"aad"), &iv)?;
+        let cached = cipher.clone();
+
+        for clone in [&fresh, &cached] {
+            assert_eq!(clone, &cipher);
+            assert_eq!(clone.parallel_threshold(), 64);
+            assert_eq!(clone.encrypt_ecb(&plaintext), cipher.encrypt_ecb(&plaintext));
+            assert_eq!(clone.encrypt_gcm_with_iv(&plaintext, Some(b"aad"), &iv)?, expected);
+        }
+
+        // clones can be moved into worker threads
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let worker = cipher.clone();
+                std::thread::spawn(move || worker.encrypt_gcm_with_iv(&plaintext, Some(b"aad"), &iv))
+            })
+            .collect();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap()?, expected);
+        }
         Ok(())
     }
 
@@ -481,7 +3246,7 @@ mod tests {
 
         // encrypt the plaintext bytes using AES-256-CTR.
         // note that the key size does not need to be explicitly stated.
-        let ciphertext = cipher.encrypt_ctr(&plaintext).expect("Counter overflow");
+        let ciphertext = cipher.encrypt_ctr(plaintext).expect("Counter overflow");
 
         // decrypt the resultant ciphertext.
         let decrypted_ct = cipher.decrypt_ctr(&ciphertext).expect("Counter overflow");
@@ -0,0 +1,165 @@
+//! AES-NI backend for x86-64. Selected at runtime by [encrypt_block](super::encrypt_block) and
+//! [decrypt_block](super::decrypt_block) when the CPU supports the `aes` feature.
+
+use core::arch::x86_64::*;
+
+/// Returns true if the CPU supports AES-NI. The result is cached by the standard library after the first call.
+/// Without std there is no runtime detection, so this reports whether the feature was enabled at compile time.
+#[inline(always)]
+pub(crate) fn available() -> bool {
+    #[cfg(feature = "std")]
+    return std::arch::is_x86_feature_detected!("aes");
+    #[cfg(not(feature = "std"))]
+    return cfg!(target_feature = "aes");
+}
+
+#[inline(always)]
+fn load(block: &[u8; 16]) -> __m128i {
+    // SAFETY: block is 16 readable bytes, and loadu has no alignment requirement
+    unsafe { _mm_loadu_si128(block.as_ptr().cast()) }
+}
+
+#[inline(always)]
+fn store(state: __m128i) -> [u8; 16] {
+    let mut out = [0u8; 16];
+    // SAFETY: out is 16 writable bytes, and storeu has no alignment requirement
+    unsafe { _mm_storeu_si128(out.as_mut_ptr().cast(), state) };
+    out
+}
+
+/// Encrypts a block using `aesenc`/`aesenclast`.
+///
+/// # Safety
+/// The CPU must support AES-NI (see [available]).
+#[target_feature(enable = "aes")]
+pub(crate) unsafe fn encrypt_block(plaintext: &[u8; 16], round_keys: &[[u8; 16]]) -> [u8; 16] {
+    let last_key_idx = round_keys.len() - 1;
+
+    let mut state = _mm_xor_si128(load(plaintext), load(&round_keys[0]));
+    for round_key in &round_keys[1..last_key_idx] {
+        state = _mm_aesenc_si128(state, load(round_key));
+    }
+    state = _mm_aesenclast_si128(state, load(&round_keys[last_key_idx]));
+
+    store(state)
+}
+
+/// Encrypts 8 independent blocks, interleaving the rounds so that the latency of each `aesenc` is
+/// hidden behind the other blocks.
+///
+/// # Safety
+/// The CPU must support AES-NI (see [available]).
+#[target_feature(enable = "aes")]
+pub(crate) unsafe fn encrypt_blocks8(blocks: &[[u8; 16]; 8], round_keys: &[[u8; 16]]) -> [[u8; 16]; 8] {
+    let last_key_idx = round_keys.len() - 1;
+
+    let rk = load(&round_keys[0]);
+    let mut state = blocks.map(|b| _mm_xor_si128(load(&b), rk));
+    for round_key in &round_keys[1..last_key_idx] {
+        let rk = load(round_key);
+        for s in state.iter_mut() {
+            *s = _mm_aesenc_si128(*s, rk);
+        }
+    }
+    let rk = load(&round_keys[last_key_idx]);
+    state.map(|s| store(_mm_aesenclast_si128(s, rk)))
+}
+
+/// Decrypts a block using `aesdec`/`aesdeclast`.
+///
+/// `aesdec` implements the equivalent inverse cipher (FIPS-197 section 5.3.5), which applies
+/// InvMixColumns before AddRoundKey. The middle round keys are transformed with `aesimc` to match.
+///
+/// # Safety
+/// The CPU must support AES-NI (see [available]).
+#[target_feature(enable = "aes")]
+pub(crate) unsafe fn decrypt_block(ciphertext: &[u8; 16], round_keys: &[[u8; 16]]) -> [u8; 16] {
+    let last_key_idx = round_keys.len() - 1;
+
+    let mut state = _mm_xor_si128(load(ciphertext), load(&round_keys[last_key_idx]));
+    for round_key in round_keys[1..last_key_idx].iter().rev() {
+        state = _mm_aesdec_si128(state, _mm_aesimc_si128(load(round_key)));
+    }
+    state = _mm_aesdeclast_si128(state, load(&round_keys[0]));
+
+    store(state)
+}
+
+/// Decrypts a block using `aesdec`/`aesdeclast` with round keys already transformed by
+/// [inverse_round_keys](super::decryption::inverse_round_keys), saving an `aesimc` per round.
+///
+/// # Safety
+/// The CPU must support AES-NI (see [available]).
+#[target_feature(enable = "aes")]
+pub(crate) unsafe fn decrypt_block_eq(ciphertext: &[u8; 16], inv_round_keys: &[[u8; 16]]) -> [u8; 16] {
+    let last_key_idx = inv_round_keys.len() - 1;
+
+    let mut state = _mm_xor_si128(load(ciphertext), load(&inv_round_keys[0]));
+    for round_key in &inv_round_keys[1..last_key_idx] {
+        state = _mm_aesdec_si128(state, load(round_key));
+    }
+    state = _mm_aesdeclast_si128(state, load(&inv_round_keys[last_key_idx]));
+
+    store(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aesp::core::decryption::{decrypt_block_soft, inverse_round_keys};
+    use crate::aesp::core::encryption::encrypt_block_soft;
+    use crate::{Cipher, Key, Result};
+
+    #[test]
+    fn matches_software_backend() -> Result<()> {
+        if !available() {
+            return Ok(());
+        }
+
+        for key in [Key::rand_key_128()?, Key::rand_key_192()?, Key::rand_key_256()?] {
+            let cipher = Cipher::new(&key);
+            let rk = cipher.round_keys();
+            let inv_rk = inverse_round_keys(rk);
+
+            let mut block = [0u8; 16];
+            for i in 0..256 {
+                block[i % 16] ^= i as u8;
+                block = encrypt_block_soft(&block, rk);
+
+                // SAFETY: AES-NI support checked above
+                let (enc, dec) = unsafe { (encrypt_block(&block, rk), decrypt_block(&block, rk)) };
+                assert_eq!(enc, encrypt_block_soft(&block, rk));
+                assert_eq!(dec, decrypt_block_soft(&block, rk));
+                // SAFETY: AES-NI support checked above
+                assert_eq!(unsafe { decrypt_block_eq(&block, &inv_rk) }, dec);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn fips_197_example() -> Result<()> {
+        if !available() {
+            return Ok(());
+        }
+
+        // FIPS-197 Appendix C.3: AES-256
+        let key: Vec<u8> = (0..32).collect();
+        let plaintext: [u8; 16] = [
+            0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, //
+            0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff, //
+        ];
+        let ciphertext: [u8; 16] = [
+            0x8e, 0xa2, 0xb7, 0xca, 0x51, 0x67, 0x45, 0xbf, //
+            0xea, 0xfc, 0x49, 0x90, 0x4b, 0x49, 0x60, 0x89, //
+        ];
+
+        let cipher = Cipher::new(&Key::try_from_slice(&key)?);
+        // SAFETY: AES-NI support checked above
+        unsafe {
+            assert_eq!(encrypt_block(&plaintext, cipher.round_keys()), ciphertext);
+            assert_eq!(decrypt_block(&ciphertext, cipher.round_keys()), plaintext);
+        }
+        Ok(())
+    }
+}
@@ -1,13 +1,27 @@
-use super::constants::SBOX_INV;
-use super::util::{dbl, add_round_key};
+use alloc::vec::Vec;
+
+use super::util::{dbl, add_round_key, inv_sub_byte};
 
 /// Core AES decryption function. Decrypts 16 byte block using provided round keys.
+/// Uses AES-NI when the CPU supports it, otherwise falls back to the software implementation.
 #[inline(always)]
 pub fn decrypt_block(ciphertext: &[u8; 16], round_keys: &[[u8; 16]]) -> [u8; 16] {
+    #[cfg(target_arch = "x86_64")]
+    if super::aesni::available() {
+        // SAFETY: AES-NI support checked at runtime
+        return unsafe { super::aesni::decrypt_block(ciphertext, round_keys) };
+    }
+
+    decrypt_block_soft(ciphertext, round_keys)
+}
+
+/// Software AES decryption.
+#[inline(always)]
+pub(crate) fn decrypt_block_soft(ciphertext: &[u8; 16], round_keys: &[[u8; 16]]) -> [u8; 16] {
     let mut state = *ciphertext;
     let num_rounds = round_keys.len();
 
-    add_round_key(&mut state, &round_keys.last().unwrap());
+    add_round_key(&mut state, round_keys.last().unwrap());
 
     for round_key in round_keys[1..num_rounds - 1].iter().rev() {
         shift_rows_inv(&mut state);
@@ -23,11 +37,60 @@ pub fn decrypt_block(ciphertext: &[u8; 16], round_keys: &[[u8; 16]]) -> [u8; 16]
     state
 }
 
+/// Key schedule for the equivalent inverse cipher (FIPS-197 section 5.3.5). The round keys are reversed,
+/// and InvMixColumns is applied to all but the first and last, so that decryption can run the rounds in
+/// the same order as encryption (see [decrypt_block_eq]).
+pub fn inverse_round_keys(round_keys: &[[u8; 16]]) -> Vec<[u8; 16]> {
+    let last_key_idx = round_keys.len() - 1;
+    let mut inv: Vec<[u8; 16]> = round_keys.iter().rev().copied().collect();
+    for round_key in &mut inv[1..last_key_idx] {
+        mix_columns_inv(round_key);
+    }
+    inv
+}
+
+/// AES decryption using the equivalent inverse cipher. Takes the round keys produced by
+/// [inverse_round_keys], and gives the same result as [decrypt_block] with the original round keys.
+/// Uses AES-NI when the CPU supports it, which avoids transforming each round key on every block.
+#[inline(always)]
+pub fn decrypt_block_eq(ciphertext: &[u8; 16], inv_round_keys: &[[u8; 16]]) -> [u8; 16] {
+    #[cfg(target_arch = "x86_64")]
+    if super::aesni::available() {
+        // SAFETY: AES-NI support checked at runtime
+        return unsafe { super::aesni::decrypt_block_eq(ciphertext, inv_round_keys) };
+    }
+
+    decrypt_block_eq_soft(ciphertext, inv_round_keys)
+}
+
+/// Software equivalent inverse cipher. Same structure as encryption: every middle round is InvSubBytes,
+/// InvShiftRows, InvMixColumns, AddRoundKey.
+#[inline(always)]
+pub(crate) fn decrypt_block_eq_soft(ciphertext: &[u8; 16], inv_round_keys: &[[u8; 16]]) -> [u8; 16] {
+    let mut state = *ciphertext;
+    let last_key_idx = inv_round_keys.len() - 1;
+
+    add_round_key(&mut state, &inv_round_keys[0]);
+
+    for round_key in &inv_round_keys[1..last_key_idx] {
+        sub_bytes_inv(&mut state);
+        shift_rows_inv(&mut state);
+        mix_columns_inv(&mut state);
+        add_round_key(&mut state, round_key);
+    }
+
+    sub_bytes_inv(&mut state);
+    shift_rows_inv(&mut state);
+    add_round_key(&mut state, &inv_round_keys[last_key_idx]);
+
+    state
+}
+
 /// Inverse SubBytes step. Each byte is substituted using the inverse SBOX.
 #[inline(always)]
 pub(crate) fn sub_bytes_inv(state: &mut [u8; 16]) {
     for byte in state {
-        *byte = SBOX_INV[*byte as usize];
+        *byte = inv_sub_byte(*byte);
     }
 }
 
@@ -73,6 +136,7 @@ fn shift_rows_inv(state: &mut [u8; 16]) {
 /// | b2 |      | 13  09  14  11 |  | d2 |
 /// [ b3 ]      [ 11  13  09  14 ]  [ d3 ]
 #[inline(always)]
+#[allow(clippy::identity_op)] // `i + 0` kept for alignment with the rows below
 fn mix_columns_inv(state: &mut [u8; 16]) {
     // optimisation technique from https://crypto.stackexchange.com/a/71206
     for col in 0..4 {
@@ -193,6 +257,36 @@ mod tests {
             decrypted, plaintext,
             "decrypt block does not exactly reverse encrypt block"
         );
+        assert_eq!(
+            decryption::decrypt_block_soft(&encrypted, cipher.round_keys()),
+            plaintext,
+            "software decrypt block does not exactly reverse encrypt block"
+        );
+
+        let inv_round_keys = decryption::inverse_round_keys(cipher.round_keys());
+        assert_eq!(decryption::decrypt_block_eq(&encrypted, &inv_round_keys), plaintext);
+        assert_eq!(decryption::decrypt_block_eq_soft(&encrypted, &inv_round_keys), plaintext);
+
+        Ok(())
+    }
+
+    #[test]
+    fn equivalent_inverse_cipher_matches() -> Result<()> {
+        for key in [Key::rand_key_128()?, Key::rand_key_192()?, Key::rand_key_256()?] {
+            let cipher = Cipher::new(&key);
+            let inv_round_keys = decryption::inverse_round_keys(cipher.round_keys());
+            assert_eq!(inv_round_keys.len(), cipher.round_keys().len());
+
+            let mut block = [0u8; 16];
+            for i in 0..256 {
+                block[i % 16] ^= i as u8;
+                block = encryption::encrypt_block(&block, cipher.round_keys());
+                assert_eq!(
+                    decryption::decrypt_block_eq_soft(&block, &inv_round_keys),
+                    decryption::decrypt_block_soft(&block, cipher.round_keys())
+                );
+            }
+        }
 
         Ok(())
     }
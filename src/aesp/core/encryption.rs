@@ -1,9 +1,64 @@
-use super::constants::SBOX;
-use super::util::{add_round_key, dbl};
+use alloc::vec::Vec;
+
+use super::util::{add_round_key, dbl, sub_byte};
 
 /// Core AES encryption function. Encrypts 16 byte block using provided round keys.
+/// Uses AES-NI when the CPU supports it, otherwise falls back to the software implementation.
 #[inline(always)]
 pub fn encrypt_block(plaintext: &[u8; 16], round_keys: &[[u8; 16]]) -> [u8; 16] {
+    #[cfg(target_arch = "x86_64")]
+    if super::aesni::available() {
+        // SAFETY: AES-NI support checked at runtime
+        return unsafe { super::aesni::encrypt_block(plaintext, round_keys) };
+    }
+
+    encrypt_block_soft(plaintext, round_keys)
+}
+
+/// Encrypts 8 independent blocks. Interleaving the blocks round by round allows the CPU to overlap their
+/// latency, which is considerably faster than 8 calls to [encrypt_block] on the AES-NI path.
+#[inline(always)]
+pub fn encrypt_blocks8(blocks: &[[u8; 16]; 8], round_keys: &[[u8; 16]]) -> [[u8; 16]; 8] {
+    #[cfg(target_arch = "x86_64")]
+    if super::aesni::available() {
+        // SAFETY: AES-NI support checked at runtime
+        return unsafe { super::aesni::encrypt_blocks8(blocks, round_keys) };
+    }
+
+    encrypt_blocks8_soft(blocks, round_keys)
+}
+
+/// Software AES encryption of 8 blocks, processed round by round.
+#[inline(always)]
+pub(crate) fn encrypt_blocks8_soft(blocks: &[[u8; 16]; 8], round_keys: &[[u8; 16]]) -> [[u8; 16]; 8] {
+    let mut states = *blocks;
+    let last_key_idx = round_keys.len() - 1;
+
+    for state in states.iter_mut() {
+        add_round_key(state, &round_keys[0]);
+    }
+
+    for round_key in &round_keys[1..last_key_idx] {
+        for state in states.iter_mut() {
+            sub_bytes(state);
+            shift_rows(state);
+            mix_columns(state);
+            add_round_key(state, round_key);
+        }
+    }
+
+    for state in states.iter_mut() {
+        sub_bytes(state);
+        shift_rows(state);
+        add_round_key(state, &round_keys[last_key_idx]);
+    }
+
+    states
+}
+
+/// Software AES encryption.
+#[inline(always)]
+pub(crate) fn encrypt_block_soft(plaintext: &[u8; 16], round_keys: &[[u8; 16]]) -> [u8; 16] {
     let mut state = *plaintext;
     let last_key_idx = round_keys.len() - 1;
 
@@ -26,11 +81,37 @@ pub fn encrypt_block(plaintext: &[u8; 16], round_keys: &[[u8; 16]]) -> [u8; 16]
     state
 }
 
+/// Traced AES encryption. Identical to [encrypt_block], but records the state after the initial
+/// AddRoundKey and after each subsequent round. Returns the snapshots along with the final ciphertext.
+pub fn encrypt_block_trace(plaintext: &[u8; 16], round_keys: &[[u8; 16]]) -> (Vec<[u8; 16]>, [u8; 16]) {
+    let mut state = *plaintext;
+    let last_key_idx = round_keys.len() - 1;
+    let mut trace = Vec::with_capacity(round_keys.len());
+
+    add_round_key(&mut state, &round_keys[0]);
+    trace.push(state);
+
+    for round_key in &round_keys[1..last_key_idx] {
+        sub_bytes(&mut state);
+        shift_rows(&mut state);
+        mix_columns(&mut state);
+        add_round_key(&mut state, round_key);
+        trace.push(state);
+    }
+
+    sub_bytes(&mut state);
+    shift_rows(&mut state);
+    add_round_key(&mut state, &round_keys[last_key_idx]);
+    trace.push(state);
+
+    (trace, state)
+}
+
 /// SubBytes step. Each byte is substituted using the SBOX.
 #[inline(always)]
 pub(crate) fn sub_bytes(state: &mut [u8; 16]) {
     for byte in state {
-        *byte = SBOX[*byte as usize];
+        *byte = sub_byte(*byte);
     }
 }
 
@@ -77,6 +158,7 @@ pub(crate) fn shift_rows(state: &mut [u8; 16]) {
 /// | d2 |      | 1  1  2  3 |  | b2 |
 /// [ d3 ]      [ 3  1  1  2 ]  [ b3 ]
 #[inline(always)]
+#[allow(clippy::identity_op)] // `i + 0` kept for alignment with the rows below
 pub(crate) fn mix_columns(state: &mut [u8; 16]) {
     // optimisation technique from https://crypto.stackexchange.com/a/71206
     for col in 0..4 {
@@ -194,6 +276,65 @@ mod tests {
         let actual = encrypt_block(&plaintext, cipher.round_keys());
 
         assert_eq!(actual, expected, "incorrect AES-256 encryption of block");
+        assert_eq!(
+            encrypt_block_soft(&plaintext, cipher.round_keys()),
+            expected,
+            "incorrect AES-256 software encryption of block"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_encrypt_blocks8_matches_scalar() -> Result<()> {
+        for key in [Key::rand_key_128()?, Key::rand_key_192()?, Key::rand_key_256()?] {
+            let cipher = Cipher::new(&key);
+            let rk = cipher.round_keys();
+
+            // chain outputs into the next batch so every batch is distinct
+            let mut blocks: [[u8; 16]; 8] = std::array::from_fn(|i| [i as u8; 16]);
+            for _ in 0..1000 {
+                let batched = encrypt_blocks8(&blocks, rk);
+                assert_eq!(batched, encrypt_blocks8_soft(&blocks, rk));
+                assert_eq!(batched, blocks.map(|b| encrypt_block_soft(&b, rk)));
+                blocks = batched;
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_encrypt_block_trace_128() -> Result<()> {
+        // round-by-round example from FIPS-197 Appendix B
+        let key: [u8; 16] = [
+            0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, //
+            0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf, 0x4f, 0x3c, //
+        ];
+        let plaintext: [u8; 16] = [
+            0x32, 0x43, 0xf6, 0xa8, 0x88, 0x5a, 0x30, 0x8d, //
+            0x31, 0x31, 0x98, 0xa2, 0xe0, 0x37, 0x07, 0x34, //
+        ];
+
+        // state at the start of rounds 1 to 10, followed by the output
+        let expected: [[u8; 16]; 11] = [
+            [0x19, 0x3d, 0xe3, 0xbe, 0xa0, 0xf4, 0xe2, 0x2b, 0x9a, 0xc6, 0x8d, 0x2a, 0xe9, 0xf8, 0x48, 0x08],
+            [0xa4, 0x9c, 0x7f, 0xf2, 0x68, 0x9f, 0x35, 0x2b, 0x6b, 0x5b, 0xea, 0x43, 0x02, 0x6a, 0x50, 0x49],
+            [0xaa, 0x8f, 0x5f, 0x03, 0x61, 0xdd, 0xe3, 0xef, 0x82, 0xd2, 0x4a, 0xd2, 0x68, 0x32, 0x46, 0x9a],
+            [0x48, 0x6c, 0x4e, 0xee, 0x67, 0x1d, 0x9d, 0x0d, 0x4d, 0xe3, 0xb1, 0x38, 0xd6, 0x5f, 0x58, 0xe7],
+            [0xe0, 0x92, 0x7f, 0xe8, 0xc8, 0x63, 0x63, 0xc0, 0xd9, 0xb1, 0x35, 0x50, 0x85, 0xb8, 0xbe, 0x01],
+            [0xf1, 0x00, 0x6f, 0x55, 0xc1, 0x92, 0x4c, 0xef, 0x7c, 0xc8, 0x8b, 0x32, 0x5d, 0xb5, 0xd5, 0x0c],
+            [0x26, 0x0e, 0x2e, 0x17, 0x3d, 0x41, 0xb7, 0x7d, 0xe8, 0x64, 0x72, 0xa9, 0xfd, 0xd2, 0x8b, 0x25],
+            [0x5a, 0x41, 0x42, 0xb1, 0x19, 0x49, 0xdc, 0x1f, 0xa3, 0xe0, 0x19, 0x65, 0x7a, 0x8c, 0x04, 0x0c],
+            [0xea, 0x83, 0x5c, 0xf0, 0x04, 0x45, 0x33, 0x2d, 0x65, 0x5d, 0x98, 0xad, 0x85, 0x96, 0xb0, 0xc5],
+            [0xeb, 0x40, 0xf2, 0x1e, 0x59, 0x2e, 0x38, 0x84, 0x8b, 0xa1, 0x13, 0xe7, 0x1b, 0xc3, 0x42, 0xd2],
+            [0x39, 0x25, 0x84, 0x1d, 0x02, 0xdc, 0x09, 0xfb, 0xdc, 0x11, 0x85, 0x97, 0x19, 0x6a, 0x0b, 0x32],
+        ];
+
+        let cipher = Cipher::new(&Key::try_from_slice(&key)?);
+        let (trace, ciphertext) = cipher.encrypt_block_trace(&plaintext);
+
+        assert_eq!(trace, expected, "intermediate state does not match FIPS-197");
+        assert_eq!(ciphertext, expected[10]);
+        assert_eq!(ciphertext, encrypt_block(&plaintext, cipher.round_keys()));
         Ok(())
     }
 
@@ -234,6 +375,11 @@ mod tests {
         let actual = encrypt_block(&plaintext, cipher.round_keys());
 
         assert_eq!(actual, expected, "incorrect AES-192 encryption of block");
+        assert_eq!(
+            encrypt_block_soft(&plaintext, cipher.round_keys()),
+            expected,
+            "incorrect AES-192 software encryption of block"
+        );
         Ok(())
     }
 
@@ -273,6 +419,11 @@ mod tests {
         let actual = encrypt_block(&plaintext, cipher.round_keys());
 
         assert_eq!(actual, expected, "incorrect AES-128 encryption of block");
+        assert_eq!(
+            encrypt_block_soft(&plaintext, cipher.round_keys()),
+            expected,
+            "incorrect AES-128 software encryption of block"
+        );
         Ok(())
     }
 }
@@ -1,9 +1,22 @@
 //! Core AES implementation for encryption and decryption of a 16 byte block. Exports encrypt_block and decrypt_block.
 
 pub mod constants;
+#[cfg(target_arch = "x86_64")]
+mod aesni;
+#[cfg(any(feature = "constant-time", test))]
+mod sbox_ct;
 mod util;
 mod decryption;
 mod encryption;
 
-pub use decryption::decrypt_block;
-pub use encryption::encrypt_block;
+pub use decryption::{decrypt_block, decrypt_block_eq, inverse_round_keys};
+pub use encryption::{encrypt_block, encrypt_block_trace, encrypt_blocks8};
+pub(crate) use util::sub_byte;
+
+/// Returns true if block encryption and decryption use the AES-NI backend on this CPU.
+pub fn hardware_accelerated() -> bool {
+    #[cfg(target_arch = "x86_64")]
+    return aesni::available();
+    #[cfg(not(target_arch = "x86_64"))]
+    return false;
+}
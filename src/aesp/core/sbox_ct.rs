@@ -0,0 +1,68 @@
+//! Constant-time S-box, selected by the `constant-time` feature.
+//!
+//! The table S-box indexes memory with secret bytes, so which cache lines are touched depends on the key and
+//! data, and can be observed by another process on the same hardware. Here the S-box is instead computed
+//! arithmetically: the multiplicative inverse in GF(2^8), computed as x^254, followed by the affine
+//! transformation of FIPS-197 section 5.1.1. There are no secret-dependent branches or memory accesses.
+//!
+//! The tradeoff is speed: each substitution costs 11 branchless GF(2^8) multiplications instead of a single
+//! load, making the software block functions around 4-5x slower. The AES-NI path is already constant time and
+//! is unaffected.
+
+use super::util::dbl;
+
+/// Multiplication in GF(2^8), modulo x^8 + x^4 + x^3 + x + 1. Each bit of `b` selects through a mask rather
+/// than a branch.
+#[inline(always)]
+fn gf_mul(mut a: u8, b: u8) -> u8 {
+    let mut product = 0;
+    for i in 0..8 {
+        product ^= a & 0u8.wrapping_sub((b >> i) & 1);
+        a = dbl(a);
+    }
+    product
+}
+
+/// Multiplicative inverse in GF(2^8) as x^254, which maps 0 to 0 as AES requires.
+/// Addition chain: 1, 2, 3, 6, 12, 15, 30, 60, 120, 240, 252, 254.
+#[inline(always)]
+fn gf_inv(x: u8) -> u8 {
+    let x2 = gf_mul(x, x);
+    let x3 = gf_mul(x2, x);
+    let x6 = gf_mul(x3, x3);
+    let x12 = gf_mul(x6, x6);
+    let x15 = gf_mul(x12, x3);
+    let x30 = gf_mul(x15, x15);
+    let x60 = gf_mul(x30, x30);
+    let x120 = gf_mul(x60, x60);
+    let x240 = gf_mul(x120, x120);
+    let x252 = gf_mul(x240, x12);
+    gf_mul(x252, x2)
+}
+
+/// S-box substitution: inverse, then affine transformation.
+#[inline(always)]
+pub(crate) fn sub_byte(b: u8) -> u8 {
+    let x = gf_inv(b);
+    x ^ x.rotate_left(1) ^ x.rotate_left(2) ^ x.rotate_left(3) ^ x.rotate_left(4) ^ 0x63
+}
+
+/// Inverse S-box substitution: inverse affine transformation, then inverse.
+#[inline(always)]
+pub(crate) fn inv_sub_byte(b: u8) -> u8 {
+    gf_inv(b.rotate_left(1) ^ b.rotate_left(3) ^ b.rotate_left(6) ^ 0x05)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aesp::core::constants::{SBOX, SBOX_INV};
+
+    #[test]
+    fn matches_tables() {
+        for b in 0..=255u8 {
+            assert_eq!(sub_byte(b), SBOX[b as usize], "S-box {b:#04x}");
+            assert_eq!(inv_sub_byte(b), SBOX_INV[b as usize], "inverse S-box {b:#04x}");
+        }
+    }
+}
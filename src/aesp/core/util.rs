@@ -1,3 +1,23 @@
+#[cfg(not(feature = "constant-time"))]
+use super::constants::{SBOX, SBOX_INV};
+#[cfg(feature = "constant-time")]
+pub(crate) use super::sbox_ct::{inv_sub_byte, sub_byte};
+
+/// S-box substitution of a single byte, by table lookup. With the `constant-time` feature, the S-box is
+/// computed arithmetically instead (see [sbox_ct](super::sbox_ct)).
+#[cfg(not(feature = "constant-time"))]
+#[inline(always)]
+pub(crate) fn sub_byte(b: u8) -> u8 {
+    SBOX[b as usize]
+}
+
+/// Inverse S-box substitution of a single byte, by table lookup.
+#[cfg(not(feature = "constant-time"))]
+#[inline(always)]
+pub(crate) fn inv_sub_byte(b: u8) -> u8 {
+    SBOX_INV[b as usize]
+}
+
 // used for both encryption and decryption
 #[inline(always)]
 pub(crate) fn add_round_key(state: &mut [u8; 16], round_key: &[u8; 16]) {
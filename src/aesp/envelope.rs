@@ -0,0 +1,159 @@
+//! Versioned, self-describing ciphertext envelope, produced by [seal](crate::Cipher::seal) and read by
+//! [open](crate::Cipher::open).
+//!
+//! Format: `MAGIC (4 bytes) || Version (1 byte) || Mode (1 byte) || Payload`, where the payload is laid out
+//! as the output of the mode-specific encryption function (e.g. [encrypt_gcm](crate::Cipher::encrypt_gcm)).
+//! In GCM envelopes the header is also authenticated, as if it were prepended to the AAD.
+
+use crate::aesp::error::{Error, Result};
+use crate::aesp::modes::Mode;
+
+/// Identifies an aesp envelope.
+pub(crate) const MAGIC: [u8; 4] = *b"AESP";
+/// Current envelope format version. Bump when the header or any payload layout changes.
+pub(crate) const VERSION: u8 = 1;
+/// Length of the envelope header: magic, version, and mode.
+pub(crate) const HEADER_LEN: usize = 6;
+
+#[cfg(feature = "std")]
+fn mode_to_byte(mode: Mode) -> u8 {
+    match mode {
+        Mode::Ecb => 1,
+        Mode::Ctr => 2,
+        Mode::Gcm => 3,
+        #[cfg(feature = "testing")]
+        #[allow(deprecated)]
+        Mode::Null => 0xff,
+    }
+}
+
+fn mode_from_byte(byte: u8) -> Result<Mode> {
+    Ok(match byte {
+        1 => Mode::Ecb,
+        2 => Mode::Ctr,
+        3 => Mode::Gcm,
+        #[cfg(feature = "testing")]
+        #[allow(deprecated)]
+        0xff => Mode::Null,
+        _ => return Err(Error::UnknownEnvelopeMode { mode: byte }),
+    })
+}
+
+/// Builds the header for an envelope holding a payload encrypted under `mode`.
+#[cfg(feature = "std")]
+pub(crate) fn header(mode: Mode) -> [u8; HEADER_LEN] {
+    let mut header = [0u8; HEADER_LEN];
+    header[..4].copy_from_slice(&MAGIC);
+    header[4] = VERSION;
+    header[5] = mode_to_byte(mode);
+    header
+}
+
+/// Validates the header of `blob`, returning the mode and the payload that follows it.
+pub(crate) fn parse(blob: &[u8]) -> Result<(Mode, &[u8])> {
+    let (header, payload) = blob
+        .split_first_chunk::<HEADER_LEN>()
+        .ok_or(Error::InvalidEnvelope {
+            len: blob.len(),
+            context: "shorter than the 6-byte header",
+        })?;
+    if header[..4] != MAGIC {
+        return Err(Error::InvalidEnvelope {
+            len: blob.len(),
+            context: "missing magic bytes",
+        });
+    }
+    if header[4] != VERSION {
+        return Err(Error::UnsupportedEnvelopeVersion { version: header[4] });
+    }
+    Ok((mode_from_byte(header[5])?, payload))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Cipher, Key};
+
+    #[test]
+    fn seal_open_round_trip() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_256()?);
+        for mode in [Mode::Ecb, Mode::Ctr, Mode::Gcm] {
+            for pt in [&b""[..], b"Hello, World!", &[0x5a; 1000]] {
+                let blob = cipher.seal(pt, mode)?;
+                assert_eq!(blob[..4], MAGIC);
+                assert_eq!(blob[4], VERSION);
+                assert_eq!(parse(&blob)?.0, mode);
+                assert_eq!(cipher.open_unauthenticated(&blob)?, pt);
+                if mode == Mode::Gcm {
+                    assert_eq!(cipher.open(&blob)?, pt);
+                } else {
+                    assert!(matches!(cipher.open(&blob), Err(Error::UnauthenticatedEnvelope { mode: m }) if m == mode));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn gcm_header_cannot_be_rewritten() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_256()?);
+        let blob = cipher.seal(b"Hello, World!", Mode::Gcm)?;
+
+        // every other mode byte, including downgrades to ECB and CTR, fails rather than decrypting
+        for byte in 0..=u8::MAX {
+            if byte == blob[5] {
+                continue;
+            }
+            let mut rewritten = blob.clone();
+            rewritten[5] = byte;
+            assert!(cipher.open(&rewritten).is_err());
+            // open_unauthenticated is, by design, open to downgrades to any known unauthenticated mode
+            if mode_from_byte(byte).is_err() {
+                assert!(cipher.open_unauthenticated(&rewritten).is_err());
+            }
+        }
+
+        // with the header authenticated, the GCM payload alone no longer verifies
+        assert!(matches!(cipher.decrypt_gcm(&blob[HEADER_LEN..]), Err(Error::AuthFailed)));
+        Ok(())
+    }
+
+    #[test]
+    fn payload_matches_mode_layout() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_128()?);
+        let blob = cipher.seal(b"payload", Mode::Ecb)?;
+        assert_eq!(blob[HEADER_LEN..], cipher.encrypt_ecb(b"payload"));
+
+        // the GCM payload has the encrypt_gcm layout, with the header authenticated alongside it
+        let blob = cipher.seal(b"payload", Mode::Gcm)?;
+        assert_eq!(cipher.decrypt_gcm_with_header(&blob, HEADER_LEN)?.0, b"payload");
+        assert_eq!(blob.len(), HEADER_LEN + cipher.encrypt_gcm(b"payload", None)?.len());
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_bad_headers() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_128()?);
+        let blob = cipher.seal(b"data", Mode::Ctr)?;
+
+        for short in [&[][..], &blob[..3], &blob[..HEADER_LEN - 1]] {
+            assert!(matches!(cipher.open(short), Err(Error::InvalidEnvelope { .. })));
+        }
+
+        let mut bad_magic = blob.clone();
+        bad_magic[0] ^= 1;
+        assert!(matches!(cipher.open(&bad_magic), Err(Error::InvalidEnvelope { .. })));
+
+        let mut bad_version = blob.clone();
+        bad_version[4] = 2;
+        assert!(matches!(
+            cipher.open(&bad_version),
+            Err(Error::UnsupportedEnvelopeVersion { version: 2 })
+        ));
+
+        let mut bad_mode = blob.clone();
+        bad_mode[5] = 0x42;
+        assert!(matches!(cipher.open(&bad_mode), Err(Error::UnknownEnvelopeMode { mode: 0x42 })));
+        Ok(())
+    }
+}
@@ -1,17 +1,30 @@
+use alloc::format;
+use alloc::string::{String, ToString};
 use thiserror::Error;
+
+use crate::aesp::modes::Mode;
+#[cfg(feature = "std")]
 use rand::rand_core;
 
 /// AES Result type.
-pub type Result<T> = std::result::Result<T, Error>;
+pub type Result<T> = core::result::Result<T, Error>;
 
 /// AES Error type. 
 #[derive(Debug, Error)]
 #[non_exhaustive]
 pub enum Error {
     /// Computed authentication tag did not match input tag. Ciphertext and/or AAD has been modified since it was encrypted.
-    #[error("GCM authentication failed (invalid tag)")]
+    /// Also returned when an unwrapped key fails its integrity check.
+    #[error("authentication failed (invalid tag)")]
     AuthFailed,
 
+    /// Computed GCM tag did not match input tag, after `processed` bytes of ciphertext were authenticated.
+    /// Returned in place of [AuthFailed](crate::Error::AuthFailed) by
+    /// [decrypt_gcm_detailed](crate::Cipher::decrypt_gcm_detailed). The tag covers the whole ciphertext, so
+    /// `processed` is always its full length: GCM cannot verify part of a message, or locate a modification.
+    #[error("authentication failed (invalid tag) after {processed} ciphertext bytes")]
+    AuthFailedAfter { processed: usize },
+
     /// Attempted to encrypt or decrypt more than 2^32 16-byte blocks in GCM/CTR mode.
     #[error("input size caused counter overflow (maximum input size for 32 bit counter is 16 * 2^32 bytes)")]
     CounterOverflow,
@@ -20,15 +33,274 @@ pub enum Error {
     #[error("invalid key length: {len} bytes (expected 16, 24, or 32)")]
     InvalidKeyLength { len: usize },
 
+    /// Used a valid AES key with a mode that does not support its size, e.g. a 192-bit key with GCM-SIV.
+    #[error("unsupported key length: {len} bytes ({context})")]
+    UnsupportedKeyLength { len: usize, context: &'static str },
+
     /// Provided ciphertext that did not match the expected format of the mode of operation.
     #[error("invalid ciphertext length: {len} bytes ({context})")]
     InvalidCiphertext { len: usize, context: &'static str },
 
+    /// Provided an envelope that is too short or does not start with the envelope magic bytes.
+    #[error("invalid envelope: {len} bytes ({context})")]
+    InvalidEnvelope { len: usize, context: &'static str },
+
+    /// Provided an envelope written by an unsupported (likely newer) format version.
+    #[error("unsupported envelope version: {version}")]
+    UnsupportedEnvelopeVersion { version: u8 },
+
+    /// Provided an envelope whose mode byte does not correspond to a known mode of operation.
+    #[error("unknown envelope mode: {mode:#04x}")]
+    UnknownEnvelopeMode { mode: u8 },
+
+    /// Attempted to [open](crate::Cipher::open) an envelope whose header names a mode without authentication.
+    /// See [open_unauthenticated](crate::Cipher::open_unauthenticated).
+    #[error("envelope mode {mode:?} is not authenticated")]
+    UnauthenticatedEnvelope { mode: Mode },
+
+    /// Supplied an all-zero or blocklisted IV while weak IV rejection is enabled.
+    /// See [reject_weak_ivs](crate::Cipher::reject_weak_ivs).
+    #[error("weak nonce rejected (all zeros or blocklisted)")]
+    WeakNonce,
+
+    /// A known-answer test in [self_test](crate::self_test) produced the wrong output. The implementation
+    /// should not be used.
+    #[error("self-test failed: {context}")]
+    SelfTestFailed { context: &'static str },
+
+    /// A [NonceSequence](crate::NonceSequence) has used every counter value, and would repeat a nonce if continued.
+    #[error("nonce sequence exhausted (counter would wrap)")]
+    NonceExhausted,
+
+    /// Encrypted under an IV that this cipher has already used, while nonce tracking is enabled.
+    /// See [with_nonce_tracking](crate::Cipher::with_nonce_tracking).
+    #[error("nonce reused (already used for encryption under this key)")]
+    NonceReused,
+
+    /// Provided a nonce (IV) whose length is not supported by the mode, e.g. anything but 12 bytes through
+    /// the [Aead](crate::Aead) trait, or an empty IV for GCM.
+    #[error("invalid nonce length: {len} bytes")]
+    InvalidNonceLength { len: usize },
+
+    /// Requested an authentication tag length that the mode does not support, e.g. an odd number of bytes for
+    /// CCM, or a GCM tag length outside the NIST-approved set.
+    #[error("invalid tag length: {bits} bits")]
+    InvalidTagLength { bits: usize },
+
     /// Provided plaintext that did not match the expected format of the mode of operation.
     #[error("invalid ECB input length: {len} bytes (must be a multiple of 16)")]
     InvalidECBInput { len: usize },
 
+    /// Attempted to wrap an empty key. Key wrap input must be at least 1 byte.
+    #[error("invalid key wrap input length: {len} bytes (must be at least 1)")]
+    InvalidKeyWrapInput { len: usize },
+
+    /// Provided input that exceeds the maximum size supported by the operation.
+    #[error("input too long: {len} bytes (maximum {max} bytes)")]
+    InputTooLong { len: usize, max: usize },
+
+    /// Provided input that is shorter than the minimum size required by the operation.
+    #[error("input too short: {len} bytes (minimum {min} bytes)")]
+    InputTooShort { len: usize, min: usize },
+
+    /// Provided a hex string that could not be parsed. `len` is the number of hex digits, excluding whitespace.
+    #[error("invalid hex string: {len} digits ({context})")]
+    InvalidHex { len: usize, context: &'static str },
+
+    /// Provided a base64 string that could not be decoded. `len` is the length of the input string.
+    #[error("invalid base64 string: {len} characters ({context})")]
+    InvalidBase64 { len: usize, context: &'static str },
+
+    /// Provided a PEM key that does not have the expected `AES KEY` armour lines.
+    #[error("invalid PEM key ({context})")]
+    InvalidPem { context: &'static str },
+
+    /// Attempted password-based key derivation with an iteration count of zero.
+    #[error("invalid iteration count: {count} (must be at least 1)")]
+    InvalidIterationCount { count: u32 },
+
     /// OS RNG failed during random key generation.
+    #[cfg(feature = "std")]
     #[error("OS RNG failed in random key generation")]
     Rng(#[from] rand_core::OsError),
 }
+
+/// Broad category of an [Error], for branching on the kind of failure without matching every variant.
+/// Returned by [Error::kind].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// Authentication failed: the ciphertext, tag, AAD or wrapped key has been modified, or the key is wrong.
+    /// Also covers envelopes whose mode has no authentication.
+    Auth,
+    /// Input was malformed or truncated: ciphertexts, envelopes, hex, base64 or PEM.
+    Format,
+    /// A size or counter limit was exceeded: counters, nonce sequences and maximum input lengths.
+    Overflow,
+    /// A key had an invalid length, or a length the mode does not support.
+    KeyLength,
+    /// A parameter was rejected: nonce or tag lengths, weak or reused nonces and iteration counts.
+    Parameter,
+    /// A known-answer self-test failed.
+    SelfTest,
+    /// The OS random number generator failed.
+    Rng,
+}
+
+impl Error {
+    /// Returns the broad category of this error.
+    ///
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// use aesp::{Cipher, ErrorKind, Key};
+    ///
+    /// let cipher = Cipher::new(&Key::rand_key_256()?);
+    /// let mut ciphertext = cipher.encrypt_gcm_with_iv(b"Hello, World!", None, &[0x01; 12])?;
+    /// ciphertext[20] ^= 1;
+    /// let err = cipher.decrypt_gcm(&ciphertext).unwrap_err();
+    /// assert_eq!(err.kind(), ErrorKind::Auth);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Error::AuthFailed | Error::AuthFailedAfter { .. } | Error::UnauthenticatedEnvelope { .. } => {
+                ErrorKind::Auth
+            }
+            Error::CounterOverflow | Error::NonceExhausted | Error::InputTooLong { .. } => ErrorKind::Overflow,
+            Error::InvalidKeyLength { .. } | Error::UnsupportedKeyLength { .. } => ErrorKind::KeyLength,
+            Error::InvalidCiphertext { .. }
+            | Error::InvalidEnvelope { .. }
+            | Error::UnsupportedEnvelopeVersion { .. }
+            | Error::UnknownEnvelopeMode { .. }
+            | Error::InvalidECBInput { .. }
+            | Error::InvalidKeyWrapInput { .. }
+            | Error::InputTooShort { .. }
+            | Error::InvalidHex { .. }
+            | Error::InvalidBase64 { .. }
+            | Error::InvalidPem { .. } => ErrorKind::Format,
+            Error::WeakNonce
+            | Error::NonceReused
+            | Error::InvalidNonceLength { .. }
+            | Error::InvalidTagLength { .. }
+            | Error::InvalidIterationCount { .. } => ErrorKind::Parameter,
+            Error::SelfTestFailed { .. } => ErrorKind::SelfTest,
+            #[cfg(feature = "std")]
+            Error::Rng(_) => ErrorKind::Rng,
+        }
+    }
+
+    /// Returns an explanation of this error for end users, e.g. for a CLI or GUI, without the internal detail
+    /// (such as which framing check failed) that the [Display](core::fmt::Display) message includes for logs.
+    ///
+    /// ## Examples
+    /// ```
+    /// use aesp::Error;
+    ///
+    /// let err = Error::InvalidCiphertext { len: 3, context: "CTR: missing 12-byte IV" };
+    /// assert_eq!(err.to_string(), "invalid ciphertext length: 3 bytes (CTR: missing 12-byte IV)");
+    /// assert_eq!(err.user_message(), "the encrypted data is corrupted or truncated");
+    /// ```
+    pub fn user_message(&self) -> String {
+        let message = match self {
+            Error::AuthFailed | Error::AuthFailedAfter { .. } => {
+                "authentication failed: the data has been modified, or the key or associated data is wrong"
+            }
+            Error::CounterOverflow | Error::InputTooLong { .. } => "the input is too large for this mode",
+            Error::NonceExhausted => "every nonce for this key has been used; a new key is needed",
+            Error::InvalidKeyLength { len } => return format!("the key is {len} bytes, but must be 16, 24 or 32 bytes"),
+            Error::UnsupportedKeyLength { .. } => "this mode does not support keys of this size",
+            Error::InvalidCiphertext { .. } => "the encrypted data is corrupted or truncated",
+            Error::InvalidEnvelope { .. } | Error::UnknownEnvelopeMode { .. } => {
+                "the data is not in a recognised encrypted format"
+            }
+            Error::UnauthenticatedEnvelope { .. } => "the data is not authenticated, so it was not decrypted",
+            Error::UnsupportedEnvelopeVersion { .. } => "the data was encrypted by a newer version of this software",
+            Error::WeakNonce => "the nonce was rejected as insecure",
+            Error::NonceReused => "the nonce has already been used with this key",
+            Error::SelfTestFailed { .. } => "the encryption self-test failed, so encryption is unavailable",
+            Error::InvalidNonceLength { .. } => "the nonce length is not supported by this mode",
+            Error::InvalidTagLength { .. } => "the authentication tag length is not supported by this mode",
+            Error::InvalidECBInput { .. } => "the input is not a whole number of 16-byte blocks",
+            Error::InvalidKeyWrapInput { .. } => "there is no key material to wrap",
+            Error::InputTooShort { .. } => "the input is too short for this mode",
+            Error::InvalidHex { .. } => "the input is not valid hexadecimal",
+            Error::InvalidBase64 { .. } => "the input is not valid base64",
+            Error::InvalidPem { .. } => "the key is not a valid PEM-encoded AES key",
+            Error::InvalidIterationCount { .. } => "the iteration count must be at least 1",
+            #[cfg(feature = "std")]
+            Error::Rng(_) => "the system random number generator failed",
+        };
+        message.to_string()
+    }
+}
+
+// lets infallible RNGs (any `RngCore`) be passed where a fallible RNG is accepted
+impl From<core::convert::Infallible> for Error {
+    fn from(e: core::convert::Infallible) -> Self {
+        match e {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_variant_has_a_kind() {
+        let cases = [
+            (Error::AuthFailed, ErrorKind::Auth),
+            (Error::AuthFailedAfter { processed: 0 }, ErrorKind::Auth),
+            (Error::CounterOverflow, ErrorKind::Overflow),
+            (Error::NonceExhausted, ErrorKind::Overflow),
+            (Error::InputTooLong { len: 2, max: 1 }, ErrorKind::Overflow),
+            (Error::InvalidKeyLength { len: 5 }, ErrorKind::KeyLength),
+            (Error::UnsupportedKeyLength { len: 24, context: "" }, ErrorKind::KeyLength),
+            (Error::InvalidCiphertext { len: 0, context: "" }, ErrorKind::Format),
+            (Error::InvalidEnvelope { len: 0, context: "" }, ErrorKind::Format),
+            (Error::UnsupportedEnvelopeVersion { version: 9 }, ErrorKind::Format),
+            (Error::UnknownEnvelopeMode { mode: 9 }, ErrorKind::Format),
+            (Error::UnauthenticatedEnvelope { mode: Mode::Ctr }, ErrorKind::Auth),
+            (Error::InvalidECBInput { len: 3 }, ErrorKind::Format),
+            (Error::InvalidKeyWrapInput { len: 0 }, ErrorKind::Format),
+            (Error::InputTooShort { len: 0, min: 1 }, ErrorKind::Format),
+            (Error::InvalidHex { len: 1, context: "" }, ErrorKind::Format),
+            (Error::InvalidBase64 { len: 1, context: "" }, ErrorKind::Format),
+            (Error::InvalidPem { context: "" }, ErrorKind::Format),
+            (Error::WeakNonce, ErrorKind::Parameter),
+            (Error::NonceReused, ErrorKind::Parameter),
+            (Error::InvalidNonceLength { len: 0 }, ErrorKind::Parameter),
+            (Error::InvalidTagLength { bits: 8 }, ErrorKind::Parameter),
+            (Error::InvalidIterationCount { count: 0 }, ErrorKind::Parameter),
+            (Error::SelfTestFailed { context: "" }, ErrorKind::SelfTest),
+        ];
+        for (err, kind) in cases {
+            assert_eq!(err.kind(), kind, "{err:?}");
+        }
+    }
+
+    #[test]
+    fn user_message_omits_internal_detail() {
+        let cases = [
+            Error::InvalidCiphertext { len: 20, context: "insufficient bytes given aad_len" },
+            Error::InvalidEnvelope { len: 2, context: "missing magic bytes" },
+            Error::InvalidHex { len: 3, context: "odd number of digits" },
+            Error::UnsupportedKeyLength { len: 24, context: "GCM-SIV requires a 128 or 256-bit key" },
+            Error::SelfTestFailed { context: "GCM encryption" },
+        ];
+        for err in cases {
+            let (detailed, user) = (err.to_string(), err.user_message());
+            assert_ne!(detailed, user);
+            assert!(!user.contains("bytes ("), "{user}");
+            assert!(!user.contains(&detailed), "{user}");
+        }
+
+        // messages that are already user-facing keep their meaning
+        assert!(Error::AuthFailed.user_message().starts_with("authentication failed"));
+        assert_eq!(
+            Error::InvalidKeyLength { len: 5 }.user_message(),
+            "the key is 5 bytes, but must be 16, 24 or 32 bytes"
+        );
+    }
+}
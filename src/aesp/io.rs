@@ -0,0 +1,519 @@
+use std::io::{self, Read, Write};
+
+use crate::aesp::cipher::Cipher;
+use crate::aesp::error::{Error, Result};
+use crate::aesp::modes::{CtrStream, GHashState, GcmEncryptor, check_lengths, derive_j0};
+use crate::aesp::util::{ct_eq, random_iv};
+
+/// Size of the ciphertext buffer used by [DecryptReader] in GCM mode, excluding the held-back tag.
+const READ_CHUNK: usize = 8 * 1024;
+
+/// Stream context for [EncryptWriter].
+enum EncryptStream<'a> {
+    Ctr(CtrStream<'a>),
+    Gcm(GcmEncryptor<'a>),
+}
+
+/// [Write] adapter that encrypts everything written to it before forwarding it to an inner writer.
+///
+/// Output matches the one-shot functions, so it can be decrypted in memory with
+/// [decrypt_ctr](crate::Cipher::decrypt_ctr) or [decrypt_gcm](crate::Cipher::decrypt_gcm), or streamed back
+/// through a [DecryptReader]:
+/// - CTR: `IV (12 bytes) || Ciphertext`
+/// - GCM: `IV (12 bytes) || AAD length (8 bytes) || AAD || Ciphertext || Tag (16 bytes)`
+///
+/// The header (IV, plus the AAD length and AAD for GCM) is written on the first call to `write`. Writes may
+/// be any size. [finish](crate::EncryptWriter::finish) must be called once all plaintext has been written:
+/// for GCM it appends the tag, and without it the output cannot be authenticated. If a write fails, the
+/// writer should be discarded, as the output may be incomplete.
+///
+/// ## Examples
+/// ```
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use std::io::Write;
+/// use aesp::{Cipher, EncryptWriter, Key};
+///
+/// let cipher = Cipher::new(&Key::rand_key_256()?);
+///
+/// let mut writer = EncryptWriter::gcm(&cipher, Vec::new(), Some(b"header"))?;
+/// writer.write_all(b"Hello, ")?;
+/// writer.write_all(b"World!")?;
+/// let ciphertext = writer.finish()?;
+///
+/// let (plaintext, aad) = cipher.decrypt_gcm(&ciphertext)?;
+/// assert_eq!(plaintext, b"Hello, World!");
+/// assert_eq!(aad.as_deref(), Some(&b"header"[..]));
+/// # Ok(())
+/// # }
+/// ```
+pub struct EncryptWriter<'a, W: Write> {
+    inner: W,
+    stream: EncryptStream<'a>,
+    /// header not yet written to `inner`
+    header: Option<Vec<u8>>,
+}
+
+impl<'a, W: Write> EncryptWriter<'a, W> {
+    /// Encrypts in **counter mode** under a random IV. See [encrypt_ctr](crate::Cipher::encrypt_ctr).
+    pub fn ctr(cipher: &'a Cipher, inner: W) -> Result<Self> {
+        Ok(Self::ctr_with_iv(cipher, inner, &random_iv()?))
+    }
+
+    /// Encrypts in **Galois/counter mode** under a random IV, authenticating `aad` alongside the
+    /// ciphertext. See [encrypt_gcm](crate::Cipher::encrypt_gcm).
+    pub fn gcm(cipher: &'a Cipher, inner: W, aad: Option<&[u8]>) -> Result<Self> {
+        Self::gcm_with_iv(cipher, inner, aad, &random_iv()?)
+    }
+
+    pub(crate) fn ctr_with_iv(cipher: &'a Cipher, inner: W, iv: &[u8; 12]) -> Self {
+        Self {
+            inner,
+            stream: EncryptStream::Ctr(CtrStream::new(cipher, iv)),
+            header: Some(iv.to_vec()),
+        }
+    }
+
+    pub(crate) fn gcm_with_iv(cipher: &'a Cipher, inner: W, aad: Option<&[u8]>, iv: &[u8; 12]) -> Result<Self> {
+        let aad = aad.unwrap_or(&[]);
+        check_lengths(0, aad.len())?;
+
+        let mut enc = cipher.gcm_encryptor(iv);
+        enc.aad(aad);
+
+        // header: IV || AAD length (8 bytes) || AAD
+        let mut header = Vec::with_capacity(20 + aad.len());
+        header.extend_from_slice(iv);
+        header.extend_from_slice(&(aad.len() as u64).to_be_bytes());
+        header.extend_from_slice(aad);
+
+        Ok(Self {
+            inner,
+            stream: EncryptStream::Gcm(enc),
+            header: Some(header),
+        })
+    }
+
+    /// Finishes encryption: writes the header if nothing has been written yet, appends the tag for GCM,
+    /// flushes, and returns the inner writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.write_header()?;
+        match self.stream {
+            EncryptStream::Ctr(stream) => self.inner.write_all(&stream.finalize())?,
+            EncryptStream::Gcm(enc) => self.inner.write_all(&enc.finish())?,
+        }
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+
+    fn write_header(&mut self) -> io::Result<()> {
+        if let Some(header) = self.header.take() {
+            self.inner.write_all(&header)?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for EncryptWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.write_header()?;
+        let ciphertext = match &mut self.stream {
+            EncryptStream::Ctr(stream) => stream.update(buf),
+            EncryptStream::Gcm(enc) => enc.update(buf),
+        }
+        .map_err(io::Error::other)?;
+
+        self.inner.write_all(&ciphertext)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Stream context for [DecryptReader], created once the header has been read.
+enum DecryptStream<'a> {
+    Ctr(CtrStream<'a>),
+    Gcm {
+        ctr: CtrStream<'a>,
+        ghash: GHashState<'a>,
+        j0: [u8; 16],
+    },
+}
+
+/// [Read] adapter that decrypts the output of an [EncryptWriter] (or of [encrypt_ctr](crate::Cipher::encrypt_ctr)
+/// / [encrypt_gcm](crate::Cipher::encrypt_gcm)) from an inner reader.
+///
+/// The header is read from the inner reader on the first call to `read`. Plaintext is then yielded as
+/// ciphertext arrives, without buffering the whole message.
+///
+/// In GCM mode, the final 16 bytes of the stream are the tag, so the reader always holds back the last 16
+/// bytes it has read and only releases plaintext that cannot be part of the tag. The tag is verified when
+/// the inner reader reaches EOF. A mismatch is reported as an [io::Error] of kind
+/// [InvalidData](io::ErrorKind::InvalidData) wrapping [AuthFailed](crate::Error::AuthFailed).
+///
+/// **Important**: plaintext returned before EOF has not been authenticated yet. Do not act on it until
+/// `read` has returned `Ok(0)`. Where that is not possible, use [GcmDecryptor](crate::GcmDecryptor), which
+/// releases nothing until the tag has been verified.
+///
+/// ## Examples
+/// ```
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use std::io::{Read, Write};
+/// use aesp::{Cipher, DecryptReader, EncryptWriter, Key};
+///
+/// let cipher = Cipher::new(&Key::rand_key_256()?);
+///
+/// let mut writer = EncryptWriter::gcm(&cipher, Vec::new(), Some(b"header"))?;
+/// writer.write_all(b"Hello, World!")?;
+/// let ciphertext = writer.finish()?;
+///
+/// let mut reader = DecryptReader::gcm(&cipher, &ciphertext[..]);
+/// let mut plaintext = Vec::new();
+/// reader.read_to_end(&mut plaintext)?;
+/// assert_eq!(plaintext, b"Hello, World!");
+/// assert_eq!(reader.aad()?, Some(&b"header"[..]));
+/// # Ok(())
+/// # }
+/// ```
+pub struct DecryptReader<'a, R: Read> {
+    inner: R,
+    cipher: &'a Cipher,
+    gcm: bool,
+    /// `None` until the header has been read, and again once the GCM tag has been verified
+    stream: Option<DecryptStream<'a>>,
+    aad: Vec<u8>,
+    /// GCM only: ciphertext read but not yet released, `pending[pos..]`. The last 16 bytes may be the tag.
+    pending: Vec<u8>,
+    pos: usize,
+    /// inner reader has reached EOF and the tag (if any) has been verified
+    done: bool,
+}
+
+impl<'a, R: Read> DecryptReader<'a, R> {
+    /// Decrypts **counter mode** input: `IV (12 bytes) || Ciphertext`.
+    pub fn ctr(cipher: &'a Cipher, inner: R) -> Self {
+        Self::new(cipher, inner, false)
+    }
+
+    /// Decrypts **Galois/counter mode** input:
+    /// `IV (12 bytes) || AAD length (8 bytes) || AAD || Ciphertext || Tag (16 bytes)`.
+    pub fn gcm(cipher: &'a Cipher, inner: R) -> Self {
+        Self::new(cipher, inner, true)
+    }
+
+    fn new(cipher: &'a Cipher, inner: R, gcm: bool) -> Self {
+        Self {
+            inner,
+            cipher,
+            gcm,
+            stream: None,
+            aad: Vec::new(),
+            pending: Vec::new(),
+            pos: 0,
+            done: false,
+        }
+    }
+
+    /// Returns the AAD of a GCM stream, reading the header first if necessary. Returns `None` for CTR, or if
+    /// the AAD is empty. Like the plaintext, the AAD is only authenticated once `read` has returned `Ok(0)`.
+    pub fn aad(&mut self) -> io::Result<Option<&[u8]>> {
+        self.read_header()?;
+        Ok(if self.aad.is_empty() { None } else { Some(&self.aad) })
+    }
+
+    /// Returns the inner reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn read_header(&mut self) -> io::Result<()> {
+        // the GCM stream is consumed by tag verification, after which done is set
+        if self.stream.is_some() || self.done {
+            return Ok(());
+        }
+
+        let mut iv = [0u8; 12];
+        self.inner.read_exact(&mut iv)?;
+        if !self.gcm {
+            self.stream = Some(DecryptStream::Ctr(CtrStream::new(self.cipher, &iv)));
+            return Ok(());
+        }
+
+        // AAD length (8 bytes) || AAD. read through take() so that a forged length cannot force a huge allocation
+        let mut aad_len = [0u8; 8];
+        self.inner.read_exact(&mut aad_len)?;
+        let aad_len = u64::from_be_bytes(aad_len);
+        let len = usize::try_from(aad_len).unwrap_or(usize::MAX);
+        check_lengths(0, len).map_err(invalid_data)?;
+        (&mut self.inner).take(aad_len).read_to_end(&mut self.aad)?;
+        if self.aad.len() != len {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+
+        let mut ghash = GHashState::new(self.cipher.ghash_key());
+        ghash.aad(&self.aad);
+        self.stream = Some(DecryptStream::Gcm {
+            ctr: CtrStream::with_counter(self.cipher, &iv, 2), // ctr starts at 2, as per NIST spec
+            ghash,
+            j0: derive_j0(self.cipher.ghash_key(), &iv),
+        });
+        Ok(())
+    }
+
+    /// Reads from the inner reader until more than 16 bytes are pending or EOF is reached, then verifies the
+    /// tag on EOF.
+    fn fill_pending(&mut self) -> io::Result<()> {
+        // keep the (at most 16) unreleased bytes at the front, leaving room for a new chunk
+        if self.pos > 0 {
+            self.pending.drain(..self.pos);
+            self.pos = 0;
+        }
+
+        while self.pending.len() <= 16 {
+            let filled = self.pending.len();
+            self.pending.resize(16 + READ_CHUNK, 0);
+            let result = self.inner.read(&mut self.pending[filled..]);
+            self.pending.truncate(filled + *result.as_ref().unwrap_or(&0));
+            match result {
+                Ok(0) => return self.verify_tag(),
+                Ok(_) => {}
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    fn verify_tag(&mut self) -> io::Result<()> {
+        if self.pending.len() < 16 {
+            return Err(invalid_data(Error::InvalidCiphertext {
+                len: self.pending.len(),
+                context: "GCM stream: missing tag",
+            }));
+        }
+
+        let Some(DecryptStream::Gcm { ghash, j0, .. }) = self.stream.take() else {
+            unreachable!("tag only verified in GCM mode")
+        };
+        let computed_tag = ghash.tag(self.cipher.round_keys(), &j0);
+        if !ct_eq(&self.pending, &computed_tag) {
+            return Err(invalid_data(Error::AuthFailed));
+        }
+        self.done = true;
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for DecryptReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.done || buf.is_empty() {
+            return Ok(0);
+        }
+        self.read_header()?;
+
+        match &mut self.stream {
+            Some(DecryptStream::Ctr(ctr)) => {
+                let n = self.inner.read(buf)?;
+                ctr.apply_keystream(&mut buf[..n]).map_err(io::Error::other)?;
+                Ok(n)
+            }
+            Some(DecryptStream::Gcm { .. }) => {
+                if self.pending.len() - self.pos <= 16 {
+                    self.fill_pending()?;
+                    if self.done {
+                        return Ok(0);
+                    }
+                }
+
+                // release everything except the last 16 bytes, which may be the tag
+                let available = self.pending.len() - self.pos - 16;
+                let n = available.min(buf.len());
+                let out = &mut buf[..n];
+                out.copy_from_slice(&self.pending[self.pos..self.pos + n]);
+                self.pos += n;
+
+                let Some(DecryptStream::Gcm { ctr, ghash, .. }) = &mut self.stream else {
+                    unreachable!()
+                };
+                ghash.ciphertext(out);
+                ctr.apply_keystream(out).map_err(io::Error::other)?;
+                Ok(n)
+            }
+            None => unreachable!("header read above"),
+        }
+    }
+}
+
+/// Wraps a library error as an [io::Error] of kind [InvalidData](io::ErrorKind::InvalidData).
+fn invalid_data(e: Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+#[cfg(test)]
+mod test_io {
+    use super::*;
+    use crate::Key;
+
+    fn test_data(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 11 + 5) as u8).collect()
+    }
+
+    fn write_chunked<W: Write>(mut writer: EncryptWriter<'_, W>, data: &[u8]) -> io::Result<W> {
+        for (i, chunk) in data.chunks(1000).enumerate() {
+            // split each chunk unevenly to exercise partial blocks
+            let (a, b) = chunk.split_at((i * 7) % chunk.len());
+            writer.write_all(a)?;
+            writer.write_all(b)?;
+        }
+        writer.finish()
+    }
+
+    #[test]
+    fn ctr_round_trip() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let cipher = Cipher::new(&Key::rand_key_256()?);
+        let iv = [0x61; 12];
+        for len in [0, 1, 15, 16, 17, 5000, 100_003] {
+            let pt = test_data(len);
+            let out = write_chunked(EncryptWriter::ctr_with_iv(&cipher, Vec::new(), &iv), &pt)?;
+
+            assert_eq!(out[..12], iv);
+            assert_eq!(out[12..], CtrStream::new(&cipher, &iv).update(&pt)?);
+            assert_eq!(cipher.decrypt_ctr(&out)?, pt, "length {len}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn gcm_round_trip() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let cipher = Cipher::new(&Key::rand_key_128()?);
+        let iv = [0x62; 12];
+        for aad in [None, Some(&b"header"[..])] {
+            for len in [0, 1, 15, 16, 17, 5000, 100_003] {
+                let pt = test_data(len);
+                let writer = EncryptWriter::gcm_with_iv(&cipher, Vec::new(), aad, &iv)?;
+                let out = write_chunked(writer, &pt)?;
+
+                assert_eq!(out, cipher.encrypt_gcm_with_iv(&pt, aad, &iv)?, "length {len}");
+                let (decrypted, returned_aad) = cipher.decrypt_gcm(&out)?;
+                assert_eq!(decrypted, pt);
+                assert_eq!(returned_aad.as_deref(), aad);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn header_written_lazily() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let cipher = Cipher::new(&Key::rand_key_128()?);
+
+        let mut writer = EncryptWriter::gcm(&cipher, Vec::new(), Some(b"aad"))?;
+        writer.flush()?;
+        assert!(writer.inner.is_empty());
+
+        // IV (12) || AAD length (8) || AAD (3) || Ciphertext (4), then Tag (16) on finish
+        writer.write_all(b"data")?;
+        assert_eq!(writer.inner.len(), 27);
+        let out = writer.finish()?;
+        assert_eq!(out.len(), 43);
+        assert_eq!(cipher.decrypt_gcm(&out)?.0, b"data");
+
+        // header is still written if no plaintext ever is
+        let out = EncryptWriter::gcm(&cipher, Vec::new(), None)?.finish()?;
+        assert_eq!(out.len(), 36);
+        assert!(cipher.decrypt_gcm(&out)?.0.is_empty());
+        Ok(())
+    }
+
+    /// Inner reader returning at most `max` bytes per call, to exercise partial reads.
+    struct Trickle<'a> {
+        data: &'a [u8],
+        max: usize,
+    }
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = self.max.min(buf.len()).min(self.data.len());
+            buf[..n].copy_from_slice(&self.data[..n]);
+            self.data = &self.data[n..];
+            Ok(n)
+        }
+    }
+
+    fn read_chunked<R: Read>(mut reader: DecryptReader<'_, R>, buf_len: usize) -> io::Result<Vec<u8>> {
+        let mut out = Vec::new();
+        let mut buf = vec![0u8; buf_len];
+        loop {
+            match reader.read(&mut buf)? {
+                0 => return Ok(out),
+                n => out.extend_from_slice(&buf[..n]),
+            }
+        }
+    }
+
+    fn auth_failed(e: &io::Error) -> bool {
+        e.kind() == io::ErrorKind::InvalidData
+            && matches!(e.get_ref().and_then(|e| e.downcast_ref()), Some(Error::AuthFailed))
+    }
+
+    #[test]
+    fn pipe_multi_megabyte() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let cipher = Cipher::new(&Key::rand_key_256()?);
+        let pt = test_data(3 * 1024 * 1024 + 7);
+
+        let ctr = write_chunked(EncryptWriter::ctr(&cipher, Vec::new())?, &pt)?;
+        let gcm = write_chunked(EncryptWriter::gcm(&cipher, Vec::new(), Some(b"aad"))?, &pt)?;
+
+        let mut decrypted = Vec::new();
+        DecryptReader::ctr(&cipher, &ctr[..]).read_to_end(&mut decrypted)?;
+        assert!(decrypted == pt, "CTR round trip");
+
+        let mut reader = DecryptReader::gcm(&cipher, &gcm[..]);
+        decrypted.clear();
+        reader.read_to_end(&mut decrypted)?;
+        assert!(decrypted == pt, "GCM round trip");
+        assert_eq!(reader.aad()?, Some(&b"aad"[..]));
+        Ok(())
+    }
+
+    #[test]
+    fn gcm_partial_reads() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let cipher = Cipher::new(&Key::rand_key_128()?);
+        for len in [0, 1, 15, 16, 17, 100, 20_000] {
+            let pt = test_data(len);
+            let ct = cipher.encrypt_gcm(&pt, None)?;
+            for (max, buf_len) in [(1, 1), (7, 3), (17, 64), (20_000, 5), (100_000, 100_000)] {
+                let reader = DecryptReader::gcm(&cipher, Trickle { data: &ct, max });
+                assert_eq!(read_chunked(reader, buf_len)?, pt, "length {len}, reads of {max}/{buf_len}");
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn gcm_rejects_tampering() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let cipher = Cipher::new(&Key::rand_key_128()?);
+        let ct = cipher.encrypt_gcm(&test_data(50_000), Some(b"aad"))?;
+
+        // ciphertext, tag, and AAD
+        for i in [30, ct.len() / 2, ct.len() - 1, 21] {
+            let mut tampered = ct.clone();
+            tampered[i] ^= 1;
+            let err = DecryptReader::gcm(&cipher, &tampered[..]).read_to_end(&mut Vec::new()).unwrap_err();
+            assert!(auth_failed(&err), "byte {i}: {err}");
+        }
+
+        // truncated before the end of the tag
+        let err = DecryptReader::gcm(&cipher, &ct[..23 + 10]).read_to_end(&mut Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let err = DecryptReader::gcm(&cipher, &ct[..10]).read_to_end(&mut Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+
+        // forged AAD length
+        let mut forged = ct.clone();
+        forged[12..20].copy_from_slice(&u64::MAX.to_be_bytes());
+        let err = DecryptReader::gcm(&cipher, &forged[..]).read_to_end(&mut Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        Ok(())
+    }
+}
@@ -1,7 +1,39 @@
-use rand::TryRngCore;
+use alloc::string::String;
+use rand::TryCryptoRng;
+#[cfg(feature = "std")]
 use rand::rngs::OsRng;
 
 use crate::aesp::error::{Error, Result};
+use crate::aesp::util::{decode_hex, encode_hex, zeroize};
+#[cfg(feature = "pem")]
+use crate::aesp::util::{decode_b64, encode_b64};
+
+#[cfg(feature = "pem")]
+const PEM_BEGIN: &str = "-----BEGIN AES KEY-----";
+#[cfg(feature = "pem")]
+const PEM_END: &str = "-----END AES KEY-----";
+
+/// AES key size.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum KeySize {
+    /// 128-bit key (AES-128).
+    Bits128,
+    /// 192-bit key (AES-192).
+    Bits192,
+    /// 256-bit key (AES-256).
+    Bits256,
+}
+
+impl KeySize {
+    /// Length of a key of this size in bytes.
+    pub fn byte_len(self) -> usize {
+        match self {
+            KeySize::Bits128 => 16,
+            KeySize::Bits192 => 24,
+            KeySize::Bits256 => 32,
+        }
+    }
+}
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 enum KeyBytes {
@@ -44,37 +76,78 @@ enum KeyBytes {
 /// # Ok(())
 /// # }
 /// ```
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq)]
 pub struct Key {
     bytes: KeyBytes,
 }
 
 impl Key {
+    /// Generate a random key of the given size. Returns Error if OsRng fails.
+    ///
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// use aesp::{Key, KeySize};
+    ///
+    /// let key = Key::generate(KeySize::Bits192)?;
+    /// assert_eq!(key.size(), KeySize::Bits192);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn generate(size: KeySize) -> Result<Self> {
+        Self::generate_with_rng(&mut OsRng, size)
+    }
+
     /// Generate a random 128-bit key. Returns Error if OsRng fails.
+    #[cfg(feature = "std")]
     pub fn rand_key_128() -> Result<Self> {
-        let mut k = [0u8; 16];
-        OsRng.try_fill_bytes(&mut k)?;
-        Ok(Self {
-            bytes: KeyBytes::K128(k),
-        })
+        Self::generate(KeySize::Bits128)
     }
 
     /// Generate a random 192-bit key. Returns Error if OsRng fails.
+    #[cfg(feature = "std")]
     pub fn rand_key_192() -> Result<Self> {
-        let mut k = [0u8; 24];
-        OsRng.try_fill_bytes(&mut k)?;
-        Ok(Self {
-            bytes: KeyBytes::K192(k),
-        })
+        Self::generate(KeySize::Bits192)
     }
 
     /// Generate a random 256-bit key. Returns Error if OsRng fails.
+    #[cfg(feature = "std")]
     pub fn rand_key_256() -> Result<Self> {
+        Self::generate(KeySize::Bits256)
+    }
+
+    /// Generate a random key of the given size from a caller-supplied RNG, e.g. a seeded `ChaCha20Rng` for
+    /// reproducible tests, or a hardware RNG. Unlike [generate](crate::Key::generate), this is available
+    /// without the `std` feature. Returns Error if the RNG fails.
+    ///
+    /// Accepts any (fallible or infallible) cryptographically secure RNG whose error converts into [Error].
+    ///
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// use aesp::{Key, KeySize};
+    /// use rand_chacha::ChaCha20Rng;
+    /// use rand_chacha::rand_core::SeedableRng;
+    ///
+    /// // the same seed always produces the same key
+    /// let key = Key::generate_with_rng(&mut ChaCha20Rng::seed_from_u64(42), KeySize::Bits256)?;
+    /// assert_eq!(key, Key::generate_with_rng(&mut ChaCha20Rng::seed_from_u64(42), KeySize::Bits256)?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn generate_with_rng<R: TryCryptoRng + ?Sized>(rng: &mut R, size: KeySize) -> Result<Self>
+    where
+        Error: From<R::Error>,
+    {
         let mut k = [0u8; 32];
-        OsRng.try_fill_bytes(&mut k)?;
-        Ok(Self {
-            bytes: KeyBytes::K256(k),
-        })
+        let k = &mut k[..size.byte_len()];
+        let key = match rng.try_fill_bytes(k) {
+            Ok(()) => Self::try_from_slice(k),
+            Err(e) => Err(e.into()),
+        };
+        zeroize(k); // wipe even if the RNG failed part way
+        key
     }
 
     /// Attempts to build a key from a slice of bytes. Will return an InvalidKeyLength error
@@ -94,6 +167,197 @@ impl Key {
         })
     }
 
+    /// Attempts to build a key from a hex string, such as one read from a config file. Whitespace is
+    /// ignored and digits may be upper or lower case. Returns an InvalidHex error if the string has an odd
+    /// number of digits or contains a non-hex character, or an InvalidKeyLength error if it does not
+    /// decode to 16, 24, or 32 bytes.
+    ///
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// use aesp::Key;
+    ///
+    /// let key = Key::try_from_hex("2b7e1516 28aed2a6 abf71588 09cf4f3c")?;
+    /// assert_eq!(key.to_hex(), "2b7e151628aed2a6abf7158809cf4f3c");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn try_from_hex(s: &str) -> Result<Self> {
+        let mut bytes = decode_hex(s)?;
+        let key = Self::try_from_slice(&bytes);
+        zeroize(&mut bytes);
+        key
+    }
+
+    /// Returns the key as a lowercase hex string. The returned string is not wiped on drop, so avoid
+    /// holding on to it longer than necessary.
+    pub fn to_hex(&self) -> String {
+        encode_hex(self.as_bytes())
+    }
+
+    /// Attempts to build a key from a PEM container: a base64 body between `-----BEGIN AES KEY-----` and
+    /// `-----END AES KEY-----` lines, as written by [to_pem](crate::Key::to_pem). Surrounding whitespace and
+    /// line breaks within the body are ignored. Requires the `pem` feature.
+    ///
+    /// Returns an InvalidPem error if either armour line is missing or has a different label, an InvalidBase64
+    /// error if the body is malformed, or an InvalidKeyLength error if it does not decode to 16, 24, or 32 bytes.
+    ///
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// use aesp::Key;
+    ///
+    /// let pem = "-----BEGIN AES KEY-----\nK34VFiiu0qar9xWICc9PPA==\n-----END AES KEY-----\n";
+    /// let key = Key::from_pem(pem)?;
+    /// assert_eq!(key.to_hex(), "2b7e151628aed2a6abf7158809cf4f3c");
+    /// assert_eq!(key.to_pem(), pem);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "pem")]
+    pub fn from_pem(s: &str) -> Result<Self> {
+        let body = s
+            .trim()
+            .strip_prefix(PEM_BEGIN)
+            .ok_or(Error::InvalidPem { context: "missing BEGIN AES KEY line" })?
+            .strip_suffix(PEM_END)
+            .ok_or(Error::InvalidPem { context: "missing END AES KEY line" })?;
+        let body: String = body.chars().filter(|c| !c.is_ascii_whitespace()).collect();
+
+        let mut bytes = decode_b64(&body)?;
+        let key = Self::try_from_slice(&bytes);
+        zeroize(&mut bytes);
+        key
+    }
+
+    /// Returns the key in a PEM container, readable by [from_pem](crate::Key::from_pem). The body is base64,
+    /// which fits on a single line for every key size. As with [to_hex](crate::Key::to_hex), the returned
+    /// string is not wiped on drop. Requires the `pem` feature.
+    #[cfg(feature = "pem")]
+    pub fn to_pem(&self) -> String {
+        alloc::format!("{PEM_BEGIN}\n{}\n{PEM_END}\n", encode_b64(self.as_bytes()))
+    }
+
+    /// Derives a key from a password using PBKDF2-HMAC-SHA256 (RFC 8018). Requires the `kdf` feature.
+    ///
+    /// `salt` should be random, unique per password, and stored alongside the ciphertext. `iterations`
+    /// sets the work factor: higher values slow down brute-force attacks (OWASP recommends at least
+    /// 600,000 for PBKDF2-HMAC-SHA256). Returns an InvalidIterationCount error if `iterations` is zero.
+    ///
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// use aesp::{Key, KeySize};
+    ///
+    /// let key = Key::from_password_pbkdf2(b"correct horse", b"random salt", 1_000, KeySize::Bits256)?;
+    /// assert_eq!(key.as_bytes().len(), 32);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "kdf")]
+    pub fn from_password_pbkdf2(
+        password: &[u8],
+        salt: &[u8],
+        iterations: u32,
+        size: KeySize,
+    ) -> Result<Self> {
+        if iterations == 0 {
+            return Err(Error::InvalidIterationCount { count: iterations });
+        }
+
+        let mut k = [0u8; 32];
+        let k = &mut k[..size.byte_len()];
+        pbkdf2::pbkdf2_hmac::<sha2::Sha256>(password, salt, iterations, k);
+        let key = Self::try_from_slice(k);
+        zeroize(k);
+        key
+    }
+
+    /// Derives a key from a shared secret, such as the output of an ECDH key agreement, using the one-step
+    /// key derivation function of NIST SP 800-56C (section 4.1, option 1) with SHA-256. Requires the `kdf` feature.
+    ///
+    /// Output blocks are `K(i) = SHA-256(i || secret || info)`, for a 32-bit big-endian counter `i` starting
+    /// at 1, concatenated and truncated to the key size. Since SHA-256 produces 32 bytes, every key size needs
+    /// only `K(1)`. `info` binds the key to its context (e.g. party identifiers and the algorithm), so that
+    /// different uses of the same secret yield unrelated keys.
+    ///
+    /// This is not a password KDF: the secret must already be high-entropy. For passwords, use
+    /// [from_password_pbkdf2](crate::Key::from_password_pbkdf2).
+    ///
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// use aesp::{Key, KeySize};
+    ///
+    /// # let shared_secret = [0x42u8; 32];
+    /// let key = Key::derive_kdf(&shared_secret, b"alice|bob|AES-256-GCM", KeySize::Bits256)?;
+    /// assert_eq!(key.as_bytes().len(), 32);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "kdf")]
+    pub fn derive_kdf(secret: &[u8], info: &[u8], size: KeySize) -> Result<Self> {
+        use sha2::{Digest, Sha256};
+
+        let mut k = [0u8; 32];
+        let k = &mut k[..size.byte_len()];
+        for (i, chunk) in k.chunks_mut(32).enumerate() {
+            let mut block = Sha256::new()
+                .chain_update((i as u32 + 1).to_be_bytes())
+                .chain_update(secret)
+                .chain_update(info)
+                .finalize();
+            chunk.copy_from_slice(&block[..chunk.len()]);
+            zeroize(&mut block);
+        }
+        let key = Self::try_from_slice(k);
+        zeroize(k);
+        key
+    }
+
+    /// Derives a subkey from this key using HKDF-Expand (RFC 5869) with HMAC-SHA256, taking this key as the
+    /// pseudorandom key. Requires the `kdf` feature.
+    ///
+    /// Gives domain separation from a single master key: different `info` values (e.g. `b"encryption"`,
+    /// `b"mac"`, or a tenant identifier) yield unrelated subkeys, and no subkey reveals the master key or any
+    /// other subkey. Every key size needs only the first output block, `T(1) = HMAC(key, info || 0x01)`,
+    /// truncated to the requested size.
+    ///
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// use aesp::{Key, KeySize};
+    ///
+    /// let master = Key::rand_key_256()?;
+    /// let enc_key = master.hkdf_expand(b"encryption", KeySize::Bits256)?;
+    /// let mac_key = master.hkdf_expand(b"mac", KeySize::Bits128)?;
+    /// assert_ne!(enc_key.as_bytes()[..16], mac_key.as_bytes()[..]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "kdf")]
+    pub fn hkdf_expand(&self, info: &[u8], size: KeySize) -> Result<Self> {
+        use hmac::{Hmac, Mac};
+
+        // HMAC accepts keys of any length, so this cannot fail
+        let mut mac = Hmac::<sha2::Sha256>::new_from_slice(self.as_bytes()).expect("HMAC takes any key length");
+        mac.update(info);
+        mac.update(&[1]);
+        let mut t = mac.finalize().into_bytes();
+        let key = Self::try_from_slice(&t[..size.byte_len()]);
+        zeroize(&mut t);
+        key
+    }
+
+    /// Returns the size of the key.
+    pub fn size(&self) -> KeySize {
+        match self.bytes {
+            KeyBytes::K128(_) => KeySize::Bits128,
+            KeyBytes::K192(_) => KeySize::Bits192,
+            KeyBytes::K256(_) => KeySize::Bits256,
+        }
+    }
+
     /// Returns a reference to the internal key as an array of bytes.
     pub fn as_bytes(&self) -> &[u8] {
         match &self.bytes {
@@ -102,4 +366,534 @@ impl Key {
             KeyBytes::K256(k) => k,
         }
     }
+
+    /// Returns a reference to the key as a 16-byte array, or `None` if it is not a 128-bit key.
+    ///
+    /// ## Examples
+    /// ```
+    /// # fn main() -> aesp::Result<()> {
+    /// use aesp::Key;
+    ///
+    /// let key = Key::from([0x2b; 16]);
+    /// assert_eq!(key.as_array_128(), Some(&[0x2b; 16]));
+    /// assert_eq!(key.as_array_256(), None);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn as_array_128(&self) -> Option<&[u8; 16]> {
+        match &self.bytes {
+            KeyBytes::K128(k) => Some(k),
+            _ => None,
+        }
+    }
+
+    /// Returns a reference to the key as a 24-byte array, or `None` if it is not a 192-bit key.
+    pub fn as_array_192(&self) -> Option<&[u8; 24]> {
+        match &self.bytes {
+            KeyBytes::K192(k) => Some(k),
+            _ => None,
+        }
+    }
+
+    /// Returns a reference to the key as a 32-byte array, or `None` if it is not a 256-bit key.
+    pub fn as_array_256(&self) -> Option<&[u8; 32]> {
+        match &self.bytes {
+            KeyBytes::K256(k) => Some(k),
+            _ => None,
+        }
+    }
+}
+
+impl core::fmt::Debug for Key {
+    /// Prints the key size only. Key bytes are redacted so they cannot leak into logs; use
+    /// [as_bytes](crate::Key::as_bytes) for deliberate access.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Key")
+            .field("size", &(self.size().byte_len() * 8))
+            .field("bytes", &format_args!("<redacted>"))
+            .finish()
+    }
+}
+
+impl Drop for Key {
+    /// Wipes the key bytes so they do not linger in memory after the key is dropped.
+    fn drop(&mut self) {
+        match &mut self.bytes {
+            KeyBytes::K128(k) => zeroize(k),
+            KeyBytes::K192(k) => zeroize(k),
+            KeyBytes::K256(k) => zeroize(k),
+        }
+    }
+}
+
+/// Equivalent to [try_from_slice](crate::Key::try_from_slice).
+///
+/// ## Examples
+/// ```
+/// # fn main() -> aesp::Result<()> {
+/// use aesp::Key;
+///
+/// let bytes: &[u8] = &[0x2b; 24];
+/// let key = Key::try_from(bytes)?;
+/// assert_eq!(key.as_bytes(), bytes);
+///
+/// assert!(Key::try_from(&bytes[..20]).is_err());
+/// # Ok(())
+/// # }
+/// ```
+impl TryFrom<&[u8]> for Key {
+    type Error = Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self> {
+        Self::try_from_slice(bytes)
+    }
+}
+
+/// Builds a 128-bit key. Cannot fail, as the array length is a valid key size.
+///
+/// ## Examples
+/// ```
+/// use aesp::{Key, KeySize};
+///
+/// let key = Key::from([0x2b; 16]);
+/// assert_eq!(key.size(), KeySize::Bits128);
+/// ```
+impl From<[u8; 16]> for Key {
+    fn from(bytes: [u8; 16]) -> Self {
+        Self {
+            bytes: KeyBytes::K128(bytes),
+        }
+    }
+}
+
+/// Builds a 192-bit key. Cannot fail, as the array length is a valid key size.
+impl From<[u8; 24]> for Key {
+    fn from(bytes: [u8; 24]) -> Self {
+        Self {
+            bytes: KeyBytes::K192(bytes),
+        }
+    }
+}
+
+/// Builds a 256-bit key. Cannot fail, as the array length is a valid key size.
+impl From<[u8; 32]> for Key {
+    fn from(bytes: [u8; 32]) -> Self {
+        Self {
+            bytes: KeyBytes::K256(bytes),
+        }
+    }
+}
+
+/// Serializes as a lowercase hex string in human-readable formats (e.g. JSON), and as raw bytes otherwise.
+/// Requires the `serde` feature.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Key {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            let mut hex = self.to_hex();
+            let res = serializer.serialize_str(&hex);
+            // SAFETY: zero bytes are valid UTF-8
+            zeroize(unsafe { hex.as_bytes_mut() });
+            res
+        } else {
+            serializer.serialize_bytes(self.as_bytes())
+        }
+    }
+}
+
+/// Deserializes from the formats written by `Serialize`. The length is validated by
+/// [try_from_slice](crate::Key::try_from_slice), so anything other than 16, 24, or 32 bytes is an error.
+/// Requires the `serde` feature.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Key {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> core::result::Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(KeyVisitor)
+        } else {
+            deserializer.deserialize_bytes(KeyVisitor)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+struct KeyVisitor;
+
+#[cfg(feature = "serde")]
+impl<'de> serde::de::Visitor<'de> for KeyVisitor {
+    type Value = Key;
+
+    fn expecting(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("a 16, 24, or 32 byte AES key, as bytes or a hex string")
+    }
+
+    fn visit_str<E: serde::de::Error>(self, v: &str) -> core::result::Result<Key, E> {
+        Key::try_from_hex(v).map_err(E::custom)
+    }
+
+    fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> core::result::Result<Key, E> {
+        Key::try_from_slice(v).map_err(E::custom)
+    }
+
+    // formats without a native byte string type hand bytes over as a sequence
+    fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> core::result::Result<Key, A::Error> {
+        let mut buf = [0u8; 32];
+        let mut len = 0;
+        while let Some(b) = seq.next_element::<u8>()? {
+            if len == buf.len() {
+                zeroize(&mut buf);
+                return Err(serde::de::Error::invalid_length(len + 1, &self));
+            }
+            buf[len] = b;
+            len += 1;
+        }
+        let key = Key::try_from_slice(&buf[..len]).map_err(serde::de::Error::custom);
+        zeroize(&mut buf);
+        key
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::mem::{ManuallyDrop, size_of};
+
+    #[test]
+    fn drop_wipes_key_bytes() -> Result<()> {
+        let mut key = ManuallyDrop::new(Key::try_from_slice(&[0xA5; 32])?);
+        let ptr = &mut *key as *mut Key;
+
+        // SAFETY: key is never used again after being dropped in place. The storage is still owned by
+        // the ManuallyDrop on this stack frame, and Key contains only u8 fields (no padding), so every
+        // byte remains initialised and readable after drop.
+        let remaining = unsafe {
+            std::ptr::drop_in_place(ptr);
+            (0..size_of::<Key>())
+                .map(|i| std::ptr::read_volatile((ptr as *const u8).add(i)))
+                .filter(|&b| b == 0xA5)
+                .count()
+        };
+
+        assert_eq!(remaining, 0, "key bytes remain in memory after drop");
+        Ok(())
+    }
+
+    #[test]
+    fn generate_and_size() -> Result<()> {
+        for size in [KeySize::Bits128, KeySize::Bits192, KeySize::Bits256] {
+            let key = Key::generate(size)?;
+            assert_eq!(key.size(), size);
+            assert_eq!(key.as_bytes().len(), size.byte_len());
+            assert_ne!(key, Key::generate(size)?);
+        }
+        assert_eq!(Key::rand_key_128()?.size(), KeySize::Bits128);
+        assert_eq!(Key::rand_key_192()?.size(), KeySize::Bits192);
+        assert_eq!(Key::rand_key_256()?.size(), KeySize::Bits256);
+        Ok(())
+    }
+
+    /// Deterministic RNG that counts up from a seed byte. Not secure; only used to check that
+    /// generate_with_rng takes its bytes from the supplied RNG.
+    struct CountingRng(u8);
+
+    impl rand::RngCore for CountingRng {
+        fn next_u32(&mut self) -> u32 {
+            rand::rand_core::impls::next_u32_via_fill(self)
+        }
+        fn next_u64(&mut self) -> u64 {
+            rand::rand_core::impls::next_u64_via_fill(self)
+        }
+        fn fill_bytes(&mut self, dst: &mut [u8]) {
+            for b in dst {
+                *b = self.0;
+                self.0 = self.0.wrapping_add(1);
+            }
+        }
+    }
+
+    impl rand::CryptoRng for CountingRng {}
+
+    #[test]
+    fn generate_with_rng_uses_supplied_rng() -> Result<()> {
+        for size in [KeySize::Bits128, KeySize::Bits192, KeySize::Bits256] {
+            let mut rng = CountingRng(7);
+            let key = Key::generate_with_rng(&mut rng, size)?;
+            assert_eq!(key.size(), size);
+            let expected: Vec<u8> = (7..7 + size.byte_len() as u8).collect();
+            assert_eq!(key.as_bytes(), expected);
+
+            // rng state advances, so the next key differs
+            assert_ne!(Key::generate_with_rng(&mut rng, size)?, key);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn generate_with_seeded_rng_is_reproducible() -> Result<()> {
+        use rand_chacha::ChaCha20Rng;
+        use rand_chacha::rand_core::SeedableRng;
+
+        let mut a = ChaCha20Rng::seed_from_u64(1);
+        let mut b = ChaCha20Rng::seed_from_u64(1);
+        for _ in 0..4 {
+            assert_eq!(
+                Key::generate_with_rng(&mut a, KeySize::Bits256)?,
+                Key::generate_with_rng(&mut b, KeySize::Bits256)?
+            );
+        }
+        assert_ne!(
+            Key::generate_with_rng(&mut ChaCha20Rng::seed_from_u64(2), KeySize::Bits256)?,
+            Key::generate_with_rng(&mut a, KeySize::Bits256)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn debug_redacts_key_bytes() -> Result<()> {
+        let key = Key::try_from_slice(&[0xA5; 32])?;
+        let debug = format!("{key:?}");
+        assert_eq!(debug, "Key { size: 256, bytes: <redacted> }");
+
+        // no key byte appears in any common representation
+        let pretty = format!("{key:#?}");
+        for s in [&debug, &pretty] {
+            assert!(!s.contains("165") && !s.to_lowercase().contains("a5"), "{s}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn from_arrays_and_slices() -> Result<()> {
+        assert_eq!(Key::from([1u8; 16]), Key::try_from_slice(&[1u8; 16])?);
+        assert_eq!(Key::from([2u8; 24]), Key::try_from_slice(&[2u8; 24])?);
+        assert_eq!(Key::from([3u8; 32]), Key::try_from_slice(&[3u8; 32])?);
+
+        // TryFrom composes with `?` and generic code
+        fn build<T: TryInto<Key, Error = Error>>(t: T) -> Result<Key> {
+            t.try_into()
+        }
+        assert_eq!(build(&[4u8; 32][..])?.size(), KeySize::Bits256);
+        assert!(matches!(build(&[0u8; 17][..]), Err(Error::InvalidKeyLength { len: 17 })));
+        Ok(())
+    }
+
+    #[test]
+    fn typed_array_accessors() {
+        let k128 = Key::from([1u8; 16]);
+        let k192 = Key::from([2u8; 24]);
+        let k256 = Key::from([3u8; 32]);
+
+        assert_eq!(k128.as_array_128(), Some(&[1u8; 16]));
+        assert_eq!(k192.as_array_192(), Some(&[2u8; 24]));
+        assert_eq!(k256.as_array_256(), Some(&[3u8; 32]));
+
+        for key in [&k192, &k256] {
+            assert_eq!(key.as_array_128(), None);
+        }
+        for key in [&k128, &k256] {
+            assert_eq!(key.as_array_192(), None);
+        }
+        for key in [&k128, &k192] {
+            assert_eq!(key.as_array_256(), None);
+        }
+    }
+
+    #[test]
+    fn hex_round_trip() -> Result<()> {
+        let key = Key::try_from_hex("2B7E151628AED2A6abf7158809cf4f3c")?;
+        assert_eq!(key.as_bytes()[..2], [0x2b, 0x7e]);
+        assert_eq!(key.to_hex(), "2b7e151628aed2a6abf7158809cf4f3c");
+
+        // whitespace anywhere is ignored
+        let spaced = Key::try_from_hex(" 2b7e1516\n28aed2a6\tabf71588 09cf4f3c\n")?;
+        assert_eq!(spaced, key);
+
+        for size in [KeySize::Bits128, KeySize::Bits192, KeySize::Bits256] {
+            let key = Key::generate(size)?;
+            assert_eq!(Key::try_from_hex(&key.to_hex())?, key);
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() -> Result<()> {
+        for size in [KeySize::Bits128, KeySize::Bits192, KeySize::Bits256] {
+            let key = Key::generate(size)?;
+
+            // human readable: hex string
+            let json = serde_json::to_string(&key).unwrap();
+            assert_eq!(json, format!("\"{}\"", key.to_hex()));
+            assert_eq!(serde_json::from_str::<Key>(&json).unwrap(), key);
+
+            // binary: length-prefixed raw bytes
+            let bin = bincode::serialize(&key).unwrap();
+            assert_eq!(bin.len(), 8 + size.byte_len());
+            assert_eq!(bin[8..], *key.as_bytes());
+            assert_eq!(bincode::deserialize::<Key>(&bin).unwrap(), key);
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_rejects_invalid_sizes() {
+        for len in [0, 15, 20, 33] {
+            let bin = bincode::serialize(&serde_bytes_of(len)).unwrap();
+            let err = bincode::deserialize::<Key>(&bin).unwrap_err();
+            assert!(err.to_string().contains("invalid key length"), "{err}");
+
+            let json = format!("\"{}\"", "ab".repeat(len));
+            let err = serde_json::from_str::<Key>(&json).unwrap_err();
+            assert!(err.to_string().contains("invalid key length"), "{err}");
+        }
+        assert!(serde_json::from_str::<Key>("\"not hex\"").is_err());
+    }
+
+    /// Serializes `len` bytes the same way a Key is serialized in binary formats.
+    #[cfg(feature = "serde")]
+    fn serde_bytes_of(len: usize) -> impl serde::Serialize {
+        struct Bytes(Vec<u8>);
+        impl serde::Serialize for Bytes {
+            fn serialize<S: serde::Serializer>(&self, s: S) -> core::result::Result<S::Ok, S::Error> {
+                s.serialize_bytes(&self.0)
+            }
+        }
+        Bytes(vec![0x5a; len])
+    }
+
+    #[test]
+    #[cfg(feature = "pem")]
+    fn pem_round_trip() -> Result<()> {
+        for key in [Key::rand_key_128()?, Key::rand_key_192()?, Key::rand_key_256()?] {
+            let pem = key.to_pem();
+            assert!(pem.starts_with("-----BEGIN AES KEY-----\n") && pem.ends_with("-----END AES KEY-----\n"));
+            assert_eq!(Key::from_pem(&pem)?, key);
+
+            // CRLF line endings and a body wrapped over several lines
+            let body = pem.lines().nth(1).unwrap();
+            let (a, b) = body.split_at(8);
+            let wrapped = format!("\r\n-----BEGIN AES KEY-----\r\n{a}\r\n{b}\r\n-----END AES KEY-----\r\n");
+            assert_eq!(Key::from_pem(&wrapped)?, key);
+        }
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "pem")]
+    fn pem_rejects_malformed_input() {
+        let body = "K34VFiiu0qar9xWICc9PPA==";
+        let cases = [
+            (format!("{body}\n-----END AES KEY-----"), "missing BEGIN AES KEY line"),
+            (format!("-----BEGIN RSA KEY-----\n{body}\n-----END AES KEY-----"), "missing BEGIN AES KEY line"),
+            (format!("-----BEGIN AES KEY-----\n{body}"), "missing END AES KEY line"),
+            (format!("-----BEGIN AES KEY-----\n{body}\n-----END PRIVATE KEY-----"), "missing END AES KEY line"),
+        ];
+        for (pem, context) in cases {
+            assert!(matches!(Key::from_pem(&pem), Err(Error::InvalidPem { context: c }) if c == context), "{pem}");
+        }
+
+        assert!(matches!(
+            Key::from_pem("-----BEGIN AES KEY-----\nK34V!iiu\n-----END AES KEY-----"),
+            Err(Error::InvalidBase64 { .. })
+        ));
+        // 12 bytes
+        assert!(matches!(
+            Key::from_pem("-----BEGIN AES KEY-----\nAAAAAAAAAAAAAAAA\n-----END AES KEY-----"),
+            Err(Error::InvalidKeyLength { len: 12 })
+        ));
+    }
+
+    #[test]
+    fn hex_rejects_malformed_input() {
+        // odd number of digits
+        assert!(matches!(
+            Key::try_from_hex("2b7e151628aed2a6abf7158809cf4f3"),
+            Err(Error::InvalidHex { len: 31, .. })
+        ));
+        // non-hex characters, including a sign accepted by from_str_radix
+        for s in ["2b7e151628aed2a6abf7158809cf4fzz", "+b7e151628aed2a6abf7158809cf4f3c", "0x7e151628aed2a6abf7158809cf4f3c"] {
+            assert!(matches!(Key::try_from_hex(s), Err(Error::InvalidHex { .. })), "{s}");
+        }
+        // valid hex, invalid key size
+        assert!(matches!(Key::try_from_hex(""), Err(Error::InvalidKeyLength { len: 0 })));
+        assert!(matches!(
+            Key::try_from_hex("2b7e151628aed2a6abf7158809cf4f"),
+            Err(Error::InvalidKeyLength { len: 15 })
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "kdf")]
+    fn pbkdf2_hmac_sha256() -> Result<()> {
+        // (password, salt, iterations, size, expected key)
+        type Case<'a> = (&'a [u8], &'a [u8], u32, KeySize, &'a str);
+
+        // RFC 6070 inputs, with outputs for HMAC-SHA256 in place of HMAC-SHA1
+        let cases: [Case; 5] = [
+            (b"password", b"salt", 1, KeySize::Bits256, "120fb6cffcf8b32c43e7225256c4f837a86548c92ccc35480805987cb70be17b"),
+            (b"password", b"salt", 2, KeySize::Bits256, "ae4d0c95af6b46d32d0adff928f06dd02a303f8ef3c251dfd6e2d85a95474c43"),
+            (b"password", b"salt", 4096, KeySize::Bits256, "c5e478d59288c841aa530db6845c4c8d962893a001ce4e11a4963873aa98134a"),
+            (b"passwordPASSWORDpassword", b"saltSALTsaltSALTsaltSALTsaltSALTsalt", 4096, KeySize::Bits192, "348c89dbcbd32b2f32d814b8116e84cf2b17347ebc180018"),
+            (b"pass\0word", b"sa\0lt", 4096, KeySize::Bits128, "89b69d0516f829893c696226650a8687"),
+        ];
+
+        for (password, salt, iterations, size, expected) in cases {
+            let key = Key::from_password_pbkdf2(password, salt, iterations, size)?;
+            let expected: Vec<u8> = (0..expected.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&expected[i..i + 2], 16).unwrap())
+                .collect();
+            assert_eq!(key.as_bytes(), expected);
+        }
+
+        assert!(matches!(
+            Key::from_password_pbkdf2(b"password", b"salt", 0, KeySize::Bits128),
+            Err(Error::InvalidIterationCount { count: 0 })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "kdf")]
+    fn one_step_kdf_sha256() -> Result<()> {
+        // expected outputs computed independently as SHA-256(00000001 || secret || info)
+        let secret = Key::try_from_hex("96c05619d56c328ab95fe84b18264b08725b85e33fd34f08")?;
+        let key = Key::derive_kdf(secret.as_bytes(), b"aesp test", KeySize::Bits256)?;
+        assert_eq!(key.to_hex(), "2b4e9d52dea8406a415338b6651698f8a10900c2953db86a3b7f667439438908");
+
+        // shorter keys are truncations of the same block
+        let key = Key::derive_kdf(secret.as_bytes(), b"aesp test", KeySize::Bits128)?;
+        assert_eq!(key.to_hex(), "2b4e9d52dea8406a415338b6651698f8");
+
+        let key = Key::derive_kdf(b"shared secret", b"", KeySize::Bits192)?;
+        assert_eq!(key.to_hex(), "2d055b5d33b0a282e4fc50391d39c16b9863abc408719623");
+
+        // info separates keys derived from the same secret
+        assert_ne!(
+            Key::derive_kdf(b"shared secret", b"a", KeySize::Bits256)?,
+            Key::derive_kdf(b"shared secret", b"b", KeySize::Bits256)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "kdf")]
+    fn hkdf_expand_sha256() -> Result<()> {
+        // RFC 5869 test case 1: PRK and info, with the first 32 bytes of the 42-byte OKM
+        let prk = Key::try_from_hex("077709362c2e32df0ddc3f0dc47bba6390b6c73bb50f9c3122ec844ad7c2b3e5")?;
+        let info = [0xf0, 0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xf7, 0xf8, 0xf9];
+        let key = prk.hkdf_expand(&info, KeySize::Bits256)?;
+        assert_eq!(key.to_hex(), "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf");
+
+        // shorter keys are truncations of the same block
+        assert_eq!(prk.hkdf_expand(&info, KeySize::Bits192)?.to_hex(), key.to_hex()[..48]);
+        assert_eq!(prk.hkdf_expand(&info, KeySize::Bits128)?.to_hex(), key.to_hex()[..32]);
+
+        // info separates subkeys of the same master key, which also works for 128-bit masters
+        let master = Key::rand_key_128()?;
+        let (a, b) = (master.hkdf_expand(b"a", KeySize::Bits256)?, master.hkdf_expand(b"b", KeySize::Bits256)?);
+        assert_ne!(a, b);
+        assert_ne!(a.as_bytes()[..16], master.as_bytes()[..]);
+        Ok(())
+    }
 }
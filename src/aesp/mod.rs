@@ -1,10 +1,33 @@
+mod aead;
+#[cfg(feature = "bench")]
+mod bench;
 mod cipher;
 mod core;
+mod envelope;
 mod error;
+#[cfg(feature = "std")]
+mod io;
 mod key;
 mod modes;
+mod nonce;
+#[cfg(feature = "std")]
+mod ratchet;
+mod self_test;
 mod util;
 
-pub use error::{Error, Result};
-pub use key::Key;
-pub use cipher::Cipher;
\ No newline at end of file
+pub use error::{Error, ErrorKind, Result};
+pub use key::{Key, KeySize};
+pub use cipher::Cipher;
+pub use aead::{Aead, Gcm, GcmSiv};
+#[cfg(feature = "bench")]
+pub use bench::bench_block_throughput;
+#[cfg(feature = "std")]
+pub use io::{DecryptReader, EncryptWriter};
+pub use nonce::NonceSequence;
+pub use modes::{CtrStream, GcmDecryptor, GcmEncryptor, GcmFrame, GcmOutput, Mode, Padding, parse_gcm};
+#[cfg(feature = "std")]
+pub use ratchet::RatchetCipher;
+pub use self_test::self_test;
+pub use util::{generate_iv_with_rng, looks_like_ecb, xor_into};
+#[cfg(feature = "base64")]
+pub use util::{decode_b64, encode_b64};
\ No newline at end of file
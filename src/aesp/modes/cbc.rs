@@ -0,0 +1,265 @@
+use alloc::vec;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use rayon::prelude::*;
+
+use crate::aesp::core::{decrypt_block_eq, encrypt_block};
+use crate::aesp::error::*;
+use crate::aesp::modes::Padding;
+use crate::aesp::modes::util::as_block;
+use crate::aesp::util::{pad, unpad, xor_into, zeroize};
+
+/*
+https://nvlpubs.nist.gov/nistpubs/Legacy/SP/nistspecialpublication800-38a-add.pdf
+
+CBC:
+    C_0 = IV
+    C_i = encrypt_block(P_i ^ C_i-1, key)
+    plain CBC pads the plaintext with PKCS#7, as ECB does.
+
+CBC-CS3 (ciphertext stealing, identical to the Kerberos variant in RFC 3962):
+    zero-pad the final partial block P_n* to a full block, CBC encrypt, then swap the final two
+    ciphertext blocks and truncate the (new) final block to the length of P_n*:
+    C_1 || ... || C_n-2 || C_n || MSB(len(P_n*), C_n-1)
+    the final two blocks are always swapped, even when the input is a multiple of 16 bytes.
+    a single-block input is plain CBC.
+*/
+
+/// CBC encryption of whole blocks. Encryption is inherently serial, as each block depends on the last.
+fn cbc_core_enc(plaintext: &[u8], round_keys: &[[u8; 16]], iv: &[u8; 16]) -> Vec<u8> {
+    debug_assert!(plaintext.len().is_multiple_of(16));
+    let mut output = vec![0u8; plaintext.len()];
+    let mut prev = *iv;
+    for (ct, pt) in output.chunks_exact_mut(16).zip(plaintext.chunks_exact(16)) {
+        xor_into(&mut prev, pt);
+        prev = encrypt_block(&prev, round_keys);
+        ct.copy_from_slice(&prev);
+    }
+    output
+}
+
+/// CBC decryption of whole blocks, using the equivalent inverse cipher's round keys. Each block depends only
+/// on the ciphertext, so inputs longer than `parallel_threshold` bytes are processed in parallel.
+fn cbc_core_dec(ciphertext: &[u8], inv_round_keys: &[[u8; 16]], iv: &[u8; 16], parallel_threshold: usize) -> Vec<u8> {
+    debug_assert!(ciphertext.len().is_multiple_of(16));
+    let mut output = vec![0u8; ciphertext.len()];
+    let (blocks, _) = ciphertext.as_chunks::<16>();
+    let crypt = |(i, pt): (usize, &mut [u8])| {
+        pt.copy_from_slice(&decrypt_block_eq(&blocks[i], inv_round_keys));
+        let prev = if i == 0 { iv } else { &blocks[i - 1] };
+        xor_into(pt, prev);
+    };
+
+    // decrypt in parallel if size exceeds threshold
+    #[cfg(feature = "std")]
+    if ciphertext.len() > parallel_threshold {
+        output.par_chunks_exact_mut(16).enumerate().for_each(crypt);
+        return output;
+    }
+    // no thread pool without std
+    #[cfg(not(feature = "std"))]
+    let _ = parallel_threshold;
+
+    output.chunks_exact_mut(16).enumerate().for_each(crypt);
+    output
+}
+
+/// CBC encryption with PKCS#7 padding. Output is 1 to 16 bytes longer than the input.
+pub fn cbc_encrypt_padded(plaintext: &[u8], round_keys: &[[u8; 16]], iv: &[u8; 16]) -> Vec<u8> {
+    let mut padded = pad(plaintext, Padding::Pkcs7);
+    let ciphertext = cbc_core_enc(&padded, round_keys, iv);
+    zeroize(&mut padded);
+    ciphertext
+}
+
+/// CBC decryption, then removes and validates PKCS#7 padding. Takes the equivalent inverse cipher's round keys.
+pub fn cbc_decrypt_padded(
+    ciphertext: &[u8],
+    inv_round_keys: &[[u8; 16]],
+    iv: &[u8; 16],
+    parallel_threshold: usize,
+) -> Result<Vec<u8>> {
+    let mut plaintext = cbc_decrypt_unpadded(ciphertext, inv_round_keys, iv, parallel_threshold)?;
+    unpad(&mut plaintext, Padding::Pkcs7)?;
+    Ok(plaintext)
+}
+
+/// CBC decryption that leaves any padding in place. Takes the equivalent inverse cipher's round keys.
+pub fn cbc_decrypt_unpadded(
+    ciphertext: &[u8],
+    inv_round_keys: &[[u8; 16]],
+    iv: &[u8; 16],
+    parallel_threshold: usize,
+) -> Result<Vec<u8>> {
+    if ciphertext.is_empty() || !ciphertext.len().is_multiple_of(16) {
+        return Err(Error::InvalidCiphertext {
+            len: ciphertext.len(),
+            context: "CBC: length must be a non-zero multiple of 16",
+        });
+    }
+    Ok(cbc_core_dec(ciphertext, inv_round_keys, iv, parallel_threshold))
+}
+
+/// CBC-CS3 encryption. Output is exactly as long as the input, which must be at least 16 bytes.
+pub fn cbc_cts_encrypt(plaintext: &[u8], round_keys: &[[u8; 16]], iv: &[u8; 16]) -> Result<Vec<u8>> {
+    let n = plaintext.len();
+    if n < 16 {
+        return Err(Error::InputTooShort { len: n, min: 16 });
+    }
+
+    // zero-pad the final partial block
+    let mut padded = plaintext.to_vec();
+    padded.resize(n.div_ceil(16) * 16, 0);
+    let mut output = cbc_core_enc(&padded, round_keys, iv);
+    if n == 16 {
+        return Ok(output);
+    }
+
+    // swap C_n-1 and C_n, then truncate C_n-1
+    let last = output.len() - 16;
+    let (head, c_n) = output.split_at_mut(last);
+    head[last - 16..].swap_with_slice(c_n);
+    output.truncate(n);
+    Ok(output)
+}
+
+/// CBC-CS3 decryption. Takes the equivalent inverse cipher's round keys.
+pub fn cbc_cts_decrypt(
+    ciphertext: &[u8],
+    inv_round_keys: &[[u8; 16]],
+    iv: &[u8; 16],
+    parallel_threshold: usize,
+) -> Result<Vec<u8>> {
+    let n = ciphertext.len();
+    if n < 16 {
+        return Err(Error::InputTooShort { len: n, min: 16 });
+    }
+    if n == 16 {
+        return Ok(cbc_core_dec(ciphertext, inv_round_keys, iv, parallel_threshold));
+    }
+
+    // ciphertext is C_1 || ... || C_n-2 || C_n || C_n-1*, where C_n-1* is the first d bytes of C_n-1
+    let full = n.div_ceil(16) * 16;
+    let d = n - (full - 16);
+    let (head, tail) = ciphertext.split_at(full - 32);
+    let (c_n, c_n1_partial) = tail.split_at(16);
+
+    // decrypting C_n gives C_n-1 ^ (P_n* || 0), so its last 16 - d bytes are the stolen bytes of C_n-1
+    let mut x = decrypt_block_eq(as_block(c_n)?, inv_round_keys);
+    x[..d].copy_from_slice(c_n1_partial);
+
+    // rebuild the plain CBC ciphertext C_1 || ... || C_n-1 || C_n, then decrypt and truncate the padding
+    let mut rebuilt = Vec::with_capacity(full);
+    rebuilt.extend_from_slice(head);
+    rebuilt.extend_from_slice(&x);
+    rebuilt.extend_from_slice(c_n);
+    let mut plaintext = cbc_core_dec(&rebuilt, inv_round_keys, iv, parallel_threshold);
+    plaintext.truncate(n);
+    Ok(plaintext)
+}
+
+#[cfg(test)]
+mod test_cbc {
+    use super::*;
+    use crate::aesp::modes::util::test_util::{KEY_128, PLAINTEXT, hex_to_bytes};
+    use crate::{Cipher, Key};
+
+    #[test]
+    fn aes_cbc_128_sp800_38a() -> Result<()> {
+        // SP 800-38A F.2.1 CBC-AES128.Encrypt
+        let expected = hex_to_bytes(
+            "
+    7649abac8119b246cee98e9b12e9197d\
+    5086cb9b507219ee95db113a917678b2\
+    73bed6b8e3c1743b7116e69e22229516\
+    3ff1caa1681fac09120eca307586e1a7",
+        );
+        let iv: [u8; 16] = core::array::from_fn(|i| i as u8);
+        let cipher = Cipher::new(&Key::try_from_slice(&KEY_128)?);
+
+        assert_eq!(cbc_core_enc(&PLAINTEXT, cipher.round_keys(), &iv), expected);
+        for threshold in [0, usize::MAX] {
+            assert_eq!(cbc_core_dec(&expected, cipher.inv_round_keys(), &iv, threshold), PLAINTEXT);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn padded_round_trip_all_lengths() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_192()?);
+        let iv = [0xa5u8; 16];
+        let message: Vec<u8> = (0..100u8).collect();
+        for len in 0..message.len() {
+            let ct = cbc_encrypt_padded(&message[..len], cipher.round_keys(), &iv);
+            assert_eq!(ct.len(), (len / 16 + 1) * 16);
+            assert_eq!(cbc_decrypt_padded(&ct, cipher.inv_round_keys(), &iv, usize::MAX)?, message[..len]);
+        }
+
+        for len in [0, 15, 17] {
+            assert!(matches!(
+                cbc_decrypt_padded(&vec![0u8; len], cipher.inv_round_keys(), &iv, usize::MAX),
+                Err(Error::InvalidCiphertext { .. })
+            ));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn cts_rfc3962_vectors() -> Result<()> {
+        // RFC 3962 appendix B: AES-128 key "chicken teriyaki", IV of zeros
+        let cipher = Cipher::new(&Key::try_from_slice(b"chicken teriyaki")?);
+        let iv = [0u8; 16];
+        let message = b"I would like the General Gau's Chicken, please, and wonton soup.";
+        let expected = [
+            (17, "c6353568f2bf8cb4d8a580362da7ff7f97"),
+            (31, "fc00783e0efdb2c1d445d4c8eff7ed2297687268d6ecccc0c07b25e25ecfe5"),
+            (32, "39312523a78662d5be7fcbcc98ebf5a897687268d6ecccc0c07b25e25ecfe584"),
+            (47, "97687268d6ecccc0c07b25e25ecfe584b3fffd940c16a18c1b5549d2f838029e39312523a78662d5be7fcbcc98ebf5"),
+            (48, "97687268d6ecccc0c07b25e25ecfe5849dad8bbb96c4cdc03bc103e1a194bbd839312523a78662d5be7fcbcc98ebf5a8"),
+            (
+                64,
+                "97687268d6ecccc0c07b25e25ecfe58439312523a78662d5be7fcbcc98ebf5a8\
+                 4807efe836ee89a526730dbc2f7bc8409dad8bbb96c4cdc03bc103e1a194bbd8",
+            ),
+        ];
+
+        for (len, ct) in expected {
+            let ct = hex_to_bytes(&ct.replace(' ', ""));
+            assert_eq!(cbc_cts_encrypt(&message[..len], cipher.round_keys(), &iv)?, ct, "{len}");
+            assert_eq!(cbc_cts_decrypt(&ct, cipher.inv_round_keys(), &iv, usize::MAX)?, message[..len], "{len}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn cts_round_trip_all_lengths() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_256()?);
+        let iv = [0x5au8; 16];
+        let message: Vec<u8> = (0..200u8).collect();
+        for len in 16..message.len() {
+            let ct = cbc_cts_encrypt(&message[..len], cipher.round_keys(), &iv)?;
+            assert_eq!(ct.len(), len);
+            for threshold in [0, usize::MAX] {
+                assert_eq!(cbc_cts_decrypt(&ct, cipher.inv_round_keys(), &iv, threshold)?, message[..len]);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn cts_rejects_short_input() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_128()?);
+        for len in [0, 1, 15] {
+            let input = vec![0u8; len];
+            assert!(matches!(
+                cbc_cts_encrypt(&input, cipher.round_keys(), &[0; 16]),
+                Err(Error::InputTooShort { min: 16, .. })
+            ));
+            assert!(matches!(
+                cbc_cts_decrypt(&input, cipher.inv_round_keys(), &[0; 16], usize::MAX),
+                Err(Error::InputTooShort { min: 16, .. })
+            ));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,285 @@
+use alloc::vec::Vec;
+
+use crate::aesp::core::encrypt_block;
+use crate::aesp::error::*;
+use crate::aesp::util::{ct_eq, xor_into, zeroize};
+
+/*
+https://www.rfc-editor.org/rfc/rfc3610 (also NIST SP 800-38C)
+
+nonce length N = 7..=13 bytes, leaving L = 15 - N bytes for the message length and counter
+tag length M = 4, 6, ..., 16 bytes
+
+B_0 = Flags || Nonce || l(m) (L bytes, big-endian)
+    Flags = 64 * Adata + 8 * (M - 2) / 2 + (L - 1)
+AAD, if any, is prefixed with its encoded length and zero-padded to whole blocks:
+    0 < l(a) < 2^16 - 2^8:  2 bytes
+    l(a) < 2^32:            0xff 0xfe || 4 bytes
+    otherwise:              0xff 0xff || 8 bytes
+the plaintext is zero-padded to whole blocks
+
+T = CBC-MAC(B_0 || AAD blocks || plaintext blocks), truncated to M bytes
+
+A_i = (L - 1) || Nonce || i (L bytes)
+C = plaintext ^ E(A_1) || E(A_2) ...
+U = T ^ E(A_0), the transmitted tag
+*/
+
+/// Checks the nonce and tag lengths, returning `L`, the size of the length field in bytes.
+fn check_params(nonce: &[u8], tag_len: usize) -> Result<usize> {
+    if !(7..=13).contains(&nonce.len()) {
+        return Err(Error::InvalidNonceLength { len: nonce.len() });
+    }
+    if !(4..=16).contains(&tag_len) || !tag_len.is_multiple_of(2) {
+        return Err(Error::InvalidTagLength { bits: tag_len.saturating_mul(8) });
+    }
+    Ok(15 - nonce.len())
+}
+
+/// Formats the first CBC-MAC block, `B_0`. Returns an [InputTooLong] error if the message length does not
+/// fit in the `15 - nonce.len()` bytes left by the nonce.
+fn format_b0(nonce: &[u8], has_aad: bool, msg_len: usize, tag_len: usize) -> Result<[u8; 16]> {
+    let l = 15 - nonce.len();
+    let max = if l >= size_of::<usize>() { usize::MAX } else { (1 << (8 * l)) - 1 };
+    if msg_len > max {
+        return Err(Error::InputTooLong { len: msg_len, max });
+    }
+
+    let mut b0 = [0u8; 16];
+    b0[0] = (u8::from(has_aad) << 6) | (((tag_len as u8 - 2) / 2) << 3) | (l as u8 - 1);
+    b0[1..16 - l].copy_from_slice(nonce);
+    b0[16 - l..].copy_from_slice(&(msg_len as u64).to_be_bytes()[8 - l..]);
+    Ok(b0)
+}
+
+/// Encodes the AAD length prefix. Empty for no AAD.
+fn encode_aad_len(aad_len: usize) -> Vec<u8> {
+    let len = aad_len as u64;
+    match len {
+        0 => Vec::new(),
+        1..0xff00 => (len as u16).to_be_bytes().to_vec(),
+        0xff00..=0xffff_ffff => [&[0xff, 0xfe][..], &(len as u32).to_be_bytes()].concat(),
+        _ => [&[0xff, 0xff][..], &len.to_be_bytes()].concat(),
+    }
+}
+
+/// Counter block `A_i`.
+fn counter_block(nonce: &[u8], i: usize) -> [u8; 16] {
+    let l = 15 - nonce.len();
+    let mut a = [0u8; 16];
+    a[0] = l as u8 - 1;
+    a[1..16 - l].copy_from_slice(nonce);
+    a[16 - l..].copy_from_slice(&(i as u64).to_be_bytes()[8 - l..]);
+    a
+}
+
+/// CBC-MAC over `B_0`, the length-prefixed AAD and the plaintext, each zero-padded to whole blocks.
+fn cbc_mac(b0: &[u8; 16], aad: &[u8], plaintext: &[u8], round_keys: &[[u8; 16]]) -> [u8; 16] {
+    let mut x = encrypt_block(b0, round_keys);
+    let mut absorb = |data: &[u8]| {
+        for chunk in data.chunks(16) {
+            xor_into(&mut x, chunk);
+            x = encrypt_block(&x, round_keys);
+        }
+    };
+
+    if !aad.is_empty() {
+        let mut prefixed = encode_aad_len(aad.len());
+        prefixed.extend_from_slice(aad);
+        absorb(&prefixed);
+    }
+    absorb(plaintext);
+    x
+}
+
+/// Applies the keystream `E(A_1) || E(A_2) ...` to `buf`.
+fn ccm_ctr(buf: &mut [u8], nonce: &[u8], round_keys: &[[u8; 16]]) {
+    for (i, chunk) in buf.chunks_mut(16).enumerate() {
+        xor_into(chunk, &encrypt_block(&counter_block(nonce, i + 1), round_keys));
+    }
+}
+
+/// CCM encryption. Returns `Ciphertext || Tag (tag_len bytes)`.
+pub fn ccm_encrypt(
+    plaintext: &[u8],
+    nonce: &[u8],
+    aad: &[u8],
+    tag_len: usize,
+    round_keys: &[[u8; 16]],
+) -> Result<Vec<u8>> {
+    check_params(nonce, tag_len)?;
+    let b0 = format_b0(nonce, !aad.is_empty(), plaintext.len(), tag_len)?;
+    let mut t = cbc_mac(&b0, aad, plaintext, round_keys);
+    xor_into(&mut t, &encrypt_block(&counter_block(nonce, 0), round_keys));
+
+    let mut out = Vec::with_capacity(plaintext.len() + tag_len);
+    out.extend_from_slice(plaintext);
+    ccm_ctr(&mut out, nonce, round_keys);
+    out.extend_from_slice(&t[..tag_len]);
+    zeroize(&mut t);
+    Ok(out)
+}
+
+/// CCM decryption of `Ciphertext || Tag (tag_len bytes)`. The plaintext is only returned if the tag matches.
+pub fn ccm_decrypt(
+    ciphertext: &[u8],
+    nonce: &[u8],
+    aad: &[u8],
+    tag_len: usize,
+    round_keys: &[[u8; 16]],
+) -> Result<Vec<u8>> {
+    check_params(nonce, tag_len)?;
+    if ciphertext.len() < tag_len {
+        return Err(Error::InvalidCiphertext {
+            len: ciphertext.len(),
+            context: "CCM: missing tag",
+        });
+    }
+    let (ciphertext, tag) = ciphertext.split_at(ciphertext.len() - tag_len);
+    let b0 = format_b0(nonce, !aad.is_empty(), ciphertext.len(), tag_len)?;
+
+    // the MAC is over the plaintext, so CCM must decrypt before it can verify
+    let mut plaintext = ciphertext.to_vec();
+    ccm_ctr(&mut plaintext, nonce, round_keys);
+    let mut t = cbc_mac(&b0, aad, &plaintext, round_keys);
+    xor_into(&mut t, &encrypt_block(&counter_block(nonce, 0), round_keys));
+
+    let valid = ct_eq(tag, &t[..tag_len]);
+    zeroize(&mut t);
+    if !valid {
+        zeroize(&mut plaintext);
+        return Err(Error::AuthFailed);
+    }
+    Ok(plaintext)
+}
+
+#[cfg(test)]
+mod test_ccm {
+    use super::*;
+    use crate::aesp::modes::util::test_util::hex_to_bytes;
+    use crate::{Cipher, Key};
+
+    #[test]
+    fn b0_and_aad_length_formatting() -> Result<()> {
+        // RFC 3610 packet vector #1: 8-byte tag, 13-byte nonce (L = 2), 23-byte message, with AAD
+        let nonce = hex_to_bytes("00000003020100a0a1a2a3a4a5");
+        let b0 = format_b0(&nonce, true, 23, 8)?;
+        assert_eq!(b0[..], hex_to_bytes("5900000003020100a0a1a2a3a4a50017"));
+
+        // SP 800-38C example 1: 4-byte tag, 7-byte nonce (L = 8), 4-byte message
+        let b0 = format_b0(&hex_to_bytes("10111213141516"), true, 4, 4)?;
+        assert_eq!(b0[..], hex_to_bytes("4f101112131415160000000000000004"));
+        assert_eq!(format_b0(&nonce, false, 0, 16)?[0], 0x39);
+
+        // with L = 2, the message length must fit in 16 bits
+        assert!(format_b0(&nonce, false, 0xffff, 8).is_ok());
+        assert!(matches!(
+            format_b0(&nonce, false, 0x10000, 8),
+            Err(Error::InputTooLong { len: 0x10000, max: 0xffff })
+        ));
+
+        assert_eq!(encode_aad_len(0), b"");
+        assert_eq!(encode_aad_len(8), [0x00, 0x08]);
+        assert_eq!(encode_aad_len(0xfeff), [0xfe, 0xff]);
+        assert_eq!(encode_aad_len(0xff00), [0xff, 0xfe, 0x00, 0x00, 0xff, 0x00]);
+        Ok(())
+    }
+
+    #[test]
+    fn ccm_published_vectors() -> Result<()> {
+        // (key, nonce, aad, plaintext, tag length, ciphertext || tag)
+        let rfc_key = "c0c1c2c3c4c5c6c7c8c9cacbcccdcecf";
+        let nist_key = "404142434445464748494a4b4c4d4e4f";
+        let vectors = [
+            // RFC 3610 packet vectors #1, #2, #3 and #9
+            (
+                rfc_key,
+                "00000003020100a0a1a2a3a4a5",
+                "0001020304050607",
+                "08090a0b0c0d0e0f101112131415161718191a1b1c1d1e",
+                8,
+                "588c979a61c663d2f066d0c2c0f989806d5f6b61dac38417e8d12cfdf926e0",
+            ),
+            (
+                rfc_key,
+                "00000004030201a0a1a2a3a4a5",
+                "0001020304050607",
+                "08090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+                8,
+                "72c91a36e135f8cf291ca894085c87e3cc15c439c9e43a3ba091d56e10400916",
+            ),
+            (
+                rfc_key,
+                "00000005040302a0a1a2a3a4a5",
+                "0001020304050607",
+                "08090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f20",
+                8,
+                "51b1e5f44a197d1da46b0f8e2d282ae871e838bb64da8596574adaa76fbd9fb0c5",
+            ),
+            (
+                rfc_key,
+                "00000009080706a0a1a2a3a4a5",
+                "0001020304050607",
+                "08090a0b0c0d0e0f101112131415161718191a1b1c1d1e",
+                10,
+                "0135d1b2c95f41d5d1d4fec185d166b8094e999dfed96c048c56602c97acbb7490",
+            ),
+            // NIST SP 800-38C appendix C, examples 1 to 3
+            (nist_key, "10111213141516", "0001020304050607", "20212223", 4, "7162015b4dac255d"),
+            (
+                nist_key,
+                "1011121314151617",
+                "000102030405060708090a0b0c0d0e0f",
+                "202122232425262728292a2b2c2d2e2f",
+                6,
+                "d2a1f0e051ea5f62081a7792073d593d1fc64fbfaccd",
+            ),
+            (
+                nist_key,
+                "101112131415161718191a1b",
+                "000102030405060708090a0b0c0d0e0f10111213",
+                "202122232425262728292a2b2c2d2e2f3031323334353637",
+                8,
+                "e3b201a9f5b71a7a9b1ceaeccd97e70b6176aad9a4428aa5484392fbc1b09951",
+            ),
+        ];
+
+        for (key, nonce, aad, pt, tag_len, expected) in vectors {
+            let cipher = Cipher::new(&Key::try_from_hex(key)?);
+            let (nonce, aad, pt, expected) =
+                (hex_to_bytes(nonce), hex_to_bytes(aad), hex_to_bytes(pt), hex_to_bytes(expected));
+
+            assert_eq!(ccm_encrypt(&pt, &nonce, &aad, tag_len, cipher.round_keys())?, expected);
+            assert_eq!(ccm_decrypt(&expected, &nonce, &aad, tag_len, cipher.round_keys())?, pt);
+
+            let mut bad = expected.clone();
+            *bad.last_mut().unwrap() ^= 1;
+            assert!(matches!(
+                ccm_decrypt(&bad, &nonce, &aad, tag_len, cipher.round_keys()),
+                Err(Error::AuthFailed)
+            ));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_invalid_parameters() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_128()?);
+        let rk = cipher.round_keys();
+        for len in [0, 6, 14, 16] {
+            let nonce = vec![0u8; len];
+            assert!(matches!(
+                ccm_encrypt(b"", &nonce, b"", 8, rk),
+                Err(Error::InvalidNonceLength { len: l }) if l == len
+            ));
+        }
+        for tag_len in [0, 2, 5, 17, 18] {
+            assert!(matches!(
+                ccm_encrypt(b"", &[0u8; 13], b"", tag_len, rk),
+                Err(Error::InvalidTagLength { bits }) if bits == tag_len * 8
+            ));
+        }
+        assert!(matches!(ccm_decrypt(&[0u8; 7], &[0u8; 13], b"", 8, rk), Err(Error::InvalidCiphertext { .. })));
+        Ok(())
+    }
+}
@@ -0,0 +1,111 @@
+//! PCLMULQDQ (carryless multiply) GHASH backend for x86-64. Selected by `GHashKey::new` when the CPU
+//! supports the `pclmulqdq` feature.
+
+use core::arch::x86_64::*;
+
+/// Returns true if the CPU supports PCLMULQDQ. The result is cached by the standard library after the first call.
+/// Without std there is no runtime detection, so this reports whether the feature was enabled at compile time.
+#[inline(always)]
+pub(crate) fn available() -> bool {
+    #[cfg(feature = "std")]
+    return std::arch::is_x86_feature_detected!("pclmulqdq");
+    #[cfg(not(feature = "std"))]
+    return cfg!(target_feature = "pclmulqdq");
+}
+
+/// Loads a GHASH block into a register. GHASH numbers bits from the MSB of the first byte, so the block is
+/// byte-reversed (loaded as a little-endian u128) to give the bit-reflected order the multiply expects.
+#[inline(always)]
+fn load(block: [u8; 16]) -> __m128i {
+    let v = u128::from_be_bytes(block);
+    // SAFETY: u128 and __m128i are both 16-byte plain-old-data types
+    unsafe { core::mem::transmute::<u128, __m128i>(v) }
+}
+
+#[inline(always)]
+fn store(v: __m128i) -> [u8; 16] {
+    // SAFETY: u128 and __m128i are both 16-byte plain-old-data types
+    unsafe { core::mem::transmute::<__m128i, u128>(v) }.to_be_bytes()
+}
+
+/// GHASH key for the carryless multiply backend.
+#[derive(Clone)]
+pub(crate) struct ClmulKey {
+    h: __m128i,
+}
+
+impl ClmulKey {
+    pub(crate) fn new(h: [u8; 16]) -> Self {
+        Self { h: load(h) }
+    }
+
+    /// Zero H.
+    pub(crate) fn wipe(&mut self) {
+        // SAFETY: self.h is a valid, aligned &mut __m128i
+        unsafe { core::ptr::write_volatile(&mut self.h, _mm_setzero_si128()) };
+        core::sync::atomic::compiler_fence(core::sync::atomic::Ordering::SeqCst);
+    }
+
+    /// For each 16-byte block in data:   s = (s ^ data[i]) * H
+    ///
+    /// # Safety
+    /// The CPU must support PCLMULQDQ (see [available]).
+    #[target_feature(enable = "pclmulqdq")]
+    pub(crate) unsafe fn ghash(&self, s: [u8; 16], data: &[u8]) -> [u8; 16] {
+        // keep the accumulator in a register across blocks
+        let mut acc = load(s);
+        let mut chunks = data.chunks_exact(16);
+        for chunk in &mut chunks {
+            acc = gf_mul(_mm_xor_si128(acc, load(chunk.try_into().unwrap())), self.h);
+        }
+
+        // zero-pad final partial block
+        let rem = chunks.remainder();
+        if !rem.is_empty() {
+            let mut block = [0u8; 16];
+            block[..rem.len()].copy_from_slice(rem);
+            acc = gf_mul(_mm_xor_si128(acc, load(block)), self.h);
+        }
+
+        store(acc)
+    }
+}
+
+/// Multiplication in GF(2^128) on bit-reflected operands: 128x128 -> 256-bit carryless multiply, a
+/// 1-bit left shift to account for the reflection, then reduction modulo x^128 + x^7 + x^2 + x + 1.
+/// Algorithm 5 of the Intel "Carry-Less Multiplication and Its Usage for Computing the GCM Mode" paper.
+#[inline]
+#[target_feature(enable = "pclmulqdq")]
+fn gf_mul(a: __m128i, b: __m128i) -> __m128i {
+    // schoolbook multiply: lo = a0*b0, hi = a1*b1, mid = a0*b1 + a1*b0
+    let mut lo = _mm_clmulepi64_si128(a, b, 0x00);
+    let mut mid = _mm_xor_si128(_mm_clmulepi64_si128(a, b, 0x10), _mm_clmulepi64_si128(a, b, 0x01));
+    let mut hi = _mm_clmulepi64_si128(a, b, 0x11);
+    lo = _mm_xor_si128(lo, _mm_slli_si128(mid, 8));
+    mid = _mm_srli_si128(mid, 8);
+    hi = _mm_xor_si128(hi, mid);
+
+    // shift the 256-bit product hi:lo left by 1
+    let lo_carry = _mm_srli_epi32(lo, 31);
+    let hi_carry = _mm_srli_epi32(hi, 31);
+    lo = _mm_slli_epi32(lo, 1);
+    hi = _mm_slli_epi32(hi, 1);
+    let cross = _mm_srli_si128(lo_carry, 12);
+    hi = _mm_or_si128(hi, _mm_slli_si128(hi_carry, 4));
+    hi = _mm_or_si128(hi, cross);
+    lo = _mm_or_si128(lo, _mm_slli_si128(lo_carry, 4));
+
+    // first phase of reduction
+    let mut t = _mm_xor_si128(_mm_slli_epi32(lo, 31), _mm_slli_epi32(lo, 30));
+    t = _mm_xor_si128(t, _mm_slli_epi32(lo, 25));
+    let t_hi = _mm_srli_si128(t, 4);
+    lo = _mm_xor_si128(lo, _mm_slli_si128(t, 12));
+
+    // second phase of reduction
+    let mut u = _mm_xor_si128(_mm_srli_epi32(lo, 1), _mm_srli_epi32(lo, 2));
+    u = _mm_xor_si128(u, _mm_srli_epi32(lo, 7));
+    u = _mm_xor_si128(u, t_hi);
+    lo = _mm_xor_si128(lo, u);
+
+    _mm_xor_si128(hi, lo)
+}
@@ -0,0 +1,144 @@
+use crate::aesp::core::encrypt_block;
+use crate::aesp::util::{xor_into, zeroize};
+
+/*
+https://www.rfc-editor.org/rfc/rfc4493
+
+L = encrypt_block(0^128, key)
+K1 = L << 1, XOR Rb if MSB(L) = 1
+K2 = K1 << 1, XOR Rb if MSB(K1) = 1
+
+final block:
+    - complete: M_last = M_n ^ K1
+    - partial (or empty message): M_last = (M_n || 0x80 || 0x00...) ^ K2
+
+X = 0, for each block: X = encrypt_block(X ^ M_i, key), using M_last for the final block
+tag = X
+*/
+
+/// Constant for subkey generation, x^128 = x^7 + x^2 + x + 1
+const RB: u128 = 0x87;
+
+/// Doubling in GF(2^128) as used by CMAC: left shift, XOR Rb if the MSB was set.
+#[inline(always)]
+fn dbl(block: [u8; 16]) -> [u8; 16] {
+    let v = u128::from_be_bytes(block);
+    ((v << 1) ^ (RB & 0u128.wrapping_sub(v >> 127))).to_be_bytes()
+}
+
+/// Core CMAC implementation (RFC 4493 / NIST SP 800-38B).
+pub fn cmac_core(message: &[u8], round_keys: &[[u8; 16]]) -> [u8; 16] {
+    // derive subkeys
+    let mut l = encrypt_block(&[0u8; 16], round_keys);
+    let mut k1 = dbl(l);
+    let mut k2 = dbl(k1);
+
+    // all blocks except the last are processed as-is (an empty message has a single, empty, last block)
+    let last_start = message.len().saturating_sub(1) / 16 * 16;
+    let (head, last) = message.split_at(last_start);
+
+    let mut x = [0u8; 16];
+    for block in head.chunks_exact(16) {
+        xor_into(&mut x, block);
+        x = encrypt_block(&x, round_keys);
+    }
+
+    // complete final block is masked with K1, otherwise pad with 0x80 || 0x00... and mask with K2
+    let mut m_last = [0u8; 16];
+    m_last[..last.len()].copy_from_slice(last);
+    if last.len() == 16 {
+        xor_into(&mut m_last, &k1);
+    } else {
+        m_last[last.len()] = 0x80;
+        xor_into(&mut m_last, &k2);
+    }
+
+    xor_into(&mut x, &m_last);
+    let tag = encrypt_block(&x, round_keys);
+
+    zeroize(&mut l);
+    zeroize(&mut k1);
+    zeroize(&mut k2);
+    tag
+}
+
+#[cfg(test)]
+mod test_cmac {
+    use super::*;
+    use crate::aesp::modes::util::test_util::{KEY_128, KEY_192, KEY_256, PLAINTEXT, hex_to_arr_16};
+    use crate::{Cipher, Key, Result};
+
+    // message lengths from RFC 4493 section 4 (and NIST SP 800-38B appendix D)
+    const LENGTHS: [usize; 4] = [0, 16, 40, 64];
+
+    fn check(key: &[u8], expected: [&str; 4]) -> Result<()> {
+        let cipher = Cipher::new(&Key::try_from_slice(key)?);
+        for (len, tag) in LENGTHS.into_iter().zip(expected) {
+            let expected = hex_to_arr_16(tag);
+            assert_eq!(cipher.cmac(&PLAINTEXT[..len]), expected, "message length {len}");
+            cipher.verify_cmac(&PLAINTEXT[..len], &expected)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn subkeys() {
+        // RFC 4493 section 4: L, K1 and K2 for the AES-128 example key
+        let cipher = Cipher::new(&Key::try_from_slice(&KEY_128).unwrap());
+        let l = encrypt_block(&[0u8; 16], cipher.round_keys());
+        assert_eq!(l, hex_to_arr_16("7df76b0c1ab899b33e42f047b91b546f"));
+        assert_eq!(dbl(l), hex_to_arr_16("fbeed618357133667c85e08f7236a8de"));
+        assert_eq!(dbl(dbl(l)), hex_to_arr_16("f7ddac306ae266ccf90bc11ee46d513b"));
+    }
+
+    #[test]
+    fn cmac_128() -> Result<()> {
+        check(
+            &KEY_128,
+            [
+                "bb1d6929e95937287fa37d129b756746",
+                "070a16b46b4d4144f79bdd9dd04a287c",
+                "dfa66747de9ae63030ca32611497c827",
+                "51f0bebf7e3b9d92fc49741779363cfe",
+            ],
+        )
+    }
+
+    #[test]
+    fn cmac_192() -> Result<()> {
+        check(
+            &KEY_192,
+            [
+                "d17ddf46adaacde531cac483de7a9367",
+                "9e99a7bf31e710900662f65e617c5184",
+                "8a1de5be2eb31aad089a82e6ee908b0e",
+                "a1d5df0eed790f794d77589659f39a11",
+            ],
+        )
+    }
+
+    #[test]
+    fn cmac_256() -> Result<()> {
+        check(
+            &KEY_256,
+            [
+                "028962f61b7bf89efc6b551f4667d983",
+                "28a7023f452e8f82bd4bf28d8c37c35c",
+                "aaf3d8f1de5640c232f5b169b9c911e6",
+                "e1992190549f6ed5696a2c056c315410",
+            ],
+        )
+    }
+
+    #[test]
+    fn verify_rejects_modification() -> Result<()> {
+        let cipher = Cipher::new(&Key::try_from_slice(&KEY_128)?);
+        let tag = cipher.cmac(&PLAINTEXT[..40]);
+
+        let mut bad_tag = tag;
+        bad_tag[3] ^= 1;
+        assert!(matches!(cipher.verify_cmac(&PLAINTEXT[..40], &bad_tag), Err(crate::Error::AuthFailed)));
+        assert!(matches!(cipher.verify_cmac(&PLAINTEXT[..39], &tag), Err(crate::Error::AuthFailed)));
+        Ok(())
+    }
+}
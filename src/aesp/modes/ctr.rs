@@ -1,74 +1,212 @@
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
 use rayon::prelude::*;
 
-use crate::aesp::core::encrypt_block;
+use crate::aesp::core::{encrypt_block, encrypt_blocks8};
 use crate::aesp::error::*;
-use crate::aesp::modes::util::ctr_block;
+use crate::aesp::modes::util::{ctr_block, ctr_block64};
+use crate::aesp::util::{xor_into, zeroize};
 
 /// Core counter encryption and decryption implementation.
+/// Inputs longer than `parallel_threshold` bytes are processed in parallel.
 pub fn ctr_core(
     input: &[u8],
     round_keys: &[[u8; 16]],
     iv: &[u8; 12],
     ctr_start: u32,
+    parallel_threshold: usize,
 ) -> Result<Vec<u8>> {
-    if input.is_empty() {
-        return Ok(Vec::new());
+    let mut output = input.to_vec();
+    ctr_core_in_place(&mut output, round_keys, iv, ctr_start, parallel_threshold)?;
+    Ok(output)
+}
+
+/// Core counter encryption and decryption implementation, operating in place.
+/// `XOR`s the keystream directly into `buf`.
+pub fn ctr_core_in_place(
+    buf: &mut [u8],
+    round_keys: &[[u8; 16]],
+    iv: &[u8; 12],
+    ctr_start: u32,
+    parallel_threshold: usize,
+) -> Result<()> {
+    // empty input must return early, otherwise num_blocks - 1 below underflows
+    if buf.is_empty() {
+        return Ok(());
     }
 
     // check if counter will overflow
-    let num_blocks = u32::try_from((input.len() + 15) / 16).map_err(|_| Error::CounterOverflow)?;
+    let num_blocks = u32::try_from(buf.len().div_ceil(16)).map_err(|_| Error::CounterOverflow)?;
     ctr_start
         .checked_add(num_blocks - 1)
         .ok_or(Error::CounterOverflow)?;
 
-    let mut output = vec![0u8; input.len()];
-    if input.len() > crate::aesp::modes::util::PARALLEL_THRESHOLD {
-        // encrypt in parallel if size exceeds threshold
-        output
-            .par_chunks_mut(16)
-            .zip(input.par_chunks(16))
-            .enumerate()
-            .for_each(|(i, (out_chunk, in_chunk))| {
-                let ctr = ctr_start + i as u32; // overflow already checked above
-                let block = ctr_block(iv, ctr); // form block from iv + ctr
-                // xor each element of input chunk (1-16 bytes) with encrypted ctr block
-                let keystream = encrypt_block(&block, round_keys);
-
-                // XOR bytes of block (last chunk may be lt 16)
-                for j in 0..in_chunk.len() {
-                    out_chunk[j] = keystream[j] ^ in_chunk[j];
-                }
-            });
-    } else {
-        // input len below threshold, encrypt serially
-        output
-            .chunks_mut(16)
-            .zip(input.chunks(16))
-            .enumerate()
-            .for_each(|(i, (out_chunk, in_chunk))| {
-                let ctr = ctr_start + i as u32; // overflow already checked above
-                let block = ctr_block(iv, ctr); // form block from iv + ctr
-                // xor each element of input chunk (1-16 bytes) with encrypted ctr block
-                let keystream = encrypt_block(&block, round_keys);
-
-                // XOR bytes of block (last chunk may be lt 16)
-                for j in 0..in_chunk.len() {
-                    out_chunk[j] = keystream[j] ^ in_chunk[j];
-                }
-            });
+    // keystream is generated 8 blocks (128 bytes) at a time
+    let apply_keystream = |(i, chunk): (usize, &mut [u8])| {
+        let batch_start = ctr_start + (i * 8) as u32; // overflow already checked above
+
+        if chunk.len() == 128 {
+            // form 8 blocks from iv + ctr, encrypt together
+            let blocks = core::array::from_fn(|j| ctr_block(iv, batch_start + j as u32));
+            let mut keystream = encrypt_blocks8(&blocks, round_keys);
+            xor_into(chunk, keystream.as_flattened());
+            zeroize(keystream.as_flattened_mut());
+        } else {
+            // final partial batch, one block at a time (last block may be lt 16)
+            for (j, block_chunk) in chunk.chunks_mut(16).enumerate() {
+                let block = ctr_block(iv, batch_start + j as u32);
+                let mut keystream = encrypt_block(&block, round_keys);
+                xor_into(block_chunk, &keystream);
+                zeroize(&mut keystream);
+            }
+        }
+    };
+
+    // encrypt in parallel if size exceeds threshold
+    #[cfg(feature = "std")]
+    if buf.len() > parallel_threshold {
+        buf.par_chunks_mut(128).enumerate().for_each(apply_keystream);
+        return Ok(());
     }
+    // no thread pool without std
+    #[cfg(not(feature = "std"))]
+    let _ = parallel_threshold;
+
+    // input len below threshold, encrypt serially
+    buf.chunks_mut(128).enumerate().for_each(apply_keystream);
+    Ok(())
+}
 
+/// Counter mode with a 64-bit counter, for streams longer than the 2^32 blocks allowed by [ctr_core].
+/// Counter blocks are `IV (8 bytes) || Counter (8 bytes)`, see [ctr_block64].
+/// Inputs longer than `parallel_threshold` bytes are processed in parallel.
+pub fn ctr_core_64(
+    input: &[u8],
+    round_keys: &[[u8; 16]],
+    iv: &[u8; 8],
+    ctr_start: u64,
+    parallel_threshold: usize,
+) -> Result<Vec<u8>> {
+    let mut output = input.to_vec();
+    if output.is_empty() {
+        return Ok(output);
+    }
+
+    // check if counter will overflow
+    let num_blocks = output.len().div_ceil(16) as u64;
+    ctr_start
+        .checked_add(num_blocks - 1)
+        .ok_or(Error::CounterOverflow)?;
+
+    // keystream is generated 8 blocks (128 bytes) at a time
+    let apply_keystream = |(i, chunk): (usize, &mut [u8])| {
+        let batch_start = ctr_start + (i * 8) as u64; // overflow already checked above
+
+        if chunk.len() == 128 {
+            let blocks = core::array::from_fn(|j| ctr_block64(iv, batch_start + j as u64));
+            let mut keystream = encrypt_blocks8(&blocks, round_keys);
+            xor_into(chunk, keystream.as_flattened());
+            zeroize(keystream.as_flattened_mut());
+        } else {
+            for (j, block_chunk) in chunk.chunks_mut(16).enumerate() {
+                let block = ctr_block64(iv, batch_start + j as u64);
+                let mut keystream = encrypt_block(&block, round_keys);
+                xor_into(block_chunk, &keystream);
+                zeroize(&mut keystream);
+            }
+        }
+    };
+
+    // encrypt in parallel if size exceeds threshold
+    #[cfg(feature = "std")]
+    if output.len() > parallel_threshold {
+        output.par_chunks_mut(128).enumerate().for_each(apply_keystream);
+        return Ok(output);
+    }
+    // no thread pool without std
+    #[cfg(not(feature = "std"))]
+    let _ = parallel_threshold;
+
+    output.chunks_mut(128).enumerate().for_each(apply_keystream);
     Ok(output)
 }
 
 #[cfg(test)]
 mod test_ctr {
     use super::*;
+    use crate::aesp::modes::util::PARALLEL_THRESHOLD;
     use crate::aesp::modes::util::test_util::{
         CTR_IV, CTR_START, KEY_128, KEY_192, KEY_256, PLAINTEXT, hex_to_bytes,
     };
     use crate::{Cipher, Key};
 
+    #[test]
+    fn batched_keystream_matches_scalar() -> Result<()> {
+        let key = Key::try_from_slice(&KEY_256)?;
+        let cipher = Cipher::new(&key);
+        let rk = cipher.round_keys();
+
+        // lengths around the 128-byte batch size, and above the parallel threshold
+        for len in [1, 16, 127, 128, 129, 255, 256, 257, 1000, 5000, 5003] {
+            let input: Vec<u8> = (0..len).map(|i| (i * 13) as u8).collect();
+            let expected: Vec<u8> = input
+                .chunks(16)
+                .enumerate()
+                .flat_map(|(i, chunk)| {
+                    let keystream = encrypt_block(&ctr_block(&CTR_IV, 5 + i as u32), rk);
+                    chunk.iter().zip(keystream).map(|(a, b)| a ^ b).collect::<Vec<u8>>()
+                })
+                .collect();
+            assert_eq!(ctr_core(&input, rk, &CTR_IV, 5, PARALLEL_THRESHOLD)?, expected, "length {len}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn counter_64_carries_past_32_bits() -> Result<()> {
+        let cipher = Cipher::new(&Key::try_from_slice(&KEY_128)?);
+        let rk = cipher.round_keys();
+        let iv = [0xa5; 8];
+
+        // start just below 2^32, where a 32-bit counter would overflow, with enough blocks to cross it
+        let start = u64::from(u32::MAX) - 2;
+        let input: Vec<u8> = (0..1000u32).map(|i| (i * 7) as u8).collect();
+        let expected: Vec<u8> = input
+            .chunks(16)
+            .enumerate()
+            .flat_map(|(i, chunk)| {
+                let keystream = encrypt_block(&ctr_block64(&iv, start + i as u64), rk);
+                chunk.iter().zip(keystream).map(|(a, b)| a ^ b).collect::<Vec<u8>>()
+            })
+            .collect();
+        for threshold in [0, usize::MAX] {
+            assert_eq!(ctr_core_64(&input, rk, &iv, start, threshold)?, expected);
+        }
+        // the counter carries into the upper 32 bits rather than wrapping
+        assert_eq!(ctr_block64(&iv, start + 3)[8..], [0, 0, 0, 1, 0, 0, 0, 0]);
+
+        // the final block may use u64::MAX, but not run past it
+        assert_eq!(ctr_core_64(&input[..32], rk, &iv, u64::MAX - 1, usize::MAX)?.len(), 32);
+        assert!(matches!(
+            ctr_core_64(&input[..33], rk, &iv, u64::MAX - 1, usize::MAX),
+            Err(Error::CounterOverflow)
+        ));
+        assert!(ctr_core_64(&[], rk, &iv, u64::MAX, usize::MAX)?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn empty_input() -> Result<()> {
+        let key = Key::try_from_slice(&KEY_128)?;
+        let cipher = Cipher::new(&key);
+
+        // no blocks are processed, so even the maximum counter start cannot overflow
+        for ctr_start in [0, 2, CTR_START, u32::MAX] {
+            assert!(ctr_core(&[], cipher.round_keys(), &CTR_IV, ctr_start, PARALLEL_THRESHOLD)?.is_empty());
+        }
+        Ok(())
+    }
+
     #[test]
     fn aes_ctr_128_encrypt() -> Result<()> {
         let expected = hex_to_bytes(
@@ -81,7 +219,7 @@ mod test_ctr {
 
         let key = Key::try_from_slice(&KEY_128)?;
         let cipher = Cipher::new(&key);
-        let encrypted = ctr_core(&PLAINTEXT, cipher.round_keys(), &CTR_IV, CTR_START)?;
+        let encrypted = ctr_core(&PLAINTEXT, cipher.round_keys(), &CTR_IV, CTR_START, PARALLEL_THRESHOLD)?;
 
         assert_eq!(
             expected, encrypted,
@@ -102,7 +240,7 @@ mod test_ctr {
 
         let key = Key::try_from_slice(&KEY_128)?;
         let cipher = Cipher::new(&key);
-        let decrypted = ctr_core(&ciphertext, cipher.round_keys(), &CTR_IV, CTR_START)?;
+        let decrypted = ctr_core(&ciphertext, cipher.round_keys(), &CTR_IV, CTR_START, PARALLEL_THRESHOLD)?;
 
         assert_eq!(
             PLAINTEXT.to_vec(),
@@ -124,7 +262,7 @@ mod test_ctr {
 
         let key = Key::try_from_slice(&KEY_192)?;
         let cipher = Cipher::new(&key);
-        let encrypted = ctr_core(&PLAINTEXT, cipher.round_keys(), &CTR_IV, CTR_START)?;
+        let encrypted = ctr_core(&PLAINTEXT, cipher.round_keys(), &CTR_IV, CTR_START, PARALLEL_THRESHOLD)?;
 
         assert_eq!(
             expected, encrypted,
@@ -145,7 +283,7 @@ mod test_ctr {
 
         let key = Key::try_from_slice(&KEY_192)?;
         let cipher = Cipher::new(&key);
-        let decrypted = ctr_core(&ciphertext, cipher.round_keys(), &CTR_IV, CTR_START)?;
+        let decrypted = ctr_core(&ciphertext, cipher.round_keys(), &CTR_IV, CTR_START, PARALLEL_THRESHOLD)?;
 
         assert_eq!(
             PLAINTEXT.to_vec(),
@@ -167,7 +305,7 @@ mod test_ctr {
 
         let key = Key::try_from_slice(&KEY_256)?;
         let cipher = Cipher::new(&key);
-        let encrypted = ctr_core(&PLAINTEXT, cipher.round_keys(), &CTR_IV, CTR_START)?;
+        let encrypted = ctr_core(&PLAINTEXT, cipher.round_keys(), &CTR_IV, CTR_START, PARALLEL_THRESHOLD)?;
 
         assert_eq!(
             expected, encrypted,
@@ -188,7 +326,7 @@ mod test_ctr {
 
         let key = Key::try_from_slice(&KEY_256)?;
         let cipher = Cipher::new(&key);
-        let decrypted = ctr_core(&ciphertext, cipher.round_keys(), &CTR_IV, CTR_START)?;
+        let decrypted = ctr_core(&ciphertext, cipher.round_keys(), &CTR_IV, CTR_START, PARALLEL_THRESHOLD)?;
 
         assert_eq!(
             PLAINTEXT.to_vec(),
@@ -0,0 +1,218 @@
+use alloc::vec::Vec;
+
+use crate::aesp::cipher::Cipher;
+use crate::aesp::core::encrypt_block;
+use crate::aesp::error::*;
+use crate::aesp::modes::ctr::ctr_core_in_place;
+use crate::aesp::modes::util::ctr_block;
+use crate::aesp::util::{xor_into, zeroize};
+
+/// Incremental **counter mode** encryption and decryption.
+///
+/// Processes input in chunks of any size, producing the same output as a single call over the
+/// concatenated input. Keystream bytes left over from a partial block are carried between calls to
+/// [update](crate::CtrStream::update), so chunks need not be aligned to the 16-byte block size.
+///
+/// The counter starts at 0, matching [encrypt_ctr](crate::Cipher::encrypt_ctr). Unlike `encrypt_ctr`,
+/// the IV is not included in the output and must be transmitted separately. The same IV reuse warning
+/// applies: never reuse an IV under the same key.
+///
+/// ## Examples
+/// ```
+/// # fn main() -> aesp::Result<()> {
+/// use aesp::{Cipher, CtrStream, Key};
+///
+/// let cipher = Cipher::new(&Key::rand_key_256()?);
+/// let iv = [0x24; 12];
+///
+/// let mut enc = CtrStream::new(&cipher, &iv);
+/// let mut ciphertext = enc.update(b"Hello, ")?;
+/// ciphertext.extend(enc.update(b"World!")?);
+/// ciphertext.extend(enc.finalize());
+///
+/// let mut dec = CtrStream::new(&cipher, &iv);
+/// assert_eq!(dec.update(&ciphertext)?, b"Hello, World!");
+/// # Ok(())
+/// # }
+/// ```
+pub struct CtrStream<'a> {
+    cipher: &'a Cipher,
+    iv: [u8; 12],
+    /// counter of the next keystream block. u64 so that running past block 2^32 - 1 can be detected.
+    counter: u64,
+    /// keystream block for the current partial block
+    keystream: [u8; 16],
+    /// number of bytes of `keystream` already used (16 when there is no partial block)
+    used: usize,
+}
+
+impl<'a> CtrStream<'a> {
+    /// Instantiates a stream under `cipher`, using `iv` with a counter starting at 0.
+    pub fn new(cipher: &'a Cipher, iv: &[u8; 12]) -> Self {
+        Self {
+            cipher,
+            iv: *iv,
+            counter: 0,
+            keystream: [0u8; 16],
+            used: 16,
+        }
+    }
+
+    /// Instantiates a stream whose first keystream block uses counter `ctr_start`. Used by GCM, which
+    /// starts encryption at counter 2.
+    pub(crate) fn with_counter(cipher: &'a Cipher, iv: &[u8; 12], ctr_start: u32) -> Self {
+        let mut stream = Self::new(cipher, iv);
+        stream.counter = ctr_start as u64;
+        stream
+    }
+
+    /// Encrypts or decrypts the next chunk of input. Returns [CounterOverflow](crate::Error::CounterOverflow)
+    /// if the total input would exceed 2^32 blocks, in which case no input is consumed.
+    pub fn update(&mut self, data: &[u8]) -> Result<Vec<u8>> {
+        let mut out = data.to_vec();
+        self.apply_keystream(&mut out)?;
+        Ok(out)
+    }
+
+    /// Finishes the stream. CTR does not buffer input, so all output has already been returned by
+    /// [update](crate::CtrStream::update) and the returned vector is always empty. Any leftover keystream
+    /// is wiped.
+    pub fn finalize(self) -> Vec<u8> {
+        Vec::new()
+    }
+
+    /// Encrypts or decrypts `buf` in place. Same as [update](crate::CtrStream::update), without allocating.
+    pub(crate) fn apply_keystream(&mut self, buf: &mut [u8]) -> Result<()> {
+        // check for counter overflow before modifying any state
+        let leftover = 16 - self.used;
+        let new_blocks = buf.len().saturating_sub(leftover).div_ceil(16) as u64;
+        if self.counter + new_blocks > 1 << 32 {
+            return Err(Error::CounterOverflow);
+        }
+
+        // use up keystream left over from the previous call
+        let take = leftover.min(buf.len());
+        let (head, rest) = buf.split_at_mut(take);
+        xor_into(head, &self.keystream[self.used..]);
+        self.used += take;
+
+        // whole blocks go through the (possibly parallel) core implementation
+        let (blocks, tail) = rest.split_at_mut(rest.len() / 16 * 16);
+        if !blocks.is_empty() {
+            ctr_core_in_place(
+                blocks,
+                self.cipher.round_keys(),
+                &self.iv,
+                self.counter as u32,
+                self.cipher.parallel_threshold(),
+            )?;
+            self.counter += (blocks.len() / 16) as u64;
+        }
+
+        // partial final block, keeping the unused keystream for the next call
+        if !tail.is_empty() {
+            let block = ctr_block(&self.iv, self.counter as u32);
+            self.keystream = encrypt_block(&block, self.cipher.round_keys());
+            self.counter += 1;
+            xor_into(tail, &self.keystream);
+            self.used = tail.len();
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for CtrStream<'_> {
+    fn drop(&mut self) {
+        zeroize(&mut self.keystream);
+    }
+}
+
+#[cfg(test)]
+mod test_ctr_stream {
+    use super::*;
+    use crate::Key;
+
+    fn test_data(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 7 + 3) as u8).collect()
+    }
+
+    #[test]
+    fn odd_chunks_match_one_shot() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_256()?);
+        let iv = [0x5a; 12];
+        let plaintext = test_data(10_000); // crosses the parallel threshold
+
+        let one_shot = CtrStream::new(&cipher, &iv).update(&plaintext)?;
+
+        for sizes in [&[1usize][..], &[3, 5, 7], &[15, 16, 17], &[13, 4097, 31, 1]] {
+            let mut stream = CtrStream::new(&cipher, &iv);
+            let mut chunked = Vec::with_capacity(plaintext.len());
+            let mut rest = &plaintext[..];
+            for &size in sizes.iter().cycle() {
+                if rest.is_empty() {
+                    break;
+                }
+                let (chunk, tail) = rest.split_at(size.min(rest.len()));
+                chunked.extend(stream.update(chunk)?);
+                rest = tail;
+            }
+            chunked.extend(stream.finalize());
+            assert_eq!(chunked, one_shot, "chunk sizes {sizes:?}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn matches_encrypt_ctr() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_128()?);
+        let plaintext = test_data(100);
+
+        // encrypt_ctr output: IV || ciphertext
+        let expected = cipher.encrypt_ctr(&plaintext)?;
+        let iv: [u8; 12] = expected[..12].try_into().unwrap();
+
+        let mut stream = CtrStream::new(&cipher, &iv);
+        let mut ciphertext = stream.update(&plaintext[..33])?;
+        ciphertext.extend(stream.update(&plaintext[33..])?);
+        assert_eq!(ciphertext, expected[12..]);
+
+        let mut stream = CtrStream::new(&cipher, &iv);
+        assert_eq!(stream.update(&ciphertext)?, plaintext);
+        Ok(())
+    }
+
+    #[test]
+    fn empty_updates() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_128()?);
+        let iv = [0u8; 12];
+        let plaintext = test_data(40);
+
+        let mut stream = CtrStream::new(&cipher, &iv);
+        let mut ciphertext = stream.update(&[])?;
+        ciphertext.extend(stream.update(&plaintext[..20])?);
+        ciphertext.extend(stream.update(&[])?);
+        ciphertext.extend(stream.update(&plaintext[20..])?);
+        assert!(stream.finalize().is_empty());
+
+        assert_eq!(ciphertext, CtrStream::new(&cipher, &iv).update(&plaintext)?);
+        Ok(())
+    }
+
+    #[test]
+    fn counter_overflow() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_128()?);
+        let mut stream = CtrStream::new(&cipher, &[0u8; 12]);
+        stream.counter = u32::MAX as u64; // one block remaining
+
+        // a partial block uses the final counter, leftover keystream remains usable
+        stream.update(&[0u8; 10])?;
+        stream.update(&[0u8; 6])?;
+
+        // no counter values remain, state is unchanged on failure
+        assert!(matches!(stream.update(&[0u8; 1]), Err(Error::CounterOverflow)));
+        assert_eq!(stream.counter, 1 << 32);
+        assert!(stream.update(&[])?.is_empty());
+        Ok(())
+    }
+}
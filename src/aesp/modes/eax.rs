@@ -0,0 +1,192 @@
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use rayon::prelude::*;
+
+use crate::aesp::core::{encrypt_block, encrypt_blocks8};
+use crate::aesp::error::*;
+use crate::aesp::modes::cmac_core;
+use crate::aesp::util::{ct_eq, xor_into};
+
+/*
+https://web.cs.ucdavis.edu/~rogaway/papers/eax.pdf
+
+EAX is built from the CMAC (OMAC1) module in cmac.rs and counter mode:
+    OMAC^t(M) = CMAC([0]^15 || t || M), a CMAC tweaked by a full block holding t
+    N' = OMAC^0(nonce)
+    H' = OMAC^1(header)
+    C  = CTR(M), counter blocks N', N' + 1, ... (the whole block is the counter, mod 2^128)
+    C' = OMAC^2(C)
+    tag = N' ^ H' ^ C'
+
+the nonce and header may be any length, including empty.
+*/
+
+/// CMAC of `message`, tweaked by prefixing a block holding `t`.
+fn omac(t: u8, message: &[u8], round_keys: &[[u8; 16]]) -> [u8; 16] {
+    let mut input = Vec::with_capacity(16 + message.len());
+    input.extend_from_slice(&[0u8; 15]);
+    input.push(t);
+    input.extend_from_slice(message);
+    cmac_core(&input, round_keys)
+}
+
+/// Counter mode with a full 128-bit counter starting at `n`, wrapping at 2^128 as EAX requires.
+/// Inputs longer than `parallel_threshold` bytes are processed in parallel.
+fn eax_ctr(input: &[u8], round_keys: &[[u8; 16]], n: &[u8; 16], parallel_threshold: usize) -> Vec<u8> {
+    let mut output = input.to_vec();
+    let n = u128::from_be_bytes(*n);
+
+    // keystream is generated 8 blocks (128 bytes) at a time
+    let apply_keystream = |(i, chunk): (usize, &mut [u8])| {
+        let batch_start = n.wrapping_add((i * 8) as u128);
+
+        if chunk.len() == 128 {
+            let blocks = core::array::from_fn(|j| batch_start.wrapping_add(j as u128).to_be_bytes());
+            let keystream = encrypt_blocks8(&blocks, round_keys);
+            xor_into(chunk, keystream.as_flattened());
+        } else {
+            for (j, block_chunk) in chunk.chunks_mut(16).enumerate() {
+                let block = batch_start.wrapping_add(j as u128).to_be_bytes();
+                xor_into(block_chunk, &encrypt_block(&block, round_keys));
+            }
+        }
+    };
+
+    // encrypt in parallel if size exceeds threshold
+    #[cfg(feature = "std")]
+    if output.len() > parallel_threshold {
+        output.par_chunks_mut(128).enumerate().for_each(apply_keystream);
+        return output;
+    }
+    // no thread pool without std
+    #[cfg(not(feature = "std"))]
+    let _ = parallel_threshold;
+
+    output.chunks_mut(128).enumerate().for_each(apply_keystream);
+    output
+}
+
+/// EAX encryption. Returns the ciphertext, which is the same length as the plaintext, and the tag.
+pub fn eax_encrypt(
+    plaintext: &[u8],
+    nonce: &[u8],
+    header: &[u8],
+    round_keys: &[[u8; 16]],
+    parallel_threshold: usize,
+) -> (Vec<u8>, [u8; 16]) {
+    let n = omac(0, nonce, round_keys);
+    let ciphertext = eax_ctr(plaintext, round_keys, &n, parallel_threshold);
+
+    let mut tag = n;
+    xor_into(&mut tag, &omac(1, header, round_keys));
+    xor_into(&mut tag, &omac(2, &ciphertext, round_keys));
+    (ciphertext, tag)
+}
+
+/// EAX decryption. The tag is verified before anything is decrypted.
+pub fn eax_decrypt(
+    ciphertext: &[u8],
+    tag: &[u8; 16],
+    nonce: &[u8],
+    header: &[u8],
+    round_keys: &[[u8; 16]],
+    parallel_threshold: usize,
+) -> Result<Vec<u8>> {
+    let n = omac(0, nonce, round_keys);
+
+    let mut computed_tag = n;
+    xor_into(&mut computed_tag, &omac(1, header, round_keys));
+    xor_into(&mut computed_tag, &omac(2, ciphertext, round_keys));
+    if !ct_eq(tag, &computed_tag) {
+        return Err(Error::AuthFailed);
+    }
+
+    Ok(eax_ctr(ciphertext, round_keys, &n, parallel_threshold))
+}
+
+#[cfg(test)]
+mod test_eax {
+    use super::*;
+    use crate::aesp::modes::util::test_util::hex_to_bytes;
+    use crate::{Cipher, Key};
+
+    #[test]
+    fn eax_paper_vectors() -> Result<()> {
+        // Bellare, Rogaway and Wagner, "The EAX Mode of Operation", appendix: (msg, key, nonce, header, ct || tag)
+        let vectors = [
+            (
+                "",
+                "233952dee4d5ed5f9b9c6d6ff80ff478",
+                "62ec67f9c3a4a407fcb2a8c49031a8b3",
+                "6bfb914fd07eae6b",
+                "e037830e8389f27b025a2d6527e79d01",
+            ),
+            (
+                "f7fb",
+                "91945d3f4dcbee0bf45ef52255f095a4",
+                "becaf043b0a23d843194ba972c66debd",
+                "fa3bfd4806eb53fa",
+                "19dd5c4c9331049d0bdab0277408f67967e5",
+            ),
+            (
+                "1a47cb4933",
+                "01f74ad64077f2e704c0f60ada3dd523",
+                "70c3db4f0d26368400a10ed05d2bff5e",
+                "234a3463c1264ac6",
+                "d851d5bae03a59f238a23e39199dc9266626c40f80",
+            ),
+            (
+                "481c9e39b1",
+                "d07cf6cbb7f313bdde66b727afd3c5e8",
+                "8408dfff3c1a2b1292dc199e46b7d617",
+                "33cce2eabff5a79d",
+                "632a9d131ad4c168a4225d8e1ff755939974a7bede",
+            ),
+            (
+                "40d0c07da5e4",
+                "35b6d0580005bbc12b0587124557d2c2",
+                "fdb6b06676eedc5c61d74276e1f8e816",
+                "aeb96eaebe2970e9",
+                "071dfe16c675cb0677e536f73afe6a14b74ee49844dd",
+            ),
+            (
+                "8b0a79306c9ce7ed99dae4f87f8dd61636",
+                "7c77d6e813bed5ac98baa417477a2e7d",
+                "1a8c98dcd73d38393b2bf1569deefc19",
+                "65d2017990d62528",
+                "02083e3979da014812f59f11d52630da30137327d10649b0aa6e1c181db617d7f2",
+            ),
+        ];
+
+        for (msg, key, nonce, header, expected) in vectors {
+            let cipher = Cipher::new(&Key::try_from_hex(key)?);
+            let (msg, nonce, header, expected) =
+                (hex_to_bytes(msg), hex_to_bytes(nonce), hex_to_bytes(header), hex_to_bytes(expected));
+
+            let (ct, tag) = eax_encrypt(&msg, &nonce, &header, cipher.round_keys(), usize::MAX);
+            assert_eq!([&ct[..], &tag[..]].concat(), expected);
+            let tag: [u8; 16] = expected[msg.len()..].try_into().unwrap();
+            assert_eq!(eax_decrypt(&ct, &tag, &nonce, &header, cipher.round_keys(), usize::MAX)?, msg);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn counter_wraps_at_128_bits() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_128()?);
+        let rk = cipher.round_keys();
+        let keystream = eax_ctr(&[0u8; 32], rk, &[0xff; 16], usize::MAX);
+        assert_eq!(keystream[..16], encrypt_block(&[0xff; 16], rk));
+        assert_eq!(keystream[16..], encrypt_block(&[0u8; 16], rk));
+        Ok(())
+    }
+
+    #[test]
+    fn parallel_matches_serial() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_256()?);
+        let msg: Vec<u8> = (0..5000).map(|i| i as u8).collect();
+        let serial = eax_encrypt(&msg, b"nonce", b"header", cipher.round_keys(), usize::MAX);
+        assert_eq!(eax_encrypt(&msg, b"nonce", b"header", cipher.round_keys(), 0), serial);
+        Ok(())
+    }
+}
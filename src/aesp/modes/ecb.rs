@@ -1,60 +1,90 @@
+use alloc::vec;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
 use rayon::prelude::*;
 
-use crate::aesp::core::{decrypt_block, encrypt_block};
+use crate::aesp::core::{decrypt_block_eq, encrypt_block};
 use crate::aesp::error::*;
-use crate::aesp::modes::util::PARALLEL_THRESHOLD;
+use crate::aesp::modes::Padding;
+use crate::aesp::util::{pad, unpad, zeroize};
 
 /// Core ECB encryption/decryption algorithm.
 /// Crypts in 16-byte blocks to form output.
 /// Input length must be a multiple of 16, InvalidECBInput error if not.
-fn ecb_core<F>(input: &[u8], round_keys: &[[u8; 16]], block_fn: F) -> Result<Vec<u8>>
+/// Inputs longer than `parallel_threshold` bytes are processed in parallel.
+fn ecb_core<F>(input: &[u8], round_keys: &[[u8; 16]], parallel_threshold: usize, block_fn: F) -> Result<Vec<u8>>
 where
     F: Fn(&[u8; 16], &[[u8; 16]]) -> [u8; 16] + Sync + Copy,
 {
-    if input.len() % 16 != 0 {
+    if !input.len().is_multiple_of(16) {
         return Err(Error::InvalidECBInput { len: input.len() });
     }
 
     let mut output = vec![0u8; input.len()];
-
-    // encrypt in parallel if feature enabled and size exceeds threshold
-    if input.len() > PARALLEL_THRESHOLD {
-        output
-            .par_chunks_exact_mut(16)
-            .zip(input.par_chunks_exact(16))
-            .for_each(|(ct, pt)| {
-                // convert pt into [u8; 16] - safe to unwrap, used chunks_exact(16)
-                let pt_block: &[u8; 16] = pt.try_into().unwrap();
-                let enc = block_fn(pt_block, round_keys);
-                ct.copy_from_slice(&enc);
-            });
-    } else {
-        // encrypt serially
-        output
-            .chunks_exact_mut(16)
-            .zip(input.chunks_exact(16))
-            .for_each(|(ct, pt)| {
-                // convert pt into [u8; 16] - safe to unwrap, used chunks_exact(16)
-                let pt_block: &[u8; 16] = pt.try_into().unwrap();
-                let enc = block_fn(pt_block, round_keys);
-                ct.copy_from_slice(&enc);
-            });
+    let crypt = |(ct, pt): (&mut [u8], &[u8])| {
+        // convert pt into [u8; 16] - safe to unwrap, used chunks_exact(16)
+        let pt_block: &[u8; 16] = pt.try_into().unwrap();
+        let mut enc = block_fn(pt_block, round_keys);
+        ct.copy_from_slice(&enc);
+        // after decryption this is plaintext; wiped in every closure, including on worker threads
+        zeroize(&mut enc);
+    };
+
+    // encrypt in parallel if size exceeds threshold
+    #[cfg(feature = "std")]
+    if input.len() > parallel_threshold {
+        output.par_chunks_exact_mut(16).zip(input.par_chunks_exact(16)).for_each(crypt);
+        return Ok(output);
     }
+    // no thread pool without std
+    #[cfg(not(feature = "std"))]
+    let _ = parallel_threshold;
 
+    // encrypt serially
+    output.chunks_exact_mut(16).zip(input.chunks_exact(16)).for_each(crypt);
     Ok(output)
 }
 
-pub fn ecb_core_enc(plaintext: &[u8], round_keys: &[[u8; 16]]) -> Result<Vec<u8>> {
-    ecb_core(plaintext, round_keys, encrypt_block)
+pub fn ecb_core_enc(plaintext: &[u8], round_keys: &[[u8; 16]], parallel_threshold: usize) -> Result<Vec<u8>> {
+    ecb_core(plaintext, round_keys, parallel_threshold, encrypt_block)
+}
+
+/// Takes the equivalent inverse cipher's round keys (see `Cipher::inv_round_keys`).
+pub fn ecb_core_dec(ciphertext: &[u8], inv_round_keys: &[[u8; 16]], parallel_threshold: usize) -> Result<Vec<u8>> {
+    ecb_core(ciphertext, inv_round_keys, parallel_threshold, decrypt_block_eq)
+}
+
+/// Pads `plaintext` with the given scheme, then encrypts.
+pub fn ecb_core_enc_padded(
+    plaintext: &[u8],
+    round_keys: &[[u8; 16]],
+    parallel_threshold: usize,
+    padding: Padding,
+) -> Vec<u8> {
+    // safe unwrap, input is always padded
+    let mut padded = pad(plaintext, padding);
+    let ciphertext = ecb_core_enc(&padded, round_keys, parallel_threshold).unwrap();
+    zeroize(&mut padded);
+    ciphertext
 }
 
-pub fn ecb_core_dec(ciphertext: &[u8], round_keys: &[[u8; 16]]) -> Result<Vec<u8>> {
-    ecb_core(ciphertext, round_keys, decrypt_block)
+/// Decrypts, then removes and validates padding of the given scheme. Takes the equivalent inverse
+/// cipher's round keys.
+pub fn ecb_core_dec_padded(
+    ciphertext: &[u8],
+    inv_round_keys: &[[u8; 16]],
+    parallel_threshold: usize,
+    padding: Padding,
+) -> Result<Vec<u8>> {
+    let mut plaintext = ecb_core_dec(ciphertext, inv_round_keys, parallel_threshold)?;
+    unpad(&mut plaintext, padding)?;
+    Ok(plaintext)
 }
 
 #[cfg(test)]
 mod test_ecb {
     use super::*;
+    use crate::aesp::modes::util::PARALLEL_THRESHOLD;
     use crate::aesp::modes::util::test_util::{KEY_128, KEY_192, KEY_256, PLAINTEXT, hex_to_bytes};
     use crate::{Cipher, Key};
 
@@ -73,7 +103,7 @@ mod test_ecb {
         let cipher = Cipher::new(&key);
 
         // ECB core now assumes input is already 16-byte aligned and unpadded
-        let encrypted = ecb_core_enc(&PLAINTEXT, cipher.round_keys())?;
+        let encrypted = ecb_core_enc(&PLAINTEXT, cipher.round_keys(), PARALLEL_THRESHOLD)?;
 
         assert_eq!(
             expected, encrypted,
@@ -94,7 +124,7 @@ mod test_ecb {
 
         let key = Key::try_from_slice(&KEY_128)?;
         let cipher = Cipher::new(&key);
-        let decrypted = ecb_core_dec(&ciphertext, cipher.round_keys())?;
+        let decrypted = ecb_core_dec(&ciphertext, cipher.inv_round_keys(), PARALLEL_THRESHOLD)?;
 
         assert_eq!(
             PLAINTEXT.to_vec(),
@@ -117,7 +147,7 @@ mod test_ecb {
 
         let key = Key::try_from_slice(&KEY_192)?;
         let cipher = Cipher::new(&key);
-        let encrypted = ecb_core_enc(&PLAINTEXT, cipher.round_keys())?;
+        let encrypted = ecb_core_enc(&PLAINTEXT, cipher.round_keys(), PARALLEL_THRESHOLD)?;
 
         assert_eq!(
             expected, encrypted,
@@ -138,7 +168,7 @@ mod test_ecb {
 
         let key = Key::try_from_slice(&KEY_192)?;
         let cipher = Cipher::new(&key);
-        let decrypted = ecb_core_dec(&ciphertext, cipher.round_keys())?;
+        let decrypted = ecb_core_dec(&ciphertext, cipher.inv_round_keys(), PARALLEL_THRESHOLD)?;
 
         assert_eq!(
             PLAINTEXT.to_vec(),
@@ -161,7 +191,7 @@ mod test_ecb {
 
         let key = Key::try_from_slice(&KEY_256)?;
         let cipher = Cipher::new(&key);
-        let encrypted = ecb_core_enc(&PLAINTEXT, cipher.round_keys())?;
+        let encrypted = ecb_core_enc(&PLAINTEXT, cipher.round_keys(), PARALLEL_THRESHOLD)?;
 
         assert_eq!(
             expected, encrypted,
@@ -182,7 +212,7 @@ mod test_ecb {
 
         let key = Key::try_from_slice(&KEY_256)?;
         let cipher = Cipher::new(&key);
-        let decrypted = ecb_core_dec(&ciphertext, cipher.round_keys())?;
+        let decrypted = ecb_core_dec(&ciphertext, cipher.inv_round_keys(), PARALLEL_THRESHOLD)?;
 
         assert_eq!(
             PLAINTEXT.to_vec(),
@@ -1,6 +1,18 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::ops::Range;
+#[cfg(feature = "std")]
+use rayon::prelude::*;
+#[cfg(feature = "std")]
+use std::sync::OnceLock;
+
 use crate::aesp::core::encrypt_block;
 use crate::aesp::error::*;
+use crate::aesp::modes::ctr::ctr_core_in_place;
+#[cfg(target_arch = "x86_64")]
+use crate::aesp::modes::clmul::{self, ClmulKey};
 use crate::aesp::modes::util::{ctr_block, mul_x, mul_x4};
+use crate::aesp::util::{xor_into, zeroize};
 
 /*
 https://csrc.nist.rip/groups/ST/toolkit/BCM/documents/proposedmodes/gcm/gcm-spec.pdf
@@ -19,34 +31,174 @@ where blocks are:
 final tag = s ^ encrypt_block(J0, key)
 
 where J0 is:
-    - IV || 1u32 (initial ctr block for ctr = 1)
+    - IV || 1u32 (initial ctr block for ctr = 1) for 12-byte IVs
+    - GHASH(IV || zero padding || 0u64 || IV bit length as u64) for all other IV lengths
 */
 
-/// Function to compute GCM cryptographic tag from AAD + ciphertext
-pub fn compute_tag(
+/// Maximum plaintext (and ciphertext) length in bytes: 2^39 - 256 bits, i.e. 2^32 - 2 blocks, so that the
+/// 32-bit counter never repeats (NIST SP 800-38D section 5.2.1.1).
+pub(crate) const MAX_TEXT_LEN: u64 = (1 << 36) - 32;
+
+/// Maximum AAD length in bytes: 2^64 - 1 bits, rounded down to whole bytes.
+pub(crate) const MAX_AAD_LEN: u64 = u64::MAX / 8;
+
+/// Number of blocks (4 KiB) hashed by each task of a parallel GHASH.
+#[cfg(feature = "std")]
+const GHASH_CHUNK_BLOCKS: usize = 256;
+
+/// Validates plaintext (or ciphertext) and AAD lengths against the GCM limits.
+/// Returns [InputTooLong](crate::Error::InputTooLong) if either is exceeded.
+pub(crate) fn check_lengths(text_len: usize, aad_len: usize) -> Result<()> {
+    for (len, max) in [(text_len, MAX_TEXT_LEN), (aad_len, MAX_AAD_LEN)] {
+        if len as u64 > max {
+            return Err(Error::InputTooLong {
+                len,
+                max: max.try_into().unwrap_or(usize::MAX),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// The parts of [encrypt_gcm](crate::Cipher::encrypt_gcm) output, as returned by [parse_gcm].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GcmOutput {
+    pub iv: [u8; 12],
+    /// AAD length in bytes, as stored in the 8-byte length field.
+    pub aad_len: u64,
+    pub aad: Vec<u8>,
+    pub ciphertext: Vec<u8>,
+    pub tag: [u8; 16],
+}
+
+/// Byte ranges of each part of [encrypt_gcm](crate::Cipher::encrypt_gcm) output, as returned by
+/// [encrypt_gcm_framed](crate::Cipher::encrypt_gcm_framed). Index the output with a range to borrow that part.
+/// The 8-byte AAD length field lies between `iv_range` and `aad_range`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GcmFrame {
+    pub iv_range: Range<usize>,
+    pub aad_range: Range<usize>,
+    pub ct_range: Range<usize>,
+    pub tag_range: Range<usize>,
+}
+
+/// Splits [encrypt_gcm](crate::Cipher::encrypt_gcm) output into its parts without decrypting or checking
+/// the tag:
+/// `IV (12 bytes) || AAD length (8 bytes) || AAD || Ciphertext || Tag (16 bytes)`
+///
+/// Returns an [InvalidCiphertext](crate::Error::InvalidCiphertext) error if the blob is too short for this
+/// layout, or its AAD length field exceeds the bytes available.
+///
+/// ## Examples
+/// ```
+/// # fn main() -> aesp::Result<()> {
+/// # use aesp::{Key, Cipher, parse_gcm};
+/// # let cipher = Cipher::new(&Key::rand_key_256()?);
+/// let ciphertext = cipher.encrypt_gcm(b"Hello, World!", Some(b"header"))?;
+///
+/// let parts = parse_gcm(&ciphertext)?;
+/// assert_eq!(parts.aad, b"header");
+/// assert_eq!(parts.ciphertext.len(), 13);
+/// assert_eq!(&ciphertext[ciphertext.len() - 16..], &parts.tag);
+/// # Ok(())
+/// # }
+/// ```
+pub fn parse_gcm(blob: &[u8]) -> Result<GcmOutput> {
+    // minimum size is 12 (iv) + 8 (aad_len) + 16 (tag)
+    if blob.len() < 36 {
+        return Err(Error::InvalidCiphertext {
+            len: blob.len(),
+            context: "insufficient bytes for valid GCM",
+        });
+    }
+
+    let (iv, rest) = blob.split_first_chunk::<12>().unwrap(); // length checked above
+    let (aad_len, rest) = rest.split_first_chunk::<8>().unwrap();
+    let aad_len = u64::from_be_bytes(*aad_len);
+    // compare in u64, aad_len is untrusted and may not fit in usize
+    if aad_len > (rest.len() - 16) as u64 {
+        return Err(Error::InvalidCiphertext {
+            len: blob.len(),
+            context: "insufficient bytes given aad_len",
+        });
+    }
+
+    let (aad, rest) = rest.split_at(aad_len as usize);
+    let (ciphertext, tag) = rest.split_last_chunk::<16>().unwrap();
+    Ok(GcmOutput {
+        iv: *iv,
+        aad_len,
+        aad: aad.to_vec(),
+        ciphertext: ciphertext.to_vec(),
+        tag: *tag,
+    })
+}
+
+/// Derive the pre-counter block J0 from an IV of any length.
+/// Takes the fast path for 12-byte IVs, otherwise GHASHes the IV with the cipher's GHASH key (see
+/// `Cipher::ghash_key`) as per NIST SP 800-38D.
+pub fn derive_j0(gkey: &GHashKey, iv: &[u8]) -> [u8; 16] {
+    if let Ok(iv) = iv.try_into() {
+        return ctr_block(iv, 1);
+    }
+
+    // J0 = GHASH(IV || zero padding || 0u64 || IV bit length as u64)
+    let s = gkey.ghash([0u8; 16], iv);
+    let mut len = [0u8; 16];
+    len[8..].copy_from_slice(&((iv.len() as u64) * 8).to_be_bytes());
+    gkey.ghash(s, &len)
+}
+
+/// GCTR keystream starting from inc32(J0). The 32-bit counter wraps modulo 2^32 as per the spec,
+/// which can only occur when J0 was derived from a non-12-byte IV.
+pub fn gctr(input: &[u8], round_keys: &[[u8; 16]], j0: &[u8; 16], parallel_threshold: usize) -> Result<Vec<u8>> {
+    let mut output = input.to_vec();
+    gctr_in_place(&mut output, round_keys, j0, parallel_threshold)?;
+    Ok(output)
+}
+
+/// [gctr], operating in place.
+pub fn gctr_in_place(buf: &mut [u8], round_keys: &[[u8; 16]], j0: &[u8; 16], parallel_threshold: usize) -> Result<()> {
+    let mut iv = [0u8; 12];
+    iv.copy_from_slice(&j0[..12]);
+    let ctr_start = u32::from_be_bytes([j0[12], j0[13], j0[14], j0[15]]).wrapping_add(1);
+
+    // number of bytes that can be processed before the counter wraps
+    let before_wrap = (u32::MAX - ctr_start) as usize + 1;
+    match before_wrap.checked_mul(16) {
+        Some(split) if split < buf.len() => {
+            let (head, tail) = buf.split_at_mut(split);
+            ctr_core_in_place(head, round_keys, &iv, ctr_start, parallel_threshold)?;
+            ctr_core_in_place(tail, round_keys, &iv, 0, parallel_threshold)
+        }
+        _ => ctr_core_in_place(buf, round_keys, &iv, ctr_start, parallel_threshold),
+    }
+}
+
+/// Function to compute GCM cryptographic tag from AAD + ciphertext, given the pre-counter block J0.
+/// Uses the cipher's precomputed GHASH key (see `Cipher::ghash_key`).
+/// AAD or ciphertext longer than `parallel_threshold` bytes is hashed in parallel.
+pub fn compute_tag_j0(
     ciphertext: &[u8],
     round_keys: &[[u8; 16]],
-    iv: &[u8; 12],
+    j0: &[u8; 16],
     aad: &[u8],
+    gkey: &GHashKey,
+    parallel_threshold: usize,
 ) -> Result<[u8; 16]> {
-    // create initial ctr block (xor'd with tag at end)
-    let j0 = ctr_block(iv, 1);
-    let j0_e = encrypt_block(&j0, round_keys);
-
-    // generate H by encrypting block of 0s
-    let h = encrypt_block(&[0u8; 16], round_keys);
+    check_lengths(ciphertext.len(), aad.len())?;
 
-    // precompute GHASH tables for H
-    let gkey = GHashKey::new(h);
+    // encrypt initial ctr block (xor'd with tag at end)
+    let mut j0_e = encrypt_block(j0, round_keys);
 
     // s = ghash accumulator
     let mut s = [0u8; 16];
 
     // compute s over AAD (zero-pads final partial block)
-    s = gkey.ghash(s, aad);
+    s = gkey.ghash_par(s, aad, parallel_threshold);
 
     // compute s over ciphertext (zero-pads final partial block)
-    s = gkey.ghash(s, ciphertext);
+    s = gkey.ghash_par(s, ciphertext, parallel_threshold);
 
     // authenticate message length, build aad_size || ct_size
     let aad_size = (aad.len() as u64) * 8; // size in bits
@@ -56,26 +208,306 @@ pub fn compute_tag(
     len[8..].copy_from_slice(&ct_size.to_be_bytes());
 
     // s = (s + len) * H
-    for i in 0..16 {
-        s[i] ^= len[i];
-    }
-    s = gkey.mul_h(s);
+    s = gkey.ghash(s, &len);
 
     // tag = E(K, J0) + S
     for i in 0..16 {
         s[i] ^= j0_e[i];
     }
+    // E(K, J0) is keystream, and together with the tag reveals the unmasked GHASH output
+    zeroize(&mut j0_e);
 
     Ok(s)
 }
 
+/// GHASH over AAD and ciphertext, recording the accumulator after every block (each zero-padded AAD block,
+/// each zero-padded ciphertext block, then the length block). The masked tag `S ^ E(K, J0)` is appended last.
+#[cfg(feature = "debug-ghash")]
+pub(crate) fn ghash_trace(
+    gkey: &GHashKey,
+    round_keys: &[[u8; 16]],
+    j0: &[u8; 16],
+    aad: &[u8],
+    ciphertext: &[u8],
+) -> Vec<[u8; 16]> {
+    let mut trace = Vec::with_capacity(aad.len().div_ceil(16) + ciphertext.len().div_ceil(16) + 2);
+    let mut s = [0u8; 16];
+    for block in aad.chunks(16).chain(ciphertext.chunks(16)) {
+        s = gkey.ghash(s, block);
+        trace.push(s);
+    }
+
+    let mut len = [0u8; 16];
+    len[..8].copy_from_slice(&((aad.len() as u64) * 8).to_be_bytes());
+    len[8..].copy_from_slice(&((ciphertext.len() as u64) * 8).to_be_bytes());
+    s = gkey.ghash(s, &len);
+    trace.push(s);
+
+    let mut j0_e = encrypt_block(j0, round_keys);
+    let mut tag = s;
+    xor_into(&mut tag, &j0_e);
+    trace.push(tag);
+    zeroize(&mut j0_e);
+    zeroize(&mut s);
+    trace
+}
+
+/// Incremental GHASH over AAD followed by ciphertext. Buffers partial blocks between calls, so input may be
+/// split at any offset. All AAD must be absorbed before any ciphertext.
+pub struct GHashState<'a> {
+    key: &'a GHashKey,
+    s: [u8; 16],
+    buf: [u8; 16],
+    buf_len: usize,
+    aad_len: u64,
+    ct_len: u64,
+}
+
+impl<'a> GHashState<'a> {
+    /// Instantiate an empty accumulator using a precomputed GHASH key.
+    pub(crate) fn new(key: &'a GHashKey) -> Self {
+        Self {
+            key,
+            s: [0u8; 16],
+            buf: [0u8; 16],
+            buf_len: 0,
+            aad_len: 0,
+            ct_len: 0,
+        }
+    }
+
+    /// Absorb AAD. Must not be called after [ciphertext](GHashState::ciphertext).
+    pub fn aad(&mut self, data: &[u8]) {
+        debug_assert_eq!(self.ct_len, 0, "AAD absorbed after ciphertext");
+        self.absorb(data);
+        self.aad_len += data.len() as u64;
+    }
+
+    /// Absorb ciphertext. The first call zero-pads the final partial AAD block.
+    pub fn ciphertext(&mut self, data: &[u8]) {
+        if self.ct_len == 0 {
+            self.flush();
+        }
+        self.absorb(data);
+        self.ct_len += data.len() as u64;
+    }
+
+    /// Finish the hash with the length block and mask with E(K, J0) to produce the tag.
+    pub fn tag(mut self, round_keys: &[[u8; 16]], j0: &[u8; 16]) -> [u8; 16] {
+        self.flush();
+
+        // authenticate message length, build aad_size || ct_size (in bits)
+        let mut len = [0u8; 16];
+        len[..8].copy_from_slice(&(self.aad_len * 8).to_be_bytes());
+        len[8..].copy_from_slice(&(self.ct_len * 8).to_be_bytes());
+        let mut tag = self.key.ghash(self.s, &len);
+
+        // tag = E(K, J0) + S
+        let mut j0_e = encrypt_block(j0, round_keys);
+        xor_into(&mut tag, &j0_e);
+        // E(K, J0) is keystream, and together with the tag reveals the unmasked GHASH output
+        zeroize(&mut j0_e);
+        zeroize(&mut self.s);
+        tag
+    }
+
+    /// Hash whole blocks, keeping any trailing partial block in the buffer.
+    fn absorb(&mut self, mut data: &[u8]) {
+        // top up a buffered partial block first
+        if self.buf_len > 0 {
+            let take = (16 - self.buf_len).min(data.len());
+            self.buf[self.buf_len..self.buf_len + take].copy_from_slice(&data[..take]);
+            self.buf_len += take;
+            data = &data[take..];
+            if self.buf_len < 16 {
+                return;
+            }
+            self.s = self.key.ghash(self.s, &self.buf);
+            self.buf_len = 0;
+        }
+
+        let whole = data.len() / 16 * 16;
+        self.s = self.key.ghash(self.s, &data[..whole]);
+
+        let rest = &data[whole..];
+        self.buf[..rest.len()].copy_from_slice(rest);
+        self.buf_len = rest.len();
+    }
+
+    /// Hash any buffered partial block, zero-padded.
+    fn flush(&mut self) {
+        if self.buf_len > 0 {
+            self.s = self.key.ghash(self.s, &self.buf[..self.buf_len]);
+            self.buf_len = 0;
+        }
+    }
+}
+
+/// GHASH key for H. The multiplication backend is selected once, when the key is constructed.
+#[derive(Clone)]
+pub(crate) struct GHashKey {
+    /// Multiplication by H.
+    h: GHashMul,
+    /// Multiplication by H^GHASH_CHUNK_BLOCKS, which combines the chunks hashed by
+    /// [ghash_par](GHashKey::ghash_par). Computed on first parallel use and kept with the key, so keys that
+    /// only ever hash serially (POLYVAL's, or short messages) never pay for it.
+    #[cfg(feature = "std")]
+    h_chunk: OnceLock<GHashMul>,
+}
+
+impl GHashKey {
+    /// Build a GHASH key for H, using carryless multiply if the CPU supports it.
+    pub(crate) fn new(h: [u8; 16]) -> Self {
+        Self::with_mul(GHashMul::new(h))
+    }
+
+    /// Build a GHASH key for the cipher's H (a block of 0s encrypted with the key).
+    pub(crate) fn for_round_keys(round_keys: &[[u8; 16]]) -> Self {
+        Self::new(encrypt_block(&[0u8; 16], round_keys))
+    }
+
+    /// Build a GHASH key for H using the portable table backend.
+    #[cfg(test)]
+    fn table(h: [u8; 16]) -> Self {
+        Self::with_mul(GHashMul::table(h))
+    }
+
+    fn with_mul(h: GHashMul) -> Self {
+        Self {
+            h,
+            #[cfg(feature = "std")]
+            h_chunk: OnceLock::new(),
+        }
+    }
+
+    /// Wipe H and anything derived from it. Knowledge of H allows GCM tags to be forged.
+    pub(crate) fn wipe(&mut self) {
+        self.h.wipe();
+        #[cfg(feature = "std")]
+        if let Some(h_chunk) = self.h_chunk.get_mut() {
+            h_chunk.wipe();
+        }
+    }
+
+    /// Same result as [ghash](GHashKey::ghash), but splits data longer than `parallel_threshold` bytes across
+    /// the rayon thread pool.
+    ///
+    /// GHASH is linear: hashing m blocks from s gives `s * H^m ^ ghash(0, blocks)`. So each chunk is hashed
+    /// from zero in parallel, and the partial results are combined in order as `acc = acc * H^m ^ partial`.
+    pub(crate) fn ghash_par(&self, s: [u8; 16], data: &[u8], parallel_threshold: usize) -> [u8; 16] {
+        #[cfg(feature = "std")]
+        if data.len() > parallel_threshold {
+            let chunk_len = GHASH_CHUNK_BLOCKS * 16;
+            let partials: Vec<[u8; 16]> = data.par_chunks(chunk_len).map(|c| self.ghash([0u8; 16], c)).collect();
+
+            // every chunk is full apart from the last, which may be shorter (and end in a partial block),
+            // so only its power of H needs to be computed here
+            let last_blocks = (data.len() - (partials.len() - 1) * chunk_len).div_ceil(16);
+            let h_last = (last_blocks != GHASH_CHUNK_BLOCKS).then(|| self.h.power(last_blocks));
+            let h_chunk = self.h_chunk.get_or_init(|| self.h.power(GHASH_CHUNK_BLOCKS));
+
+            let mut acc = s;
+            for (i, partial) in partials.iter().enumerate() {
+                let h = match &h_last {
+                    Some(h_last) if i + 1 == partials.len() => h_last,
+                    _ => h_chunk,
+                };
+                acc = h.ghash(acc, &[0u8; 16]);
+                xor_into(&mut acc, partial);
+            }
+            return acc;
+        }
+        // no thread pool without std
+        #[cfg(not(feature = "std"))]
+        let _ = parallel_threshold;
+
+        self.ghash(s, data)
+    }
+
+    /// For each 16-byte block in data:   s = (s ^ data[i]) * H
+    #[inline(always)]
+    pub(crate) fn ghash(&self, s: [u8; 16], data: &[u8]) -> [u8; 16] {
+        self.h.ghash(s, data)
+    }
+}
+
+/// Multiplication by a fixed field element (H, or a power of it) in one of the GHASH backends.
+#[derive(Clone)]
+enum GHashMul {
+    /// Precomputed nibble tables (portable fallback).
+    Table(Box<GHashTable>),
+    /// Hardware carryless multiply.
+    #[cfg(target_arch = "x86_64")]
+    Clmul(ClmulKey),
+}
+
+impl GHashMul {
+    /// Multiplication by `h`, using carryless multiply if the CPU supports it.
+    fn new(h: [u8; 16]) -> Self {
+        #[cfg(target_arch = "x86_64")]
+        if clmul::available() {
+            return Self::Clmul(ClmulKey::new(h));
+        }
+        Self::table(h)
+    }
+
+    /// Multiplication by `h` using the portable table backend.
+    fn table(h: [u8; 16]) -> Self {
+        Self::Table(Box::new(GHashTable::new(h)))
+    }
+
+    fn wipe(&mut self) {
+        match self {
+            Self::Table(table) => table.wipe(),
+            #[cfg(target_arch = "x86_64")]
+            Self::Clmul(key) => key.wipe(),
+        }
+    }
+
+    /// Multiplication by the k-th power of this element, in the same backend.
+    #[cfg(feature = "std")]
+    fn power(&self, k: usize) -> Self {
+        // 0x80 in the first byte is 1 in GCM's reflected bit order, so each step below is x = x * H
+        let mut x = [0u8; 16];
+        x[0] = 0x80;
+        for _ in 0..k {
+            x = self.ghash(x, &[0u8; 16]);
+        }
+        let mul = match self {
+            Self::Table(_) => Self::table(x),
+            #[cfg(target_arch = "x86_64")]
+            Self::Clmul(_) => Self::Clmul(ClmulKey::new(x)),
+        };
+        zeroize(&mut x);
+        mul
+    }
+
+    /// For each 16-byte block in data:   s = (s ^ data[i]) * H
+    #[inline(always)]
+    fn ghash(&self, s: [u8; 16], data: &[u8]) -> [u8; 16] {
+        match self {
+            Self::Table(table) => table.ghash(s, data),
+            // SAFETY: Clmul is only constructed after checking CPU support
+            #[cfg(target_arch = "x86_64")]
+            Self::Clmul(key) => unsafe { key.ghash(s, data) },
+        }
+    }
+}
+
+impl Drop for GHashMul {
+    // covers every GHashKey (and chunk power) that is dropped without an explicit wipe
+    fn drop(&mut self) {
+        self.wipe();
+    }
+}
 
 /// Precompute tables for mul by H. Struct written with LLM assistance.
-struct GHashKey {
+#[derive(Clone)]
+pub(crate) struct GHashTable {
     table: [[u128; 16]; 32],
 }
 
-impl GHashKey {
+impl GHashTable {
     /// Build the precomputed nibble tables for this H
     fn new(h: [u8; 16]) -> Self {
         let mut table = [[0u128; 16]; 32];
@@ -84,7 +516,7 @@ impl GHashKey {
         // (i.e., after shifting for all earlier bits)
         let mut v_pos = u128::from_be_bytes(h);
 
-        for pos in 0..32 {
+        for pos_table in table.iter_mut() {
             for nib in 0..16u8 {
                 let mut acc = 0u128;
                 let mut v = v_pos;
@@ -96,7 +528,7 @@ impl GHashKey {
                     v = mul_x(v);
                 }
 
-                table[pos][nib as usize] = acc;
+                pos_table[nib as usize] = acc;
             }
 
             // Advance v_pos by 4 bits for next nibble position
@@ -106,6 +538,15 @@ impl GHashKey {
         Self { table }
     }
 
+    /// Zero the tables, which are all multiples of H.
+    fn wipe(&mut self) {
+        for v in self.table.as_flattened_mut() {
+            // SAFETY: v is a valid, aligned &mut u128
+            unsafe { core::ptr::write_volatile(v, 0) };
+        }
+        core::sync::atomic::compiler_fence(core::sync::atomic::Ordering::SeqCst);
+    }
+
     /// For each 16-byte block in data:   s = (s ^ data[i]) * H
     #[inline(always)]
     fn ghash(&self, mut s: [u8; 16], data: &[u8]) -> [u8; 16] {
@@ -144,9 +585,318 @@ mod test_gcm {
     use crate::{Cipher, Key};
     use crate::aesp::modes::util::test_util::{hex_to_arr_12, hex_to_arr_16, hex_to_bytes};
 
+    /// Computes the tag with every GHASH backend available on this CPU, and in parallel, asserting they agree.
+    fn tag_all_backends(ct: &[u8], round_keys: &[[u8; 16]], j0: &[u8; 16], aad: &[u8]) -> [u8; 16] {
+        let h = encrypt_block(&[0u8; 16], round_keys);
+        let selected_key = GHashKey::new(h);
+        let table = compute_tag_j0(ct, round_keys, j0, aad, &GHashKey::table(h), usize::MAX).unwrap();
+        let selected = compute_tag_j0(ct, round_keys, j0, aad, &selected_key, usize::MAX).unwrap();
+        assert_eq!(table, selected, "GHASH backends disagree");
+        let parallel = compute_tag_j0(ct, round_keys, j0, aad, &selected_key, 0).unwrap();
+        assert_eq!(parallel, selected, "parallel GHASH disagrees");
+        selected
+    }
+
+    #[test]
+    #[cfg(target_arch = "x86_64")]
+    fn clmul_matches_table() {
+        if !clmul::available() {
+            return;
+        }
+        // random-looking H and data, including a partial final block
+        let h = encrypt_block(&[0x42; 16], &[[0x13; 16]; 11]);
+        let data: Vec<u8> = (0..1000u32).map(|i| (i * 31 + 7) as u8).collect();
+        let table = GHashKey::table(h);
+        let clmul = GHashKey::new(h);
+        assert!(matches!(clmul.h, GHashMul::Clmul(_)));
+        for len in [0, 1, 15, 16, 17, 100, 1000] {
+            assert_eq!(table.ghash([0x5a; 16], &data[..len]), clmul.ghash([0x5a; 16], &data[..len]));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn parallel_ghash_matches_serial() {
+        let h = encrypt_block(&[0x42; 16], &[[0x13; 16]; 11]);
+        let data: Vec<u8> = (0..20_000u32).map(|i| (i * 31 + 7) as u8).collect();
+        let chunk = GHASH_CHUNK_BLOCKS * 16;
+
+        for gkey in [GHashKey::table(h), GHashKey::new(h)] {
+            // the chunk power is only computed once parallel hashing needs it
+            assert!(gkey.h_chunk.get().is_none());
+
+            // around the chunk size, with full and partial final chunks and blocks
+            for len in [0, 1, 16, chunk - 1, chunk, chunk + 1, 2 * chunk, 2 * chunk + 15, data.len()] {
+                let serial = gkey.ghash([0x5a; 16], &data[..len]);
+                assert_eq!(gkey.ghash_par([0x5a; 16], &data[..len], 0), serial, "length {len}");
+            }
+
+            // and is then cached on the key
+            let cached = gkey.h_chunk.get().expect("computed by ghash_par");
+            let x = [0x5a; 16];
+            assert_eq!(cached.ghash(x, &[0u8; 16]), gkey.h.power(GHASH_CHUNK_BLOCKS).ghash(x, &[0u8; 16]));
+        }
+    }
+
+    #[test]
+    fn parallel_gcm_matches_serial() -> Result<()> {
+        // large enough to split both the keystream and GHASH into several tasks
+        let pt: Vec<u8> = (0..100_003u32).map(|i| (i * 7) as u8).collect();
+        let aad: Vec<u8> = (0..9_000u32).map(|i| i as u8).collect();
+        let key = Key::rand_key_256()?;
+        let iv = [0x24; 12];
+
+        let serial = Cipher::new(&key).with_parallel_threshold(usize::MAX);
+        let parallel = Cipher::new(&key).with_parallel_threshold(0);
+        let out = parallel.encrypt_gcm_with_iv(&pt, Some(&aad), &iv)?;
+        assert_eq!(out, serial.encrypt_gcm_with_iv(&pt, Some(&aad), &iv)?);
+        assert_eq!(serial.decrypt_gcm(&out)?.0, pt);
+        assert_eq!(parallel.decrypt_gcm(&out)?.0, pt);
+        Ok(())
+    }
+
     // all test vectors from
     // https://boringssl.googlesource.com/boringssl.git/%2B/734fca08902889c88e84839134262bdf5c12eebf/crypto/cipher/cipher_test.txt
 
+    #[test]
+    fn iv_8_bytes() -> Result<()> {
+        // NIST GCM spec test case 5: 8-byte IV
+        let key = Key::try_from_slice(&hex_to_bytes("feffe9928665731c6d6a8f9467308308"))?;
+        let cipher = Cipher::new(&key);
+        let iv = hex_to_bytes("cafebabefacedbad");
+        let plaintext = hex_to_bytes(
+            "d9313225f88406e5a55909c5aff5269a\
+             86a7a9531534f7da2e4c303d8a318a72\
+             1c3c0c95956809532fcf0e2449a6b525\
+             b16aedf5aa0de657ba637b39",
+        );
+        let aad = hex_to_bytes("feedfacedeadbeeffeedfacedeadbeefabaddad2");
+        let ciphertext = hex_to_bytes(
+            "61353b4c2806934a777ff51fa22a4755\
+             699b2a714fcdc6f83766e5f97b6c7423\
+             73806900e49f24b22b097544d4896b42\
+             4989b5e1ebac0f07c23f4598",
+        );
+        let tag = hex_to_bytes("3612d2e79e3b0785561be14aaca2fccb");
+
+        let out = cipher.encrypt_gcm_with_iv_slice(&plaintext, Some(&aad), &iv)?;
+        let ct_start = 8 + 8 + aad.len();
+        assert_eq!(out[..8], iv);
+        assert_eq!(out[ct_start..out.len() - 16], ciphertext);
+        assert_eq!(out[out.len() - 16..], tag);
+
+        let (decrypted, returned_aad) = cipher.decrypt_gcm_with_iv_len(&out, 8)?;
+        assert_eq!(decrypted, plaintext);
+        assert_eq!(returned_aad, Some(aad));
+        Ok(())
+    }
+
+    #[test]
+    fn iv_60_bytes() -> Result<()> {
+        // NIST GCM spec test case 6: 60-byte IV
+        let key = Key::try_from_slice(&hex_to_bytes("feffe9928665731c6d6a8f9467308308"))?;
+        let cipher = Cipher::new(&key);
+        let iv = hex_to_bytes(
+            "9313225df88406e555909c5aff5269aa\
+             6a7a9538534f7da1e4c303d2a318a728\
+             c3c0c95156809539fcf0e2429a6b5254\
+             16aedbf5a0de6a57a637b39b",
+        );
+        let plaintext = hex_to_bytes(
+            "d9313225f88406e5a55909c5aff5269a\
+             86a7a9531534f7da2e4c303d8a318a72\
+             1c3c0c95956809532fcf0e2449a6b525\
+             b16aedf5aa0de657ba637b39",
+        );
+        let aad = hex_to_bytes("feedfacedeadbeeffeedfacedeadbeefabaddad2");
+        let ciphertext = hex_to_bytes(
+            "8ce24998625615b603a033aca13fb894\
+             be9112a5c3a211a8ba262a3cca7e2ca7\
+             01e4a9a4fba43c90ccdcb281d48c7c6f\
+             d62875d2aca417034c34aee5",
+        );
+        let tag = hex_to_bytes("619cc5aefffe0bfa462af43c1699d050");
+
+        let out = cipher.encrypt_gcm_with_iv_slice(&plaintext, Some(&aad), &iv)?;
+        let ct_start = 60 + 8 + aad.len();
+        assert_eq!(out[ct_start..out.len() - 16], ciphertext);
+        assert_eq!(out[out.len() - 16..], tag);
+
+        let (decrypted, _) = cipher.decrypt_gcm_with_iv_len(&out, 60)?;
+        assert_eq!(decrypted, plaintext);
+
+        // wrong IV length fails authentication
+        assert!(cipher.decrypt_gcm_with_iv_len(&out, 12).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn iv_12_bytes_fast_path() -> Result<()> {
+        // slice path with a 12-byte IV is identical to the fixed-size path
+        let cipher = Cipher::new(&Key::rand_key_128()?);
+        let iv = [0x42u8; 12];
+        let a = cipher.encrypt_gcm_with_iv(b"Hello, World!", Some(b"aad"), &iv)?;
+        let b = cipher.encrypt_gcm_with_iv_slice(b"Hello, World!", Some(b"aad"), &iv)?;
+        assert_eq!(a, b);
+        Ok(())
+    }
+
+    #[test]
+    fn gmac() -> Result<()> {
+        // GCM with empty plaintext, checked against an independent implementation
+        let key = Key::try_from_slice(&hex_to_bytes("feffe9928665731c6d6a8f9467308308"))?;
+        let cipher = Cipher::new(&key);
+        let iv = hex_to_arr_12("cafebabefacedbaddecaf888");
+        let aad = hex_to_bytes("feedfacedeadbeeffeedfacedeadbeefabaddad2");
+        let expected = hex_to_arr_16("346434fd51d5cd0c5887ec63e39b907a");
+
+        let tag = cipher.gmac(&aad, &iv)?;
+        assert_eq!(tag, expected);
+        cipher.verify_gmac(&aad, &iv, &tag)?;
+
+        // any flipped byte in the tag, AAD, or IV fails
+        let mut bad_tag = tag;
+        bad_tag[15] ^= 0x80;
+        assert!(matches!(cipher.verify_gmac(&aad, &iv, &bad_tag), Err(Error::AuthFailed)));
+        let mut bad_aad = aad.clone();
+        bad_aad[0] ^= 1;
+        assert!(matches!(cipher.verify_gmac(&bad_aad, &iv, &tag), Err(Error::AuthFailed)));
+        let mut bad_iv = iv;
+        bad_iv[11] ^= 1;
+        assert!(matches!(cipher.verify_gmac(&aad, &bad_iv, &tag), Err(Error::AuthFailed)));
+        Ok(())
+    }
+
+    #[test]
+    fn gcm_tag_matches_embedded_tag() -> Result<()> {
+        // NIST GCM spec test case 4
+        let cipher = Cipher::new(&Key::try_from_slice(&hex_to_bytes("feffe9928665731c6d6a8f9467308308"))?);
+        let iv = hex_to_arr_12("cafebabefacedbaddecaf888");
+        let aad = hex_to_bytes("feedfacedeadbeeffeedfacedeadbeefabaddad2");
+        let ct = hex_to_bytes(
+            "42831ec2217774244b7221b784d0d49c\
+             e3aa212f2c02a4e035c17e2329aca12e\
+             21d514b25466931c7d8f6a5aac84aa05\
+             1ba30b396a0aac973d58e091",
+        );
+        assert_eq!(cipher.gcm_tag(&ct, &aad, &iv)?, hex_to_arr_16("5bc94fbc3221a5db94fae95ae7121a47"));
+
+        // the tag embedded by encryption, with and without AAD, across partial and whole blocks
+        for len in [0, 1, 15, 16, 17, 200] {
+            let plaintext = vec![0xa5; len];
+            for aad in [None, Some(&b"header"[..])] {
+                let parts = parse_gcm(&cipher.encrypt_gcm_with_iv(&plaintext, aad, &iv)?)?;
+                assert_eq!(cipher.gcm_tag(&parts.ciphertext, &parts.aad, &iv)?, parts.tag);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "debug-ghash")]
+    fn ghash_trace_ends_in_tag() -> Result<()> {
+        use crate::xor_into;
+
+        // NIST GCM spec test case 4: 20 bytes of AAD and 60 bytes of ciphertext
+        let cipher = Cipher::new(&Key::try_from_slice(&hex_to_bytes("feffe9928665731c6d6a8f9467308308"))?);
+        let iv = hex_to_arr_12("cafebabefacedbaddecaf888");
+        let aad = hex_to_bytes("feedfacedeadbeeffeedfacedeadbeefabaddad2");
+        let ct = hex_to_bytes(
+            "42831ec2217774244b7221b784d0d49c\
+             e3aa212f2c02a4e035c17e2329aca12e\
+             21d514b25466931c7d8f6a5aac84aa05\
+             1ba30b396a0aac973d58e091",
+        );
+
+        let trace = cipher.ghash_trace(&aad, &ct, &iv);
+        // 2 AAD blocks + 4 ciphertext blocks + length block + tag
+        assert_eq!(trace.len(), 8);
+
+        let j0 = derive_j0(cipher.ghash_key(), &iv);
+        let tag = compute_tag_j0(&ct, cipher.round_keys(), &j0, &aad, cipher.ghash_key(), usize::MAX)?;
+        let mut s = trace[trace.len() - 2];
+        xor_into(&mut s, &encrypt_block(&j0, cipher.round_keys()));
+        assert_eq!(s, tag);
+        assert_eq!(trace[trace.len() - 1], tag);
+        assert_eq!(tag, hex_to_arr_16("5bc94fbc3221a5db94fae95ae7121a47"));
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn length_limits() {
+        let max_text = MAX_TEXT_LEN as usize;
+        let max_aad = MAX_AAD_LEN as usize;
+
+        // 2^32 - 2 blocks of 16 bytes, and 2^64 - 1 bits rounded down to bytes
+        assert_eq!(max_text, ((1 << 32) - 2) * 16);
+        assert_eq!(max_aad as u128 * 8, u64::MAX as u128 - 7);
+
+        assert!(check_lengths(max_text, max_aad).is_ok());
+        assert!(matches!(
+            check_lengths(max_text + 1, 0),
+            Err(Error::InputTooLong { len, max }) if len == max_text + 1 && max == max_text
+        ));
+        assert!(matches!(
+            check_lengths(0, max_aad + 1),
+            Err(Error::InputTooLong { len, max }) if len == max_aad + 1 && max == max_aad
+        ));
+    }
+
+    #[test]
+    fn gctr_counter_wraps() -> Result<()> {
+        // a J0 counter of u32::MAX - 1 wraps to 0 on the third block
+        let cipher = Cipher::new(&Key::rand_key_128()?);
+        let mut j0 = [0x11u8; 16];
+        j0[12..].copy_from_slice(&(u32::MAX - 1).to_be_bytes());
+        let input = [0u8; 48];
+
+        let keystream = gctr(&input, cipher.round_keys(), &j0, cipher.parallel_threshold())?;
+        let iv: [u8; 12] = j0[..12].try_into().unwrap();
+        assert_eq!(keystream[..16], encrypt_block(&ctr_block(&iv, u32::MAX), cipher.round_keys()));
+        assert_eq!(keystream[16..32], encrypt_block(&ctr_block(&iv, 0), cipher.round_keys()));
+        assert_eq!(keystream[32..], encrypt_block(&ctr_block(&iv, 1), cipher.round_keys()));
+        Ok(())
+    }
+
+    #[test]
+    fn parse_gcm_splits_framing() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_128()?);
+        let iv = [0x24u8; 12];
+        let blob = cipher.encrypt_gcm_with_iv(b"plaintext", Some(b"aad"), &iv)?;
+
+        let parts = parse_gcm(&blob)?;
+        assert_eq!(parts.iv, iv);
+        assert_eq!(parts.aad_len, 3);
+        assert_eq!(parts.aad, b"aad");
+        assert_eq!(parts.ciphertext, blob[23..32]);
+        assert_eq!(parts.tag, blob[32..]);
+
+        // no AAD, no plaintext: just the framing
+        let parts = parse_gcm(&cipher.encrypt_gcm_with_iv(b"", None, &iv)?)?;
+        assert!(parts.aad.is_empty() && parts.ciphertext.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn parse_gcm_rejects_malformed() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_128()?);
+        let blob = cipher.encrypt_gcm_with_iv(b"plaintext", Some(b"aad"), &[0u8; 12])?;
+
+        // truncated below the minimum, and into the AAD
+        for len in [0, 12, 35, 36 + 2] {
+            assert!(matches!(parse_gcm(&blob[..len]), Err(Error::InvalidCiphertext { .. })), "{len}");
+        }
+
+        // AAD length field larger than the blob, including one that does not fit in usize
+        for aad_len in [13, u64::MAX] {
+            let mut bad = blob.clone();
+            bad[12..20].copy_from_slice(&aad_len.to_be_bytes());
+            assert!(matches!(parse_gcm(&bad), Err(Error::InvalidCiphertext { .. })), "{aad_len}");
+        }
+        Ok(())
+    }
+
     #[test]
     fn tag_no_pt_no_aad() -> Result<()> {
         // Vector:
@@ -162,7 +912,8 @@ mod test_gcm {
 
         let key = Key::try_from_slice(&key)?;
         let cipher = Cipher::new(&key);
-        let tag = compute_tag(&ciphertext, cipher.round_keys(), &iv, &aad).unwrap();
+        let j0 = derive_j0(cipher.ghash_key(), &iv);
+        let tag = tag_all_backends(&ciphertext, cipher.round_keys(), &j0, &aad);
         assert_eq!(tag, hex_to_arr_16("58e2fccefa7e3061367f1d57a4e7455a"));
 
         Ok(())
@@ -184,7 +935,8 @@ mod test_gcm {
 
         let key = Key::try_from_slice(&key)?;
         let cipher = Cipher::new(&key);
-        let tag = compute_tag(&ciphertext, cipher.round_keys(), &iv, &aad).unwrap();
+        let j0 = derive_j0(cipher.ghash_key(), &iv);
+        let tag = tag_all_backends(&ciphertext, cipher.round_keys(), &j0, &aad);
         assert_eq!(tag, hex_to_arr_16("ab6e47d42cec13bdf53a67b21257bddf"));
 
         Ok(())
@@ -210,7 +962,8 @@ mod test_gcm {
 
         let key = Key::try_from_slice(&key)?;
         let cipher = Cipher::new(&key);
-        let tag = compute_tag(&ciphertext, cipher.round_keys(), &iv, &aad).unwrap();
+        let j0 = derive_j0(cipher.ghash_key(), &iv);
+        let tag = tag_all_backends(&ciphertext, cipher.round_keys(), &j0, &aad);
         assert_eq!(tag, hex_to_arr_16("4d5c2af327cd64a62cf35abd2ba6fab4"));
 
         Ok(())
@@ -236,7 +989,8 @@ mod test_gcm {
 
         let key = Key::try_from_slice(&key)?;
         let cipher = Cipher::new(&key);
-        let tag = compute_tag(&ciphertext, cipher.round_keys(), &iv, &aad).unwrap();
+        let j0 = derive_j0(cipher.ghash_key(), &iv);
+        let tag = tag_all_backends(&ciphertext, cipher.round_keys(), &j0, &aad);
         assert_eq!(tag, hex_to_arr_16("5bc94fbc3221a5db94fae95ae7121a47"));
 
         Ok(())
@@ -0,0 +1,264 @@
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use rayon::prelude::*;
+
+use crate::aesp::cipher::Cipher;
+use crate::aesp::core::encrypt_block;
+use crate::aesp::error::*;
+use crate::aesp::key::Key;
+use crate::aesp::modes::gcm::GHashKey;
+use crate::aesp::modes::util::mul_x;
+use crate::aesp::util::{ct_eq, xor_into, zeroize};
+
+/*
+https://www.rfc-editor.org/rfc/rfc8452
+
+per-nonce keys, from the first 8 bytes of each of:
+    encrypt_block(LE32(i) || nonce, key) for i = 0..4 (AES-128) or 0..6 (AES-256)
+    message authentication key = blocks 0, 1
+    message encryption key     = blocks 2, 3 (and 4, 5 for AES-256)
+
+S_s = POLYVAL(auth_key, padded AAD || padded plaintext || LE64(aad bits) || LE64(pt bits))
+S_s[0..12] ^= nonce, clear the MSB of S_s[15]
+tag = encrypt_block(S_s, enc_key)
+
+ciphertext = CTR under enc_key, with the initial counter block = tag with the MSB of byte 15 set, and the
+first 4 bytes incremented as a little-endian u32 (wrapping)
+
+POLYVAL is GHASH with the bytes of each block reversed (RFC 8452 appendix A):
+POLYVAL(H, X_1, ..., X_n) = rev(GHASH(mulX_GHASH(rev(H)), rev(X_1), ..., rev(X_n)))
+so the GHASH backends (including carryless multiply) are reused rather than duplicated.
+*/
+
+/// Maximum plaintext (and AAD) length in bytes: 2^36 (RFC 8452 section 6).
+pub(crate) const MAX_LEN: u64 = 1 << 36;
+
+/// POLYVAL accumulator, implemented on top of GHASH.
+struct Polyval {
+    key: GHashKey,
+    s: [u8; 16],
+}
+
+impl Polyval {
+    fn new(h: &[u8; 16]) -> Self {
+        Self {
+            key: GHashKey::new(mul_x(u128::from_le_bytes(*h)).to_be_bytes()),
+            s: [0u8; 16],
+        }
+    }
+
+    /// Absorb data, zero-padding the final partial block.
+    fn update_padded(&mut self, data: &[u8]) {
+        for chunk in data.chunks(16) {
+            let mut block = [0u8; 16];
+            block[..chunk.len()].copy_from_slice(chunk);
+            block.reverse();
+            self.s = self.key.ghash(self.s, &block);
+        }
+    }
+
+    fn finish(mut self) -> [u8; 16] {
+        let mut s = self.s;
+        s.reverse();
+        zeroize(&mut self.s);
+        s
+    }
+}
+
+/// Derive the per-nonce message authentication key and message encryption cipher.
+/// Only AES-128 and AES-256 are defined for GCM-SIV.
+fn derive_keys(round_keys: &[[u8; 16]], nonce: &[u8; 12]) -> Result<([u8; 16], Cipher)> {
+    let enc_len = match round_keys.len() {
+        11 => 16,
+        15 => 32,
+        n => {
+            return Err(Error::UnsupportedKeyLength {
+                len: (n - 7) * 4,
+                context: "GCM-SIV requires 16 or 32 bytes",
+            });
+        }
+    };
+
+    // 8 bytes of key material per block
+    let mut material = [0u8; 48];
+    let mut block = [0u8; 16];
+    block[4..].copy_from_slice(nonce);
+    for (i, out) in material[..16 + enc_len].chunks_exact_mut(8).enumerate() {
+        block[..4].copy_from_slice(&(i as u32).to_le_bytes());
+        let mut e = encrypt_block(&block, round_keys);
+        out.copy_from_slice(&e[..8]);
+        zeroize(&mut e);
+    }
+
+    let mut auth_key = [0u8; 16];
+    auth_key.copy_from_slice(&material[..16]);
+    let enc = Cipher::new(&Key::try_from_slice(&material[16..16 + enc_len])?);
+    zeroize(&mut material);
+    Ok((auth_key, enc))
+}
+
+/// Validates plaintext (or ciphertext) and AAD lengths against the GCM-SIV limits.
+fn check_lengths(text_len: usize, aad_len: usize) -> Result<()> {
+    for len in [text_len, aad_len] {
+        if len as u64 > MAX_LEN {
+            return Err(Error::InputTooLong {
+                len,
+                max: MAX_LEN.try_into().unwrap_or(usize::MAX),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Compute the tag over AAD and plaintext. GCM-SIV authenticates the plaintext, not the ciphertext.
+fn compute_tag(auth_key: &[u8; 16], enc: &Cipher, nonce: &[u8; 12], plaintext: &[u8], aad: &[u8]) -> [u8; 16] {
+    let mut polyval = Polyval::new(auth_key);
+    polyval.update_padded(aad);
+    polyval.update_padded(plaintext);
+
+    let mut len = [0u8; 16];
+    len[..8].copy_from_slice(&((aad.len() as u64) * 8).to_le_bytes());
+    len[8..].copy_from_slice(&((plaintext.len() as u64) * 8).to_le_bytes());
+    polyval.update_padded(&len);
+
+    let mut s = polyval.finish();
+    xor_into(&mut s[..12], nonce);
+    s[15] &= 0x7f;
+    encrypt_block(&s, enc.round_keys())
+}
+
+/// CTR with a little-endian 32-bit counter in the first 4 bytes of the block, wrapping modulo 2^32.
+/// Inputs longer than `parallel_threshold` bytes are processed in parallel.
+fn siv_ctr(buf: &mut [u8], enc: &Cipher, tag: &[u8; 16], parallel_threshold: usize) {
+    let mut initial = *tag;
+    initial[15] |= 0x80;
+    let ctr_start = u32::from_le_bytes([initial[0], initial[1], initial[2], initial[3]]);
+
+    let apply_keystream = |(i, chunk): (usize, &mut [u8])| {
+        let mut block = initial;
+        block[..4].copy_from_slice(&ctr_start.wrapping_add(i as u32).to_le_bytes());
+        xor_into(chunk, &encrypt_block(&block, enc.round_keys()));
+    };
+
+    #[cfg(feature = "std")]
+    if buf.len() > parallel_threshold {
+        buf.par_chunks_mut(16).enumerate().for_each(apply_keystream);
+        return;
+    }
+    #[cfg(not(feature = "std"))]
+    let _ = parallel_threshold;
+
+    buf.chunks_mut(16).enumerate().for_each(apply_keystream);
+}
+
+/// Core GCM-SIV encryption (RFC 8452). Returns `(ciphertext, tag)`.
+pub fn gcm_siv_encrypt(
+    plaintext: &[u8],
+    aad: &[u8],
+    round_keys: &[[u8; 16]],
+    nonce: &[u8; 12],
+    parallel_threshold: usize,
+) -> Result<(Vec<u8>, [u8; 16])> {
+    check_lengths(plaintext.len(), aad.len())?;
+    let (mut auth_key, enc) = derive_keys(round_keys, nonce)?;
+    let tag = compute_tag(&auth_key, &enc, nonce, plaintext, aad);
+    zeroize(&mut auth_key);
+
+    let mut ciphertext = plaintext.to_vec();
+    siv_ctr(&mut ciphertext, &enc, &tag, parallel_threshold);
+    Ok((ciphertext, tag))
+}
+
+/// Core GCM-SIV decryption (RFC 8452). Returns [AuthFailed](crate::Error::AuthFailed) if the tag does not
+/// match, in which case no plaintext is released.
+pub fn gcm_siv_decrypt(
+    ciphertext: &[u8],
+    tag: &[u8; 16],
+    aad: &[u8],
+    round_keys: &[[u8; 16]],
+    nonce: &[u8; 12],
+    parallel_threshold: usize,
+) -> Result<Vec<u8>> {
+    check_lengths(ciphertext.len(), aad.len())?;
+    let (mut auth_key, enc) = derive_keys(round_keys, nonce)?;
+
+    // the tag is computed over the plaintext, so decrypt first
+    let mut plaintext = ciphertext.to_vec();
+    siv_ctr(&mut plaintext, &enc, tag, parallel_threshold);
+    let computed_tag = compute_tag(&auth_key, &enc, nonce, &plaintext, aad);
+    zeroize(&mut auth_key);
+
+    if !ct_eq(tag, &computed_tag) {
+        zeroize(&mut plaintext);
+        return Err(Error::AuthFailed);
+    }
+    Ok(plaintext)
+}
+
+#[cfg(test)]
+mod test_gcm_siv {
+    use super::*;
+    use crate::aesp::modes::util::test_util::{hex_to_arr_12, hex_to_arr_16, hex_to_bytes};
+    use crate::{Cipher, Key};
+
+    #[test]
+    fn polyval_rfc8452_example() {
+        // RFC 8452 appendix A
+        let h = hex_to_arr_16("25629347589242761d31f826ba4b757b");
+        let mut polyval = Polyval::new(&h);
+        polyval.update_padded(&hex_to_bytes(
+            "4f4f95668c83dfb6401762bb2d01a262d1a24ddd2721d006bbe45f20d3c9f362",
+        ));
+        assert_eq!(polyval.finish(), hex_to_arr_16("f7a3b47b846119fae5b7866cf5e5b77e"));
+    }
+
+    #[test]
+    fn derives_rfc8452_keys() -> Result<()> {
+        // RFC 8452 appendix C.1, first vector
+        let cipher = Cipher::new(&Key::try_from_slice(&hex_to_bytes("01000000000000000000000000000000"))?);
+        let (auth_key, enc) = derive_keys(cipher.round_keys(), &hex_to_arr_12("030000000000000000000000"))?;
+        assert_eq!(auth_key, hex_to_arr_16("d9b360279694941ac5dbc6987ada7377"));
+        assert_eq!(enc.round_keys()[0], hex_to_arr_16("4004a0dcd862f2a57360219d2d44ef6c"));
+        Ok(())
+    }
+
+    #[test]
+    fn counter_wraps_little_endian() -> Result<()> {
+        // RFC 8452 appendix C.3: the initial counter is 0xffffffff, so the second block wraps to 0
+        let cipher = Cipher::new(&Key::try_from_slice(&hex_to_bytes(
+            "0000000000000000000000000000000000000000000000000000000000000000",
+        ))?);
+        let nonce = hex_to_arr_12("000000000000000000000000");
+        let plaintext = hex_to_bytes(
+            "000000000000000000000000000000004db923dc793ee6497c76dcc03a98e108",
+        );
+        let (ct, tag) = gcm_siv_encrypt(&plaintext, &[], cipher.round_keys(), &nonce, usize::MAX)?;
+        assert_eq!(
+            ct,
+            hex_to_bytes("f3f80f2cf0cb2dd9c5984fcda908456cc537703b5ba70324a6793a7bf218d3ea")
+        );
+        assert_eq!(tag, hex_to_arr_16("ffffffff000000000000000000000000"));
+        Ok(())
+    }
+
+    #[test]
+    fn parallel_matches_serial() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_256()?);
+        let nonce = [7u8; 12];
+        let plaintext: Vec<u8> = (0..10_000u32).map(|i| i as u8).collect();
+        let serial = gcm_siv_encrypt(&plaintext, b"aad", cipher.round_keys(), &nonce, usize::MAX)?;
+        let parallel = gcm_siv_encrypt(&plaintext, b"aad", cipher.round_keys(), &nonce, 0)?;
+        assert_eq!(serial, parallel);
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_192_bit_keys() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_192()?);
+        assert!(matches!(
+            gcm_siv_encrypt(b"", &[], cipher.round_keys(), &[0u8; 12], usize::MAX),
+            Err(Error::UnsupportedKeyLength { len: 24, .. })
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,301 @@
+use alloc::vec::Vec;
+
+use crate::aesp::cipher::Cipher;
+use crate::aesp::error::*;
+use crate::aesp::modes::ctr::ctr_core;
+use crate::aesp::modes::ctr_stream::CtrStream;
+use crate::aesp::modes::gcm::GHashState;
+use crate::aesp::modes::util::ctr_block;
+use crate::aesp::util::ct_eq;
+
+/// Incremental **Galois/counter mode** encryption, created by [gcm_encryptor](crate::Cipher::gcm_encryptor).
+///
+/// AAD is supplied through any number of calls to [aad](crate::GcmEncryptor::aad), followed by plaintext
+/// through any number of calls to [update](crate::GcmEncryptor::update). Chunks may be any size. The tag is
+/// returned by [finish](crate::GcmEncryptor::finish).
+///
+/// Output is raw ciphertext only: unlike [encrypt_gcm](crate::Cipher::encrypt_gcm), the IV, AAD, and tag
+/// are not packed into the output and must be transmitted separately.
+///
+/// ## Examples
+/// ```
+/// # fn main() -> aesp::Result<()> {
+/// use aesp::{Cipher, Key};
+///
+/// let cipher = Cipher::new(&Key::rand_key_256()?);
+/// let iv = [0x42; 12];
+///
+/// let mut enc = cipher.gcm_encryptor(&iv);
+/// enc.aad(b"header");
+/// let mut ciphertext = enc.update(b"Hello, ")?;
+/// ciphertext.extend(enc.update(b"World!")?);
+/// let tag = enc.finish();
+///
+/// let mut dec = cipher.gcm_decryptor(&iv);
+/// dec.aad(b"header");
+/// dec.update(&ciphertext);
+/// assert_eq!(dec.finish(&tag)?, b"Hello, World!");
+/// # Ok(())
+/// # }
+/// ```
+pub struct GcmEncryptor<'a> {
+    cipher: &'a Cipher,
+    j0: [u8; 16],
+    ctr: CtrStream<'a>,
+    ghash: GHashState<'a>,
+    started: bool,
+}
+
+impl<'a> GcmEncryptor<'a> {
+    pub(crate) fn new(cipher: &'a Cipher, iv: &[u8; 12]) -> Self {
+        Self {
+            cipher,
+            j0: ctr_block(iv, 1),
+            ctr: CtrStream::with_counter(cipher, iv, 2), // ctr starts at 2, as per NIST spec
+            ghash: GHashState::new(cipher.ghash_key()),
+            started: false,
+        }
+    }
+
+    /// Supplies additional authenticated data. May be called any number of times: the chunks are
+    /// authenticated in order, exactly as if they had been passed in a single call. All AAD must be
+    /// supplied before the first call to [update](crate::GcmEncryptor::update).
+    ///
+    /// # Panics
+    /// Panics if called after [update](crate::GcmEncryptor::update).
+    pub fn aad(&mut self, aad: &[u8]) {
+        assert!(!self.started, "GCM AAD must be supplied before any plaintext");
+        self.ghash.aad(aad);
+    }
+
+    /// Encrypts the next chunk of plaintext. Returns [CounterOverflow](crate::Error::CounterOverflow) if the
+    /// total plaintext would exceed the GCM limit of 2^32 - 2 blocks.
+    pub fn update(&mut self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        self.started = true;
+        let ciphertext = self.ctr.update(plaintext)?;
+        self.ghash.ciphertext(&ciphertext);
+        Ok(ciphertext)
+    }
+
+    /// Finishes encryption, returning the 16-byte authentication tag.
+    pub fn finish(self) -> [u8; 16] {
+        self.ghash.tag(self.cipher.round_keys(), &self.j0)
+    }
+}
+
+/// Incremental **Galois/counter mode** decryption, created by [gcm_decryptor](crate::Cipher::gcm_decryptor).
+///
+/// Mirrors [GcmEncryptor]. Ciphertext passed to [update](crate::GcmDecryptor::update) is buffered, and
+/// plaintext is only released by [finish](crate::GcmDecryptor::finish) once the tag has been verified, so
+/// unauthenticated plaintext is never exposed: a truncated or tampered stream yields an error and no plaintext.
+///
+/// The cost of this guarantee is memory. The whole ciphertext is held until `finish`, and the plaintext is
+/// allocated alongside it, so peak usage is about twice the message size. For messages too large for that,
+/// [DecryptReader](crate::DecryptReader) streams in constant memory, but releases plaintext before the tag is
+/// checked.
+pub struct GcmDecryptor<'a> {
+    cipher: &'a Cipher,
+    iv: [u8; 12],
+    ghash: GHashState<'a>,
+    ciphertext: Vec<u8>,
+    started: bool,
+}
+
+impl<'a> GcmDecryptor<'a> {
+    pub(crate) fn new(cipher: &'a Cipher, iv: &[u8; 12]) -> Self {
+        Self {
+            cipher,
+            iv: *iv,
+            ghash: GHashState::new(cipher.ghash_key()),
+            ciphertext: Vec::new(),
+            started: false,
+        }
+    }
+
+    /// Supplies additional authenticated data.
+    ///
+    /// # Panics
+    /// Panics if called after [update](crate::GcmDecryptor::update).
+    pub fn aad(&mut self, aad: &[u8]) {
+        assert!(!self.started, "GCM AAD must be supplied before any ciphertext");
+        self.ghash.aad(aad);
+    }
+
+    /// Buffers the next chunk of ciphertext.
+    pub fn update(&mut self, ciphertext: &[u8]) {
+        self.started = true;
+        self.ghash.ciphertext(ciphertext);
+        self.ciphertext.extend_from_slice(ciphertext);
+    }
+
+    /// Verifies `tag` and returns the plaintext. Returns [AuthFailed](crate::Error::AuthFailed) if the tag
+    /// does not match, in which case no plaintext is produced.
+    pub fn finish(self, tag: &[u8; 16]) -> Result<Vec<u8>> {
+        let computed_tag = self.ghash.tag(self.cipher.round_keys(), &ctr_block(&self.iv, 1));
+        if !ct_eq(tag, &computed_tag) {
+            return Err(Error::AuthFailed);
+        }
+        ctr_core(
+            &self.ciphertext,
+            self.cipher.round_keys(),
+            &self.iv,
+            2,
+            self.cipher.parallel_threshold(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test_gcm_stream {
+    use super::*;
+    use crate::Key;
+    use crate::aesp::modes::util::test_util::{hex_to_arr_12, hex_to_arr_16, hex_to_bytes};
+
+    // NIST GCM spec test cases 4 (AES-128) and 16 (AES-256)
+    const IV: &str = "cafebabefacedbaddecaf888";
+    const PT: &str = "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a72\
+                      1c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39";
+    const AAD: &str = "feedfacedeadbeeffeedfacedeadbeefabaddad2";
+    const VECTORS: [(&str, &str, &str); 2] = [
+        (
+            "feffe9928665731c6d6a8f9467308308",
+            "42831ec2217774244b7221b784d0d49ce3aa212f2c02a4e035c17e2329aca12e\
+             21d514b25466931c7d8f6a5aac84aa051ba30b396a0aac973d58e091",
+            "5bc94fbc3221a5db94fae95ae7121a47",
+        ),
+        (
+            "feffe9928665731c6d6a8f9467308308feffe9928665731c6d6a8f9467308308",
+            "522dc1f099567d07f47f37a32a84427d643a8cdcbfe5c0c97598a2bd2555d1aa\
+             8cb08e48590dbb3da7b08b1056828838c5f61e6393ba7a0abcc9f662",
+            "76fc6ece0f4e1768cddf8853bb2d551b",
+        ),
+    ];
+
+    #[test]
+    fn nist_vectors_incremental() -> Result<()> {
+        let iv = hex_to_arr_12(IV);
+        let pt = hex_to_bytes(PT);
+        let aad = hex_to_bytes(AAD);
+
+        for (key, ct, tag) in VECTORS {
+            let cipher = Cipher::new(&Key::try_from_slice(&hex_to_bytes(key))?);
+            let expected_ct = hex_to_bytes(ct);
+            let expected_tag = hex_to_arr_16(tag);
+
+            for chunk in [1, 3, 15, 16, 17, 64] {
+                let mut enc = cipher.gcm_encryptor(&iv);
+                for a in aad.chunks(chunk) {
+                    enc.aad(a);
+                }
+                let mut ciphertext = Vec::new();
+                for p in pt.chunks(chunk) {
+                    ciphertext.extend(enc.update(p)?);
+                }
+                assert_eq!(ciphertext, expected_ct, "chunk size {chunk}");
+                assert_eq!(enc.finish(), expected_tag, "chunk size {chunk}");
+
+                let mut dec = cipher.gcm_decryptor(&iv);
+                for a in aad.chunks(chunk) {
+                    dec.aad(a);
+                }
+                for c in expected_ct.chunks(chunk) {
+                    dec.update(c);
+                }
+                assert_eq!(dec.finish(&expected_tag)?, pt, "chunk size {chunk}");
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn aad_split_across_calls() -> Result<()> {
+        let (key, ct, tag) = VECTORS[0];
+        let cipher = Cipher::new(&Key::try_from_slice(&hex_to_bytes(key))?);
+        let iv = hex_to_arr_12(IV);
+        let aad = hex_to_bytes(AAD);
+
+        let single = {
+            let mut enc = cipher.gcm_encryptor(&iv);
+            enc.aad(&aad);
+            enc.update(&hex_to_bytes(PT))?;
+            enc.finish()
+        };
+
+        // 20 bytes of AAD in three uneven calls, crossing the 16-byte block boundary mid-call
+        let mut enc = cipher.gcm_encryptor(&iv);
+        enc.aad(&aad[..5]);
+        enc.aad(&aad[5..18]);
+        enc.aad(&aad[18..]);
+        assert_eq!(enc.update(&hex_to_bytes(PT))?, hex_to_bytes(ct));
+        let split = enc.finish();
+
+        assert_eq!(split, single);
+        assert_eq!(split, hex_to_arr_16(tag));
+        Ok(())
+    }
+
+    #[test]
+    fn matches_one_shot() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_192()?);
+        let iv = [0x13; 12];
+        let pt: Vec<u8> = (0..5000u32).map(|i| i as u8).collect();
+
+        let one_shot = cipher.encrypt_gcm_with_iv(&pt, Some(b"aad"), &iv)?;
+        let mut enc = cipher.gcm_encryptor(&iv);
+        enc.aad(b"aad");
+        let ciphertext = enc.update(&pt)?;
+        let tag = enc.finish();
+
+        // one-shot output: IV (12) || AAD length (8) || AAD (3) || Ciphertext || Tag (16)
+        assert_eq!(one_shot[23..one_shot.len() - 16], ciphertext);
+        assert_eq!(one_shot[one_shot.len() - 16..], tag);
+        Ok(())
+    }
+
+    #[test]
+    fn decryptor_rejects_bad_tag() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_128()?);
+        let iv = [0u8; 12];
+        let mut enc = cipher.gcm_encryptor(&iv);
+        let ciphertext = enc.update(b"secret")?;
+        let mut tag = enc.finish();
+        tag[0] ^= 1;
+
+        let mut dec = cipher.gcm_decryptor(&iv);
+        dec.update(&ciphertext);
+        assert!(matches!(dec.finish(&tag), Err(Error::AuthFailed)));
+        Ok(())
+    }
+
+    #[test]
+    fn decryptor_releases_nothing_for_tampered_ciphertext() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_256()?);
+        let iv = [0x21; 12];
+        let pt = [0x5a; 1000];
+        let mut enc = cipher.gcm_encryptor(&iv);
+        let ciphertext = enc.update(&pt)?;
+        let tag = enc.finish();
+
+        for i in [0, 500, 999] {
+            let mut tampered = ciphertext.clone();
+            tampered[i] ^= 1;
+
+            // update returns nothing, so the only way to get plaintext out is a successful finish
+            let mut dec = cipher.gcm_decryptor(&iv);
+            for chunk in tampered.chunks(64) {
+                dec.update(chunk);
+            }
+            assert!(matches!(dec.finish(&tag), Err(Error::AuthFailed)), "byte {i}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic(expected = "before any plaintext")]
+    fn aad_after_update_panics() {
+        let cipher = Cipher::new(&Key::try_from_slice(&[0u8; 16]).unwrap());
+        let mut enc = cipher.gcm_encryptor(&[0u8; 12]);
+        enc.update(b"data").unwrap();
+        enc.aad(b"late");
+    }
+}
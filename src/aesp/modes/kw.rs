@@ -0,0 +1,228 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::aesp::core::{decrypt_block, encrypt_block};
+use crate::aesp::error::*;
+use crate::aesp::modes::util::as_block;
+use crate::aesp::util::zeroize;
+
+/*
+AES Key Wrap with Padding (RFC 5649), built on the RFC 3394 wrapping process W.
+
+Alternative initial value (AIV) = 0xA65959A6 || message length in bytes (u32 BE)
+
+wrap:
+    - zero-pad the key to a multiple of 8 bytes
+    - if padded length is 8: output = encrypt_block(AIV || P)
+    - otherwise: output = W(AIV, P)
+
+unwrap reverses the process, then checks the AIV constant, that the length lies in the final
+8-byte block, and that the padding bytes are zero.
+*/
+
+/// High 32 bits of the RFC 5649 alternative initial value.
+const AIV_PREFIX: [u8; 4] = [0xa6, 0x59, 0x59, 0xa6];
+
+/// RFC 3394 wrapping process W. Wraps 64-bit registers `r` in place, returning the final value of A.
+fn w(mut a: [u8; 8], r: &mut [u8], round_keys: &[[u8; 16]]) -> [u8; 8] {
+    let n = r.len() / 8;
+    let mut b = [0u8; 16];
+    for j in 0..6 {
+        for (i, ri) in r.chunks_exact_mut(8).enumerate() {
+            // B = AES(K, A || R[i]), A = MSB(64, B) ^ t, R[i] = LSB(64, B)
+            b[..8].copy_from_slice(&a);
+            b[8..].copy_from_slice(ri);
+            b = encrypt_block(&b, round_keys);
+
+            let t = (n * j + i + 1) as u64;
+            a = (u64::from_be_bytes(b[..8].try_into().unwrap()) ^ t).to_be_bytes();
+            ri.copy_from_slice(&b[8..]);
+        }
+    }
+    zeroize(&mut b);
+    a
+}
+
+/// RFC 3394 unwrapping process W^-1. Unwraps 64-bit registers `r` in place, returning the recovered A.
+fn w_inv(mut a: [u8; 8], r: &mut [u8], round_keys: &[[u8; 16]]) -> [u8; 8] {
+    let n = r.len() / 8;
+    let mut b = [0u8; 16];
+    for j in (0..6).rev() {
+        for (i, ri) in r.chunks_exact_mut(8).enumerate().rev() {
+            // B = AES-1(K, (A ^ t) || R[i]), A = MSB(64, B), R[i] = LSB(64, B)
+            let t = (n * j + i + 1) as u64;
+            b[..8].copy_from_slice(&(u64::from_be_bytes(a) ^ t).to_be_bytes());
+            b[8..].copy_from_slice(ri);
+            b = decrypt_block(&b, round_keys);
+
+            a.copy_from_slice(&b[..8]);
+            ri.copy_from_slice(&b[8..]);
+        }
+    }
+    zeroize(&mut b);
+    a
+}
+
+/// RFC 5649 key wrap. Input must be between 1 and 2^32 - 1 bytes.
+pub fn kwp_wrap(key_data: &[u8], round_keys: &[[u8; 16]]) -> Result<Vec<u8>> {
+    if key_data.is_empty() {
+        return Err(Error::InvalidKeyWrapInput { len: 0 });
+    }
+    let mli: u32 = key_data.len().try_into().map_err(|_| Error::InputTooLong {
+        len: key_data.len(),
+        max: u32::MAX as usize,
+    })?;
+
+    let mut aiv = [0u8; 8];
+    aiv[..4].copy_from_slice(&AIV_PREFIX);
+    aiv[4..].copy_from_slice(&mli.to_be_bytes());
+
+    // zero-pad to a multiple of 8 bytes
+    let mut out = vec![0u8; 8 + key_data.len().div_ceil(8) * 8];
+    out[8..8 + key_data.len()].copy_from_slice(key_data);
+
+    if out.len() == 16 {
+        // single 64-bit block: one AES encryption of AIV || P
+        let mut block = [0u8; 16];
+        block[..8].copy_from_slice(&aiv);
+        block[8..].copy_from_slice(&out[8..]);
+        out.copy_from_slice(&encrypt_block(&block, round_keys));
+        zeroize(&mut block);
+    } else {
+        let a = w(aiv, &mut out[8..], round_keys);
+        out[..8].copy_from_slice(&a);
+    }
+
+    Ok(out)
+}
+
+/// RFC 5649 key unwrap. Returns AuthFailed if the AIV, embedded length, or padding do not verify.
+pub fn kwp_unwrap(wrapped: &[u8], round_keys: &[[u8; 16]]) -> Result<Vec<u8>> {
+    if wrapped.len() < 16 || !wrapped.len().is_multiple_of(8) {
+        return Err(Error::InvalidCiphertext {
+            len: wrapped.len(),
+            context: "key wrap input must be a multiple of 8 bytes and at least 16 bytes",
+        });
+    }
+
+    let mut a = [0u8; 8];
+    let mut p = wrapped[8..].to_vec();
+    if wrapped.len() == 16 {
+        let mut b = decrypt_block(as_block(wrapped)?, round_keys);
+        a.copy_from_slice(&b[..8]);
+        p.copy_from_slice(&b[8..]);
+        zeroize(&mut b);
+    } else {
+        a.copy_from_slice(&wrapped[..8]);
+        a = w_inv(a, &mut p, round_keys);
+    }
+
+    // length must fall within the final 8-byte block, and all padding bytes must be zero
+    let mli = u32::from_be_bytes([a[4], a[5], a[6], a[7]]) as usize;
+    let valid = a[..4] == AIV_PREFIX
+        && mli > p.len() - 8
+        && mli <= p.len()
+        && p[mli..].iter().all(|&byte| byte == 0);
+
+    if !valid {
+        zeroize(&mut p);
+        return Err(Error::AuthFailed);
+    }
+
+    p.truncate(mli);
+    Ok(p)
+}
+
+#[cfg(test)]
+mod test_kw {
+    use super::*;
+    use crate::aesp::modes::util::test_util::hex_to_bytes;
+    use crate::{Cipher, Key};
+
+    const KEK: &str = "5840df6e29b02af1ab493b705bf16ea1ae8338f4dcc176a8";
+
+    #[test]
+    fn rfc5649_20_byte_key() -> Result<()> {
+        let cipher = Cipher::new(&Key::try_from_slice(&hex_to_bytes(KEK))?);
+        let key = hex_to_bytes("c37b7e6492584340bed12207808941155068f738");
+        let expected = hex_to_bytes(
+            "138bdeaa9b8fa7fc61f97742e72248ee5ae6ae5360d1ae6a5f54f373fa543b6a",
+        );
+
+        let wrapped = cipher.wrap_key_padded(&key)?;
+        assert_eq!(wrapped, expected);
+        assert_eq!(cipher.unwrap_key_padded(&wrapped)?, key);
+        Ok(())
+    }
+
+    #[test]
+    fn rfc5649_7_byte_key() -> Result<()> {
+        let cipher = Cipher::new(&Key::try_from_slice(&hex_to_bytes(KEK))?);
+        let key = hex_to_bytes("466f7250617369");
+        let expected = hex_to_bytes("afbeb0f07dfbf5419200f2ccb50bb24f");
+
+        let wrapped = cipher.wrap_key_padded(&key)?;
+        assert_eq!(wrapped, expected);
+        assert_eq!(cipher.unwrap_key_padded(&wrapped)?, key);
+        Ok(())
+    }
+
+    #[test]
+    fn round_trip_all_lengths() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_256()?);
+        for len in 1..=64usize {
+            let key: Vec<u8> = (0..len as u8).collect();
+            let wrapped = cipher.wrap_key_padded(&key)?;
+            assert_eq!(wrapped.len(), 8 + len.div_ceil(8) * 8);
+            assert_eq!(cipher.unwrap_key_padded(&wrapped)?, key);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_bad_aiv_and_length() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_128()?);
+        let rk = cipher.round_keys();
+        let data = [0x11u8; 16];
+
+        // wrong AIV constant
+        let mut r = data;
+        let a = w([0xa6; 8], &mut r, rk);
+        let wrapped = [&a[..], &r[..]].concat();
+        assert!(matches!(cipher.unwrap_key_padded(&wrapped), Err(Error::AuthFailed)));
+
+        // embedded length outside the final block
+        for mli in [0u32, 8, 17] {
+            let mut aiv = [0u8; 8];
+            aiv[..4].copy_from_slice(&AIV_PREFIX);
+            aiv[4..].copy_from_slice(&mli.to_be_bytes());
+            let mut r = data;
+            let a = w(aiv, &mut r, rk);
+            let wrapped = [&a[..], &r[..]].concat();
+            assert!(matches!(cipher.unwrap_key_padded(&wrapped), Err(Error::AuthFailed)));
+        }
+
+        // non-zero padding: 16 bytes of data claiming a length of 12
+        let mut aiv = [0u8; 8];
+        aiv[..4].copy_from_slice(&AIV_PREFIX);
+        aiv[4..].copy_from_slice(&12u32.to_be_bytes());
+        let mut r = data;
+        let a = w(aiv, &mut r, rk);
+        let wrapped = [&a[..], &r[..]].concat();
+        assert!(matches!(cipher.unwrap_key_padded(&wrapped), Err(Error::AuthFailed)));
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_tampering_and_bad_lengths() -> Result<()> {
+        let cipher = Cipher::new(&Key::rand_key_192()?);
+        let mut wrapped = cipher.wrap_key_padded(b"0123456789abcdef")?;
+        wrapped[10] ^= 1;
+        assert!(matches!(cipher.unwrap_key_padded(&wrapped), Err(Error::AuthFailed)));
+
+        assert!(matches!(cipher.unwrap_key_padded(&[0u8; 8]), Err(Error::InvalidCiphertext { .. })));
+        assert!(matches!(cipher.unwrap_key_padded(&[0u8; 20]), Err(Error::InvalidCiphertext { .. })));
+        assert!(matches!(cipher.wrap_key_padded(&[]), Err(Error::InvalidKeyWrapInput { len: 0 })));
+        Ok(())
+    }
+}
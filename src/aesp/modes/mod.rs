@@ -1,10 +1,60 @@
 //! Core mode of operation implementations
 
+#[cfg(target_arch = "x86_64")]
+mod clmul;
+mod cbc;
+mod ccm;
+mod cmac;
+mod eax;
 mod ecb;
 mod ctr;
+mod ctr_stream;
 mod gcm;
+mod gcm_siv;
+mod gcm_stream;
+mod kw;
 mod util;
 
-pub use ctr::ctr_core;
-pub use ecb::{ecb_core_enc, ecb_core_dec};
-pub use gcm::compute_tag;
\ No newline at end of file
+pub use cbc::{cbc_cts_decrypt, cbc_cts_encrypt, cbc_decrypt_padded, cbc_decrypt_unpadded, cbc_encrypt_padded};
+pub use ccm::{ccm_decrypt, ccm_encrypt};
+pub use cmac::cmac_core;
+pub use eax::{eax_decrypt, eax_encrypt};
+pub use ctr::{ctr_core, ctr_core_64, ctr_core_in_place};
+pub use ctr_stream::CtrStream;
+pub use ecb::{ecb_core_dec, ecb_core_dec_padded, ecb_core_enc_padded};
+#[cfg(feature = "test-vectors")]
+pub use ecb::ecb_core_enc;
+pub(crate) use gcm::{GHashKey, check_lengths};
+#[cfg(feature = "debug-ghash")]
+pub(crate) use gcm::ghash_trace;
+#[cfg(feature = "std")]
+pub(crate) use gcm::GHashState;
+pub use gcm::{GcmFrame, GcmOutput, compute_tag_j0, derive_j0, gctr, gctr_in_place, parse_gcm};
+pub use gcm_siv::{gcm_siv_decrypt, gcm_siv_encrypt};
+pub use gcm_stream::{GcmDecryptor, GcmEncryptor};
+pub use kw::{kwp_unwrap, kwp_wrap};
+pub(crate) use util::PARALLEL_THRESHOLD;
+pub use util::Padding;
+
+/// Mode of operation, used to select a mode at runtime through [encrypt](crate::Cipher::encrypt) and
+/// [decrypt](crate::Cipher::decrypt).
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[non_exhaustive]
+pub enum Mode {
+    /// **Electronic codebook** mode. See [encrypt_ecb](crate::Cipher::encrypt_ecb).
+    Ecb,
+    /// **Counter** mode. See [encrypt_ctr](crate::Cipher::encrypt_ctr).
+    Ctr,
+    /// **Galois/counter** mode, without AAD. See [encrypt_gcm](crate::Cipher::encrypt_gcm).
+    Gcm,
+    /// **Null cipher. Performs no encryption or authentication. Never use in production.**
+    ///
+    /// Copies input to output unchanged, with the same framing as CTR (12-byte IV prefix, all zeros).
+    /// Intended for isolating the cost of the surrounding I/O in pipelines and benchmarks.
+    ///
+    /// Only available with the `testing` feature, which is off by default. Any use emits a
+    /// deprecation warning as a further guard against shipping it accidentally.
+    #[cfg(feature = "testing")]
+    #[deprecated(note = "Mode::Null performs no encryption; for testing and benchmarking only")]
+    Null,
+}
\ No newline at end of file
@@ -1,7 +1,28 @@
-pub const PARALLEL_THRESHOLD: usize = 4 * 1024; // encrypt in parallel if input size exceeds 4 KiB
+use crate::aesp::error::*;
+
+pub const PARALLEL_THRESHOLD: usize = 4 * 1024; // default: encrypt in parallel if input size exceeds 4 KiB
 const GHASH_R: u128 = 0xE100_0000_0000_0000_0000_0000_0000_0000; // reduction constant for GHASH
 
+/// Block padding scheme for [ECB](crate::Cipher::encrypt_ecb_with_padding) encryption and decryption.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+#[non_exhaustive]
+pub enum Padding {
+    /// PKCS#7: every padding byte holds the padding length. Always adds 1 to 16 bytes.
+    #[default]
+    Pkcs7,
+    /// ANSI X9.23: zero bytes followed by a final byte holding the padding length. Always adds 1 to 16 bytes.
+    AnsiX923,
+    /// ISO/IEC 7816-4: a single `0x80` byte followed by zero bytes. Always adds 1 to 16 bytes.
+    Iso7816,
+    /// Zero bytes up to the next block boundary, none if the input is already aligned.
+    ///
+    /// **Ambiguous**: all trailing zero bytes are removed on decryption, including any that were part of
+    /// the plaintext. Only suitable for data that cannot end in a zero byte.
+    Zero,
+}
+
 #[inline(always)]
+#[allow(clippy::zero_prefixed_literal)] // zero-prefixed indices keep the layout aligned
 pub(crate) fn ctr_block(iv: &[u8; 12], ctr: u32) -> [u8; 16] {
     let cb = ctr.to_be_bytes();
     [
@@ -12,6 +33,22 @@ pub(crate) fn ctr_block(iv: &[u8; 12], ctr: u32) -> [u8; 16] {
     ]
 }
 
+/// Counter block for [ctr_core_64](crate::aesp::modes::ctr_core_64): `IV (8 bytes) || Counter (8 bytes, big-endian)`.
+#[inline(always)]
+pub(crate) fn ctr_block64(iv: &[u8; 8], ctr: u64) -> [u8; 16] {
+    let mut block = [0u8; 16];
+    block[..8].copy_from_slice(iv);
+    block[8..].copy_from_slice(&ctr.to_be_bytes());
+    block
+}
+
+/// Views `slice` as a single block, returning an InvalidCiphertext error rather than panicking if it is not
+/// exactly 16 bytes long. For slices whose length is not already guaranteed by `chunks_exact`.
+#[inline(always)]
+pub(crate) fn as_block(slice: &[u8]) -> Result<&[u8; 16]> {
+    slice.try_into().map_err(|_| Error::InvalidCiphertext { len: slice.len(), context: "expected a 16-byte block" })
+}
+
 #[inline(always)]
 pub(crate) fn mul_x(v: u128) -> u128 {
     let lsb = v & 1;
@@ -29,11 +66,25 @@ pub(crate) fn mul_x4(mut v: u128) -> u128 {
     v
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn as_block_rejects_wrong_lengths() {
+        let bytes = [0x42u8; 33];
+        assert_eq!(as_block(&bytes[..16]).unwrap(), &[0x42; 16]);
+        for len in [0, 1, 15, 17, 32, 33] {
+            assert!(matches!(as_block(&bytes[..len]), Err(Error::InvalidCiphertext { len: l, .. }) if l == len));
+        }
+    }
+}
+
 #[cfg(test)]
 pub(crate) mod test_util {
     pub fn hex_to_bytes(s: &str) -> Vec<u8> {
         let s = s.trim();
-        assert!(s.len() % 2 == 0, "hex string must have even length");
+        assert!(s.len().is_multiple_of(2), "hex string must have even length");
         (0..s.len())
             .step_by(2)
             .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
@@ -0,0 +1,96 @@
+use crate::aesp::error::{Error, Result};
+
+/// Deterministic 12-byte nonces, formed as `Prefix (4 bytes) || Counter (8 bytes, big-endian)`
+/// (the construction in NIST SP 800-38D section 8.2.1).
+///
+/// When one party controls the order of messages under a key, a counter guarantees unique nonces, rather than
+/// relying on random nonces not colliding. Use a distinct prefix for each sender sharing the key.
+/// Once the counter has been used up, [next](crate::NonceSequence::next) returns a
+/// [NonceExhausted](crate::Error::NonceExhausted) error instead of repeating a nonce.
+///
+/// A sequence must not be cloned, or restarted with the same prefix under the same key: both reuse nonces.
+///
+/// ## Examples
+/// ```
+/// # fn main() -> aesp::Result<()> {
+/// use aesp::{Cipher, Key, NonceSequence};
+///
+/// let cipher = Cipher::new(&Key::rand_key_256()?);
+/// let mut seq = NonceSequence::new([0x00, 0x00, 0x00, 0x01]);
+///
+/// let first = cipher.encrypt_gcm_with_iv(b"first", None, &seq.next()?)?;
+/// let second = cipher.encrypt_gcm_with_iv(b"second", None, &seq.next()?)?;
+/// assert_eq!(&first[..12], &[0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0]);
+/// assert_eq!(&second[..12], &[0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 1]);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct NonceSequence {
+    prefix: [u8; 4],
+    /// next counter value, or None once u64::MAX has been used
+    counter: Option<u64>,
+}
+
+impl NonceSequence {
+    /// Instantiates a sequence with a fixed 4-byte prefix, starting from counter 0.
+    pub fn new(prefix: [u8; 4]) -> Self {
+        Self::with_counter(prefix, 0)
+    }
+
+    /// Instantiates a sequence starting from `counter`, e.g. to resume a sequence whose last used counter
+    /// value was persisted. Resuming must start after the last used value, never at or before it.
+    pub fn with_counter(prefix: [u8; 4], counter: u64) -> Self {
+        Self {
+            prefix,
+            counter: Some(counter),
+        }
+    }
+
+    /// Returns the next nonce and advances the counter. Returns a [NonceExhausted](crate::Error::NonceExhausted)
+    /// error once every counter value has been used.
+    #[allow(clippy::should_implement_trait)] // fallible, so not an Iterator
+    pub fn next(&mut self) -> Result<[u8; 12]> {
+        let counter = self.counter.ok_or(Error::NonceExhausted)?;
+        self.counter = counter.checked_add(1);
+
+        let mut nonce = [0u8; 12];
+        nonce[..4].copy_from_slice(&self.prefix);
+        nonce[4..].copy_from_slice(&counter.to_be_bytes());
+        Ok(nonce)
+    }
+
+    /// The counter value the next nonce will use, or None if the sequence is exhausted.
+    pub fn counter(&self) -> Option<u64> {
+        self.counter
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_up_from_start() -> Result<()> {
+        let mut seq = NonceSequence::with_counter([0xaa, 0xbb, 0xcc, 0xdd], 0xff);
+        assert_eq!(seq.next()?, [0xaa, 0xbb, 0xcc, 0xdd, 0, 0, 0, 0, 0, 0, 0, 0xff]);
+        assert_eq!(seq.next()?, [0xaa, 0xbb, 0xcc, 0xdd, 0, 0, 0, 0, 0, 0, 1, 0x00]);
+        assert_eq!(seq.counter(), Some(0x101));
+        Ok(())
+    }
+
+    #[test]
+    fn refuses_to_repeat_after_overflow() -> Result<()> {
+        let mut seq = NonceSequence::with_counter([0; 4], u64::MAX - 1);
+        assert_eq!(seq.next()?[4..], (u64::MAX - 1).to_be_bytes());
+        // the final counter value is still usable once
+        assert_eq!(seq.next()?[4..], u64::MAX.to_be_bytes());
+        assert_eq!(seq.counter(), None);
+
+        // never wraps back to counter 0
+        for _ in 0..3 {
+            assert!(matches!(seq.next(), Err(Error::NonceExhausted)));
+        }
+        Ok(())
+    }
+}
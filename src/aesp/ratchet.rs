@@ -0,0 +1,227 @@
+use crate::aesp::cipher::Cipher;
+use crate::aesp::modes::cmac_core;
+use crate::aesp::error::{Error, Result};
+use crate::aesp::key::Key;
+use crate::aesp::util::zeroize;
+
+/// Record flag: no rekey since the previous record.
+const FLAG_NONE: u8 = 0x00;
+/// Record flag: sender ratcheted to a new key before sealing this record.
+const FLAG_REKEY: u8 = 0x01;
+/// SP 800-108 label for the key derivation in [ratchet].
+const RATCHET_LABEL: &[u8; 12] = b"aesp ratchet";
+
+/// GCM stream that automatically ratchets to a fresh key after a configurable number of bytes.
+///
+/// Each call to [seal](crate::RatchetCipher::seal) produces one record. Once the number of plaintext bytes
+/// sealed under the current key reaches the budget, the next record is sealed under a new key derived from
+/// the current one, and the current key is wiped. A rekey marker is carried in the record's (authenticated)
+/// AAD so that the receiving `RatchetCipher` derives the same key before [opening](crate::RatchetCipher::open).
+///
+/// Each record is formatted as the output of [encrypt_gcm](crate::Cipher::encrypt_gcm), with a single
+/// AAD byte holding the rekey marker.
+///
+/// ## Forward secrecy
+/// The next key is derived from the current one with the NIST SP 800-108 counter-mode KDF, using AES-CMAC
+/// as the PRF. Recovering an earlier key from a later one requires breaking AES, so compromise of the
+/// current key does not expose records sealed under earlier keys.
+///
+/// Limits:
+/// - Records sealed under the compromised key, and all later keys, are exposed. There is no recovery
+///   after compromise.
+/// - Records must be opened in the order they were sealed. A dropped or reordered rekey record
+///   desynchronises the receiver, and all later records will fail authentication.
+/// - The budget is counted in plaintext bytes, and a record is never split, so a single record may exceed it.
+///
+/// ## Examples
+/// ```
+/// # fn main() -> aesp::Result<()> {
+/// use aesp::{Key, RatchetCipher};
+///
+/// let key = Key::rand_key_256()?;
+/// let mut sender = RatchetCipher::new(&key, 1024);
+/// let mut receiver = RatchetCipher::new(&key, 1024);
+///
+/// for msg in [&b"first"[..], b"second", b"third"] {
+///     let record = sender.seal(msg)?;
+///     assert_eq!(receiver.open(&record)?, msg);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct RatchetCipher {
+    key: Key,
+    cipher: Cipher,
+    budget: usize,
+    sealed: usize,
+}
+
+impl RatchetCipher {
+    /// Instantiates a ratchet starting from `key`, rekeying after `budget` plaintext bytes. Sender and
+    /// receiver must be instantiated with the same key.
+    pub fn new(key: &Key, budget: usize) -> Self {
+        Self {
+            key: key.clone(),
+            cipher: Cipher::new(key),
+            budget,
+            sealed: 0,
+        }
+    }
+
+    /// Seals `plaintext` into a record, first ratcheting to a new key if the budget has been reached.
+    pub fn seal(&mut self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let flag = if self.sealed >= self.budget {
+            self.advance(ratchet(&self.key));
+            FLAG_REKEY
+        } else {
+            FLAG_NONE
+        };
+
+        let record = self.cipher.encrypt_gcm(plaintext, Some(&[flag]))?;
+        self.sealed = self.sealed.saturating_add(plaintext.len());
+        Ok(record)
+    }
+
+    /// Opens a record produced by [seal](crate::RatchetCipher::seal), ratcheting first if it carries a
+    /// rekey marker. The ratchet only advances if the record authenticates.
+    pub fn open(&mut self, record: &[u8]) -> Result<Vec<u8>> {
+        // rekey marker lives in the single AAD byte: IV (12) || AAD length (8) || flag
+        let flag = match record.get(12..21) {
+            Some([0, 0, 0, 0, 0, 0, 0, 1, flag]) => *flag,
+            _ => {
+                return Err(Error::InvalidCiphertext {
+                    len: record.len(),
+                    context: "ratchet: missing rekey marker",
+                });
+            }
+        };
+
+        match flag {
+            FLAG_NONE => {
+                let (plaintext, _) = self.cipher.decrypt_gcm(record)?;
+                Ok(plaintext)
+            }
+            FLAG_REKEY => {
+                // only commit to the next key once the record authenticates under it
+                let next_key = ratchet(&self.key);
+                let (plaintext, _) = Cipher::new(&next_key).decrypt_gcm(record)?;
+                self.advance(next_key);
+                Ok(plaintext)
+            }
+            _ => Err(Error::InvalidCiphertext {
+                len: record.len(),
+                context: "ratchet: unknown rekey marker",
+            }),
+        }
+    }
+
+    /// Replaces the current key (which is wiped on drop) and resets the byte count.
+    fn advance(&mut self, next_key: Key) {
+        self.cipher = Cipher::new(&next_key);
+        self.key = next_key;
+        self.sealed = 0;
+    }
+}
+
+/// Derives the next key from the current key, with the SP 800-108 counter-mode KDF and AES-CMAC as the PRF.
+/// Block `i` (from 1) of the output is `CMAC(K, [i]_32 || label || 0x00 || [L]_32)`, where `L` is the key
+/// length in bits. There is no context field. The output is truncated to the length of the current key.
+fn ratchet(key: &Key) -> Key {
+    let cipher = Cipher::new(key);
+    let len = key.as_bytes().len();
+
+    let mut next = [0u8; 32];
+    for (i, chunk) in next[..len].chunks_mut(16).enumerate() {
+        // [i]_32 (4) || label (12) || 0x00 (1) || [L]_32 (4)
+        let mut input = [0u8; 21];
+        input[..4].copy_from_slice(&(i as u32 + 1).to_be_bytes());
+        input[4..16].copy_from_slice(RATCHET_LABEL);
+        input[17..].copy_from_slice(&((len * 8) as u32).to_be_bytes());
+
+        let mut out = cmac_core(&input, cipher.round_keys());
+        chunk.copy_from_slice(&out[..chunk.len()]);
+        zeroize(&mut out);
+    }
+
+    let key = Key::try_from_slice(&next[..len]).unwrap(); // same length as a valid key
+    zeroize(&mut next);
+    key
+}
+
+#[cfg(test)]
+mod test_ratchet {
+    use super::*;
+
+    #[test]
+    fn ratchet_is_sp800_108_cmac_kdf() -> Result<()> {
+        for key in [Key::rand_key_128()?, Key::rand_key_192()?, Key::rand_key_256()?] {
+            let cipher = Cipher::new(&key);
+            let len = key.as_bytes().len();
+            let bits = ((len * 8) as u32).to_be_bytes();
+
+            // [i]_32 || "aesp ratchet" || 0x00 || [L]_32 for i = 1, 2
+            let mut expected = Vec::new();
+            for i in [1u32, 2] {
+                let mut input = i.to_be_bytes().to_vec();
+                input.extend_from_slice(b"aesp ratchet\x00");
+                input.extend_from_slice(&bits);
+                expected.extend_from_slice(&cipher.cmac(&input));
+            }
+            assert_eq!(ratchet(&key).as_bytes(), &expected[..len]);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn small_budget_round_trip() -> Result<()> {
+        for key in [Key::rand_key_128()?, Key::rand_key_192()?, Key::rand_key_256()?] {
+            let mut sender = RatchetCipher::new(&key, 10);
+            let mut receiver = RatchetCipher::new(&key, 10);
+
+            let mut rekeys = 0;
+            for i in 0..20u8 {
+                let msg = vec![i; 7];
+                let record = sender.seal(&msg)?;
+                if record[20] == FLAG_REKEY {
+                    rekeys += 1;
+                }
+                assert_eq!(receiver.open(&record)?, msg);
+            }
+
+            // rekeys after every second record (14 bytes >= 10)
+            assert_eq!(rekeys, 9);
+            assert_ne!(sender.key, key);
+            assert_eq!(sender.key, receiver.key);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn old_key_cannot_open_new_records() -> Result<()> {
+        let key = Key::rand_key_256()?;
+        let mut sender = RatchetCipher::new(&key, 0); // rekey before every record
+
+        let record = sender.seal(b"secret")?;
+        assert!(matches!(Cipher::new(&key).decrypt_gcm(&record), Err(Error::AuthFailed)));
+        Ok(())
+    }
+
+    #[test]
+    fn tampered_marker_does_not_advance() -> Result<()> {
+        let key = Key::rand_key_256()?;
+        let mut sender = RatchetCipher::new(&key, 4);
+        let mut receiver = RatchetCipher::new(&key, 4);
+
+        let first = sender.seal(b"0123")?;
+        let second = sender.seal(b"4567")?;
+        assert_eq!(receiver.open(&first)?, b"0123");
+
+        let mut tampered = second.clone();
+        tampered[20] = FLAG_NONE;
+        assert!(matches!(receiver.open(&tampered), Err(Error::AuthFailed)));
+
+        // receiver state is unchanged, so the genuine record still opens
+        assert_eq!(receiver.open(&second)?, b"4567");
+        Ok(())
+    }
+}
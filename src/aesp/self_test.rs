@@ -0,0 +1,88 @@
+use alloc::vec::Vec;
+
+use crate::aesp::cipher::Cipher;
+use crate::aesp::error::{Error, Result};
+use crate::aesp::key::Key;
+use crate::aesp::util::decode_hex;
+
+/// FIPS-197 Appendix C: ciphertext of 00112233...ff under keys 000102... of 16, 24 and 32 bytes.
+const ECB: [&str; 3] = [
+    "69c4e0d86a7b0430d8cdb78070b4c55a",
+    "dda97ca4864cdfe06eaf70a0ec0d7191",
+    "8ea2b7ca516745bfeafc49904b496089",
+];
+
+/// SP 800-38A F.5: (key, first ciphertext block) for initial counter block f0f1...feff.
+const CTR: [(&str, &str); 3] = [
+    ("2b7e151628aed2a6abf7158809cf4f3c", "874d6191b620e3261bef6864990db6ce"),
+    ("8e73b0f7da0e6452c810f32b809079e562f8ead2522c6b7b", "1abc932417521ca24f2b0459fe7e6e0b"),
+    (
+        "603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4",
+        "601ec313775789a5b7a7f504bbf3d228",
+    ),
+];
+
+/// NIST GCM spec test cases 2, 8 and 14: all-zero key, IV and 16-byte plaintext. (ciphertext, tag)
+const GCM: [(&str, &str); 3] = [
+    ("0388dace60b6a392f328c2b971b2fe78", "ab6e47d42cec13bdf53a67b21257bddf"),
+    ("98e7247c07f0fe411c267e4384b0f600", "2ff58d80033927ab8ef4d4587514f0fb"),
+    ("cea7403d4d606b6e074ec5d3baf39d18", "d0d1c8a799996bf0265b98b5d48ab919"),
+];
+
+/// Known-answer self-test, for use as a guard at program start (a "power-on self-test").
+///
+/// Encrypts and decrypts fixed vectors with 128, 192 and 256-bit keys in ECB (FIPS-197), CTR (SP 800-38A)
+/// and GCM (the NIST GCM specification), through the same code paths as the public API, including any
+/// hardware acceleration selected on this CPU. Returns a [SelfTestFailed](crate::Error::SelfTestFailed) error
+/// naming the first mode whose output did not match.
+///
+/// ## Examples
+/// ```
+/// # fn main() -> aesp::Result<()> {
+/// aesp::self_test()?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn self_test() -> Result<()> {
+    let check = |ok: bool, context: &'static str| if ok { Ok(()) } else { Err(Error::SelfTestFailed { context }) };
+
+    let block = decode_hex("00112233445566778899aabbccddeeff")?;
+    for (len, expected) in [16u8, 24, 32].into_iter().zip(ECB) {
+        let key: Vec<u8> = (0..len).collect();
+        let cipher = Cipher::new(&Key::try_from_slice(&key)?);
+        let ct = cipher.encrypt_ecb(&block);
+        check(ct[..16] == decode_hex(expected)?, "ECB encryption")?;
+        check(cipher.decrypt_ecb(&ct)? == block, "ECB decryption")?;
+    }
+
+    let block = decode_hex("6bc1bee22e409f96e93d7e117393172a")?;
+    let iv: [u8; 12] = core::array::from_fn(|i| 0xf0 + i as u8);
+    for (key, expected) in CTR {
+        let cipher = Cipher::new(&Key::try_from_hex(key)?);
+        let ct = cipher.ctr_with_counter(&block, &iv, 0xfcfdfeff)?;
+        check(ct == decode_hex(expected)?, "CTR encryption")?;
+        check(cipher.ctr_with_counter(&ct, &iv, 0xfcfdfeff)? == block, "CTR decryption")?;
+    }
+
+    let block = [0u8; 16];
+    for (len, (expected_ct, expected_tag)) in [16, 24, 32].into_iter().zip(GCM) {
+        let cipher = Cipher::new(&Key::try_from_slice(&[0u8; 32][..len])?);
+        let out = cipher.encrypt_gcm_with_iv(&block, None, &[0u8; 12])?;
+        // IV (12) || AAD length (8) || Ciphertext (16) || Tag (16)
+        let (ct, tag) = out[20..].split_at(16);
+        check(ct == decode_hex(expected_ct)? && tag == decode_hex(expected_tag)?, "GCM encryption")?;
+        check(cipher.decrypt_gcm(&out).is_ok_and(|(pt, _)| pt == block), "GCM decryption")?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn passes_on_correct_build() {
+        self_test().unwrap();
+    }
+}
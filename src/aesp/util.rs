@@ -1,19 +1,209 @@
-use rand::TryRngCore;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use rand::TryCryptoRng;
+#[cfg(feature = "std")]
 use rand::rngs::OsRng;
 
 use crate::aesp::error::*;
+use crate::aesp::modes::Padding;
 
 /// Generate random 12-byte initialisation vector
+#[cfg(feature = "std")]
 pub(crate) fn random_iv() -> Result<[u8; 12]> {
-    let mut iv = [0u8; 12];
+    generate_iv_with_rng(&mut OsRng)
+}
+
+/// Generate random initialisation vector of any length, for modes that do not take a 12-byte IV (CBC, 64-bit CTR)
+#[cfg(feature = "std")]
+pub(crate) fn random_bytes<const N: usize>() -> Result<[u8; N]> {
+    use rand::TryRngCore;
+    let mut iv = [0u8; N];
     OsRng.try_fill_bytes(&mut iv)?;
     Ok(iv)
 }
 
-/// PKCS#7 padding for ECB (16-byte blocks)
-pub(crate) fn pad(plaintext: &[u8]) -> Vec<u8> {
+/// Generates a random 12-byte initialisation vector from a caller-supplied RNG, for use with the `_with_iv`
+/// functions (e.g. [encrypt_gcm_with_iv](crate::Cipher::encrypt_gcm_with_iv)). Returns Error if the RNG fails.
+///
+/// Accepts any (fallible or infallible) cryptographically secure RNG whose error converts into [Error].
+/// A seeded RNG makes encryption reproducible, which is useful in tests. Never reuse an IV under the same key.
+///
+/// ## Examples
+/// ```
+/// # fn main() -> aesp::Result<()> {
+/// use aesp::{Cipher, Key, KeySize, generate_iv_with_rng};
+/// use rand_chacha::ChaCha20Rng;
+/// use rand_chacha::rand_core::SeedableRng;
+///
+/// let mut rng = ChaCha20Rng::seed_from_u64(42);
+/// let cipher = Cipher::new(&Key::generate_with_rng(&mut rng, KeySize::Bits128)?);
+/// let iv = generate_iv_with_rng(&mut rng)?;
+/// let ciphertext = cipher.encrypt_gcm_with_iv(b"Hello, World!", None, &iv)?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn generate_iv_with_rng<R: TryCryptoRng + ?Sized>(rng: &mut R) -> Result<[u8; 12]>
+where
+    Error: From<R::Error>,
+{
+    let mut iv = [0u8; 12];
+    rng.try_fill_bytes(&mut iv)?;
+    Ok(iv)
+}
+
+/// `XOR`s `src` into `dst` in place, i.e. `dst[i] ^= src[i]`.
+///
+/// Operates over the minimum of the two lengths; any remaining bytes of the longer slice are left untouched.
+/// Processes 8 bytes at a time, which the compiler can further vectorise where SIMD is available.
+///
+/// ## Examples
+/// ```
+/// let mut dst = [0x0F, 0xF0, 0xAA, 0x55];
+/// aesp::xor_into(&mut dst, &[0xFF, 0xFF]);
+/// assert_eq!(dst, [0xF0, 0x0F, 0xAA, 0x55]);
+/// ```
+pub fn xor_into(dst: &mut [u8], src: &[u8]) {
+    let len = dst.len().min(src.len());
+    let (dst, src) = (&mut dst[..len], &src[..len]);
+
+    // xor full 8-byte words
+    let mut dst_words = dst.chunks_exact_mut(8);
+    let mut src_words = src.chunks_exact(8);
+    for (d, s) in (&mut dst_words).zip(&mut src_words) {
+        let x = u64::from_ne_bytes(d.try_into().unwrap()) ^ u64::from_ne_bytes(s.try_into().unwrap());
+        d.copy_from_slice(&x.to_ne_bytes());
+    }
+
+    // xor remaining bytes
+    for (d, s) in dst_words.into_remainder().iter_mut().zip(src_words.remainder()) {
+        *d ^= s;
+    }
+}
+
+/// Returns true if any aligned 16-byte block of `data` repeats, the signature of ECB encryption: under ECB, equal
+/// plaintext blocks always encrypt to equal ciphertext blocks, whereas CTR, GCM and CBC output repeats a block
+/// with negligible probability. Any trailing partial block is ignored.
+///
+/// Intended for security scanning, or warning users who choose ECB. A false result does not rule out ECB, as
+/// the plaintext may simply not have repeated blocks. The blocks are sorted rather than hashed, taking
+/// `O(n log n)` time with no hashing, but the running time depends on the data, so only scan ciphertext or
+/// other non-secret data with it.
+///
+/// ## Examples
+/// ```
+/// # fn main() -> aesp::Result<()> {
+/// use aesp::{Key, Cipher, looks_like_ecb};
+///
+/// let cipher = Cipher::new(&Key::rand_key_256()?);
+/// let plaintext = [0x42; 64]; // four identical blocks
+/// assert!(looks_like_ecb(&cipher.encrypt_ecb(&plaintext)));
+/// assert!(!looks_like_ecb(&cipher.encrypt_ctr(&plaintext)?));
+/// # Ok(())
+/// # }
+/// ```
+pub fn looks_like_ecb(data: &[u8]) -> bool {
+    let mut blocks: Vec<&[u8]> = data.chunks_exact(16).collect();
+    blocks.sort_unstable();
+    blocks.windows(2).any(|pair| pair[0] == pair[1])
+}
+
+/// `std::sync::OnceLock` for builds without std, so that types caching derived keys stay `Sync` with or
+/// without the feature. Provides the subset of `OnceLock`'s interface used by this crate, over `spin::Once`.
+#[cfg(not(feature = "std"))]
+pub(crate) struct OnceLock<T>(spin::Once<T>);
+
+#[cfg(not(feature = "std"))]
+impl<T> OnceLock<T> {
+    pub(crate) const fn new() -> Self {
+        Self(spin::Once::new())
+    }
+
+    pub(crate) fn get(&self) -> Option<&T> {
+        self.0.get()
+    }
+
+    pub(crate) fn get_mut(&mut self) -> Option<&mut T> {
+        self.0.get_mut()
+    }
+
+    pub(crate) fn get_or_init(&self, f: impl FnOnce() -> T) -> &T {
+        self.0.call_once(f)
+    }
+
+    pub(crate) fn take(&mut self) -> Option<T> {
+        core::mem::take(&mut self.0).try_into_inner()
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl<T> From<T> for OnceLock<T> {
+    fn from(value: T) -> Self {
+        Self(spin::Once::from(value))
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl<T: Clone> Clone for OnceLock<T> {
+    fn clone(&self) -> Self {
+        match self.get() {
+            Some(value) => Self::from(value.clone()),
+            None => Self::new(),
+        }
+    }
+}
+
+/// Overwrites a buffer with zeros using volatile writes, which the optimiser cannot elide even if the
+/// buffer is never read again (e.g. immediately before it is dropped).
+pub(crate) fn zeroize(buf: &mut [u8]) {
+    for b in buf.iter_mut() {
+        // SAFETY: `b` is a valid, aligned, exclusive reference to a u8
+        unsafe { core::ptr::write_volatile(b, 0) };
+    }
+    // prevent subsequent memory operations being reordered before the wipe
+    core::sync::atomic::compiler_fence(core::sync::atomic::Ordering::SeqCst);
+}
+
+/// Constant-time equality check. Every byte is compared regardless of where the first difference occurs,
+/// so the running time does not reveal how much of a secret value an attacker has guessed correctly.
+#[inline(never)]
+pub(crate) fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false; // lengths are public
+    }
+
+    // accumulate differences without branching on secret data
+    let mut diff = 0u8;
+    for (x, y) in a.iter().zip(b) {
+        diff |= x ^ y;
+    }
+
+    // prevent the compiler from reasoning about diff and reintroducing an early exit
+    core::hint::black_box(diff) == 0
+}
+
+/// Returns `0xFF` if `a == b`, otherwise `0x00`, without branching.
+#[inline(always)]
+fn ct_eq_u8(a: u8, b: u8) -> u8 {
+    // a ^ b is 0 only if equal, and 0 - 1 is the only case that borrows into the high byte
+    (((a ^ b) as u16).wrapping_sub(1) >> 8) as u8
+}
+
+/// Returns `0xFF` if `a < b`, otherwise `0x00`, without branching. `a` must be below 256, and `b` at most 256.
+#[inline(always)]
+fn ct_lt(a: usize, b: usize) -> u8 {
+    debug_assert!(a < 256 && b <= 256);
+    // a - b borrows into the high byte only if a < b
+    ((a as u16).wrapping_sub(b as u16) >> 8) as u8
+}
+
+/// Pad to a multiple of 16 bytes using the given scheme
+pub(crate) fn pad(plaintext: &[u8], padding: Padding) -> Vec<u8> {
     let rem = plaintext.len() % 16;
-    let pad_len = if rem == 0 { 16 } else { 16 - rem };
+    let pad_len = match (padding, rem) {
+        (Padding::Zero, 0) => 0,
+        _ => 16 - rem,
+    };
 
     let total_len = plaintext
         .len()
@@ -22,12 +212,24 @@ pub(crate) fn pad(plaintext: &[u8]) -> Vec<u8> {
 
     let mut out = vec![0u8; total_len];
     out[..plaintext.len()].copy_from_slice(plaintext);
-    out[plaintext.len()..].fill(pad_len as u8);
+    match padding {
+        Padding::Pkcs7 => out[plaintext.len()..].fill(pad_len as u8),
+        Padding::AnsiX923 => out[total_len - 1] = pad_len as u8,
+        Padding::Iso7816 => out[plaintext.len()] = 0x80,
+        Padding::Zero => {}
+    }
     out
 }
 
-/// Remove and validate PKCS#7 padding
-pub(crate) fn unpad(input: &mut Vec<u8>) -> Result<()> {
+/// Remove and validate padding of the given scheme
+pub(crate) fn unpad(input: &mut Vec<u8>, padding: Padding) -> Result<()> {
+    if padding == Padding::Zero {
+        // cannot be validated, strips every trailing zero
+        let end = input.iter().rposition(|&b| b != 0).map_or(0, |i| i + 1);
+        input.truncate(end);
+        return Ok(());
+    }
+
     if input.is_empty() {
         return Err(Error::InvalidCiphertext {
             len: 0,
@@ -35,23 +237,293 @@ pub(crate) fn unpad(input: &mut Vec<u8>) -> Result<()> {
         });
     }
 
-    // safe unwrap, confirmed non empty
-    let pad = *input.last().unwrap() as usize;
-    if pad == 0 || pad > 16 || pad > input.len() {
-        return Err(Error::InvalidCiphertext {
-            len: input.len(),
-            context: "Unpad: invalid padding length specified by last byte",
-        });
-    }
+    // validate in constant time: every byte of the final block is inspected whatever its value, folding the
+    // result into a mask, and the only branch is on the final result. Otherwise the timing of decryption
+    // would reveal how the padding was malformed, which is enough for a padding oracle attack.
+    let tail = &input[input.len().saturating_sub(16)..];
+    let t = tail.len();
+    let last = tail[t - 1];
 
-    let start = input.len() - pad;
-    if !input[start..].iter().all(|&b| b as usize == pad) {
+    let (valid, pad) = match padding {
+        Padding::Pkcs7 | Padding::AnsiX923 => {
+            // padding length is the final byte, and must be 1..=16 and within the input
+            let mut valid = !ct_eq_u8(last, 0) & ct_lt(last as usize, t + 1);
+            // the final byte is the length itself, other padding bytes repeat it (PKCS#7) or are zero (X9.23)
+            let fill = if padding == Padding::Pkcs7 { last } else { 0 };
+            for i in 1..t {
+                let in_pad = ct_lt(i, last as usize);
+                valid &= !in_pad | ct_eq_u8(tail[t - 1 - i], fill);
+            }
+            (valid, last)
+        }
+        Padding::Iso7816 => {
+            // padding ends at the last non-zero byte, which must be the 0x80 marker
+            let (mut found, mut valid, mut pad) = (0u8, 0u8, 0u8);
+            for i in 0..t {
+                let b = tail[t - 1 - i];
+                let first = !found & !ct_eq_u8(b, 0);
+                pad |= (i as u8 + 1) & first;
+                valid |= first & ct_eq_u8(b, 0x80);
+                found |= first;
+            }
+            (valid, pad)
+        }
+        Padding::Zero => unreachable!("zero padding handled above"),
+    };
+    if valid != 0xFF {
         return Err(Error::InvalidCiphertext {
             len: input.len(),
-            context: "Unpad: invalid PKCS#7 padding format",
+            context: "Unpad: invalid padding",
         });
     }
 
+    let start = input.len() - pad as usize;
     input.truncate(start);
     Ok(())
 }
+
+/// Decode a hex string into bytes. Whitespace is ignored, and upper or lower case digits are accepted.
+pub(crate) fn decode_hex(s: &str) -> Result<Vec<u8>> {
+    let digits: Vec<u8> = s.bytes().filter(|b| !b.is_ascii_whitespace()).collect();
+    if !digits.len().is_multiple_of(2) {
+        return Err(Error::InvalidHex {
+            len: digits.len(),
+            context: "odd number of hex digits",
+        });
+    }
+
+    let nibble = |d: u8| match d {
+        b'0'..=b'9' => Ok(d - b'0'),
+        b'a'..=b'f' => Ok(d - b'a' + 10),
+        b'A'..=b'F' => Ok(d - b'A' + 10),
+        _ => Err(Error::InvalidHex {
+            len: digits.len(),
+            context: "non-hex character",
+        }),
+    };
+
+    digits
+        .chunks_exact(2)
+        .map(|pair| Ok((nibble(pair[0])? << 4) | nibble(pair[1])?))
+        .collect()
+}
+
+/// Encode bytes as a lowercase hex string.
+pub(crate) fn encode_hex(bytes: &[u8]) -> String {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+    let mut out = String::with_capacity(bytes.len() * 2);
+    for &b in bytes {
+        out.push(DIGITS[(b >> 4) as usize] as char);
+        out.push(DIGITS[(b & 0x0f) as usize] as char);
+    }
+    out
+}
+
+/// Encodes bytes as a base64 string (standard alphabet, with padding). Requires the `base64` feature.
+///
+/// ## Examples
+/// ```
+/// # fn main() -> aesp::Result<()> {
+/// use aesp::{decode_b64, encode_b64};
+///
+/// assert_eq!(encode_b64(b"Hello"), "SGVsbG8=");
+/// assert_eq!(decode_b64("SGVsbG8=")?, b"Hello");
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "base64")]
+pub fn encode_b64(bytes: &[u8]) -> String {
+    use base64::Engine;
+    base64::engine::general_purpose::STANDARD.encode(bytes)
+}
+
+/// Decodes a base64 string (standard alphabet, with padding) into bytes. Returns an InvalidBase64 error if
+/// the input is malformed. Requires the `base64` feature.
+#[cfg(feature = "base64")]
+pub fn decode_b64(s: &str) -> Result<Vec<u8>> {
+    use base64::{DecodeError, Engine};
+    base64::engine::general_purpose::STANDARD
+        .decode(s)
+        .map_err(|e| Error::InvalidBase64 {
+            len: s.len(),
+            context: match e {
+                DecodeError::InvalidByte(..) => "invalid character",
+                DecodeError::InvalidLength(_) => "invalid length",
+                DecodeError::InvalidLastSymbol(..) => "invalid trailing bits",
+                DecodeError::InvalidPadding => "invalid padding",
+            },
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_repeated_blocks() {
+        // SP 800-38A ECB-AES128 ciphertext of one block, repeated at a distance
+        let block = decode_hex("3ad77bb40d7a3660a89ecaf32466ef97").unwrap();
+        let mut data = block.clone();
+        data.extend_from_slice(&[0x11; 48]);
+        data.extend_from_slice(&block);
+        assert!(looks_like_ecb(&data));
+
+        // distinct blocks, blocks that only repeat unaligned, and inputs too short to repeat
+        let distinct: Vec<u8> = (0..=255).collect();
+        assert!(!looks_like_ecb(&distinct));
+        assert!(!looks_like_ecb(&[&[0u8][..], &block, &block[..15]].concat()));
+        assert!(!looks_like_ecb(&[0u8; 31]));
+        assert!(!looks_like_ecb(&[]));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn random_data_does_not_look_like_ecb() -> Result<()> {
+        for _ in 0..16 {
+            assert!(!looks_like_ecb(&random_bytes::<4096>()?));
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "base64")]
+    #[test]
+    fn b64_round_trip_and_errors() -> Result<()> {
+        // RFC 4648 section 10 test vectors
+        for (plain, encoded) in [("", ""), ("f", "Zg=="), ("fo", "Zm8="), ("foo", "Zm9v"), ("foobar", "Zm9vYmFy")] {
+            assert_eq!(encode_b64(plain.as_bytes()), encoded);
+            assert_eq!(decode_b64(encoded)?, plain.as_bytes());
+        }
+
+        for (s, context) in [("Zm9v!", "invalid character"), ("Zm9vY", "invalid length"), ("Zh==", "invalid trailing bits"), ("Zg", "invalid padding")] {
+            assert!(
+                matches!(decode_b64(s), Err(Error::InvalidBase64 { context: c, .. }) if c == context),
+                "{s}: {:?}",
+                decode_b64(s)
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn zeroize_clears_buffer() {
+        let mut buf = [0xA5u8; 37];
+        zeroize(&mut buf);
+        assert_eq!(buf, [0u8; 37]);
+    }
+
+    #[test]
+    fn ct_eq_compares_all_bytes() {
+        assert!(ct_eq(&[1, 2, 3], &[1, 2, 3]));
+        assert!(ct_eq(&[], &[]));
+        assert!(!ct_eq(&[1, 2, 3], &[1, 2, 4]));
+        assert!(!ct_eq(&[0, 2, 3], &[1, 2, 3]));
+        assert!(!ct_eq(&[1, 2, 3], &[1, 2]));
+    }
+
+    #[test]
+    fn xor_into_equal_lengths() {
+        let mut dst: Vec<u8> = (0..37).collect();
+        let src: Vec<u8> = (0..37).map(|i| i * 7).collect();
+        let expected: Vec<u8> = dst.iter().zip(&src).map(|(a, b)| a ^ b).collect();
+
+        xor_into(&mut dst, &src);
+        assert_eq!(dst, expected);
+    }
+
+    #[test]
+    fn xor_into_unequal_lengths() {
+        // shorter src leaves tail of dst untouched
+        let mut dst = [0xFFu8; 20];
+        xor_into(&mut dst, &[0x0F; 9]);
+        assert_eq!(dst[..9], [0xF0; 9]);
+        assert_eq!(dst[9..], [0xFF; 11]);
+
+        // shorter dst ignores tail of src
+        let mut dst = [0x00u8; 3];
+        xor_into(&mut dst, &[0x01, 0x02, 0x03, 0x04, 0x05]);
+        assert_eq!(dst, [0x01, 0x02, 0x03]);
+
+        // empty input is a no-op
+        let mut dst = [0xAAu8; 4];
+        xor_into(&mut dst, &[]);
+        assert_eq!(dst, [0xAA; 4]);
+    }
+
+    #[test]
+    fn padding_schemes() {
+        let cases = [
+            (Padding::Pkcs7, &[3u8, 3, 3][..]),
+            (Padding::AnsiX923, &[0, 0, 3]),
+            (Padding::Iso7816, &[0x80, 0, 0]),
+            (Padding::Zero, &[0, 0, 0]),
+        ];
+        for (padding, tail) in cases {
+            let padded = pad(&[0xAB; 13], padding);
+            assert_eq!(padded[..13], [0xAB; 13], "{padding:?}");
+            assert_eq!(&padded[13..], tail, "{padding:?}");
+
+            let mut unpadded = padded;
+            unpad(&mut unpadded, padding).unwrap();
+            assert_eq!(unpadded, [0xAB; 13], "{padding:?}");
+
+            // aligned input gains a full block, except with zero padding
+            let expected_len = if padding == Padding::Zero { 16 } else { 32 };
+            assert_eq!(pad(&[0xAB; 16], padding).len(), expected_len, "{padding:?}");
+        }
+    }
+
+    #[test]
+    fn unpad_all_lengths() {
+        for padding in [Padding::Pkcs7, Padding::AnsiX923, Padding::Iso7816] {
+            for len in 0..=32 {
+                let mut padded = pad(&vec![0xAB; len], padding);
+                unpad(&mut padded, padding).unwrap();
+                assert_eq!(padded, vec![0xAB; len], "{padding:?} {len}");
+            }
+        }
+    }
+
+    #[test]
+    fn unpad_rejects_over_long_padding() {
+        // lengths beyond a block, beyond the input, and the largest byte value
+        for pad_byte in [17u8, 32, 0xFF] {
+            for padding in [Padding::Pkcs7, Padding::AnsiX923] {
+                let mut block = vec![pad_byte; 16];
+                assert!(unpad(&mut block, padding).is_err(), "{padding:?} {pad_byte}");
+            }
+        }
+        assert!(unpad(&mut vec![5u8; 4], Padding::Pkcs7).is_err());
+        assert!(unpad(&mut vec![0, 0, 0, 5], Padding::AnsiX923).is_err());
+        assert!(unpad(&mut vec![], Padding::Pkcs7).is_err());
+
+        // full block of padding, on a short input that is all padding
+        let mut block = vec![4u8; 4];
+        unpad(&mut block, Padding::Pkcs7).unwrap();
+        assert!(block.is_empty());
+    }
+
+    #[test]
+    fn unpad_rejects_malformed_padding() {
+        let mut block = [0xAB; 16];
+        let cases = [
+            (Padding::Pkcs7, [0x02, 0x03]),
+            (Padding::Pkcs7, [0x00, 0x00]),
+            (Padding::Pkcs7, [0x11, 0x11]),
+            (Padding::AnsiX923, [0x01, 0x02]),
+            (Padding::AnsiX923, [0x00, 0x11]),
+            (Padding::Iso7816, [0x81, 0x00]),
+            (Padding::Iso7816, [0x80, 0x01]),
+        ];
+        for (padding, tail) in cases {
+            block[14..].copy_from_slice(&tail);
+            let result = unpad(&mut block.to_vec(), padding);
+            assert!(matches!(result, Err(Error::InvalidCiphertext { .. })), "{padding:?} {tail:02x?}");
+        }
+
+        // ISO/IEC 7816-4 marker must be within the final block
+        let mut block = vec![0x80];
+        block.extend([0u8; 16]);
+        assert!(unpad(&mut block, Padding::Iso7816).is_err());
+        assert!(unpad(&mut vec![0u8; 16], Padding::Iso7816).is_err());
+    }
+}
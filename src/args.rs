@@ -14,10 +14,9 @@ pub enum Commands {
     Encrypt(EncryptArgs),
 
     /// Decrypt input to output
-    Decrypt(CommonArgs),
+    Decrypt(DecryptArgs),
 }
 
-
 #[derive(Args, Debug)]
 #[command(arg_required_else_help = true)]
 pub struct CommonArgs {
@@ -30,18 +29,32 @@ pub struct CommonArgs {
     )]
     pub mode: Mode,
 
-    /// Input file path.
+    /// Encoding of the ciphertext, written when encrypting and expected when decrypting.
+    #[arg(long = "format", value_enum, default_value_t = Format::Raw)]
+    pub format: Format,
+
+    /// Input file path. Reads stdin if omitted or `-`.
     #[arg(short = 'i', long = "input")]
-    pub input: PathBuf,
+    pub input: Option<PathBuf>,
 
-    /// Output file path.
+    /// Output file path. Writes stdout if omitted or `-`.
     #[arg(short = 'o', long = "output")]
-    pub output: PathBuf,
+    pub output: Option<PathBuf>,
 
     /// Key file path.
-    #[arg(short = 'k', long = "key")]
-    pub key: PathBuf,
+    #[arg(short = 'k', long = "key", required_unless_present = "password")]
+    pub key: Option<PathBuf>,
 
+    /// Derive the key from a password, prompting for it if no value is given. A value passed on the
+    /// command line is visible to other users of the system.
+    #[arg(
+        short = 'p',
+        long = "password",
+        value_name = "PASSWORD",
+        num_args = 0..=1,
+        conflicts_with = "key"
+    )]
+    pub password: Option<Option<String>>,
 }
 
 #[derive(Args, Debug)]
@@ -51,7 +64,7 @@ pub struct EncryptArgs {
     pub common: CommonArgs,
 
     /// Generate a random key (written to path specified by key)
-    #[arg(long = "gen-key")]
+    #[arg(long = "gen-key", conflicts_with = "password")]
     pub gen_key: bool,
 
     /// Only valid with --gen-key.
@@ -63,9 +76,29 @@ pub struct EncryptArgs {
     )]
     pub key_size: KeySize,
 
+    /// PBKDF2 iteration count [default: 600000]. Only valid with --password.
+    // clap waives `requires` when the required arg conflicts with one present, so --key is excluded directly
+    #[arg(long = "iterations", requires = "password", conflicts_with = "key")]
+    pub iterations: Option<u32>,
+
     /// Additional authenticated data, provided as hex string (optional, GCM only)
     #[arg(long = "aad", value_name = "HEX")]
     pub aad: Option<String>,
+
+    /// Read additional authenticated data from a file (optional, GCM only)
+    #[arg(long = "aad-file", value_name = "PATH", conflicts_with = "aad")]
+    pub aad_file: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+#[command(arg_required_else_help = true)]
+pub struct DecryptArgs {
+    #[command(flatten)]
+    pub common: CommonArgs,
+
+    /// Write the recovered additional authenticated data to a file instead of printing it (GCM only)
+    #[arg(long = "aad-out", value_name = "PATH")]
+    pub aad_out: Option<PathBuf>,
 }
 
 #[derive(Copy, Clone, Debug, ValueEnum, Eq, PartialEq)]
@@ -78,7 +111,18 @@ pub enum KeySize {
     Bits256,
 }
 
+impl From<KeySize> for aesp::KeySize {
+    fn from(size: KeySize) -> Self {
+        match size {
+            KeySize::Bits128 => aesp::KeySize::Bits128,
+            KeySize::Bits192 => aesp::KeySize::Bits192,
+            KeySize::Bits256 => aesp::KeySize::Bits256,
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug, ValueEnum, Eq, PartialEq)]
+#[allow(clippy::enum_variant_names)]
 pub enum Mode {
     #[value(name = "ecb")]
     ModeECB,
@@ -87,3 +131,13 @@ pub enum Mode {
     #[value(name = "gcm")]
     ModeGCM,
 }
+
+#[derive(Copy, Clone, Debug, ValueEnum, Eq, PartialEq)]
+pub enum Format {
+    #[value(name = "raw")]
+    Raw,
+    #[value(name = "hex")]
+    Hex,
+    #[value(name = "base64")]
+    Base64,
+}
@@ -1,13 +1,13 @@
 //! This crate provides an intuitive interface for AES-128, AES-192, and AES-256 encryption and decryption.
 //! The following modes of operation are supported:
 //! - **Galois/counter mode** ([GCM](crate::Cipher::encrypt_gcm)), with optional additional authenticated data (AAD). 
-//! Encrypts using CTR mode and generates an authentication tag from the AAD + ciphertext. This tag is recomputed at decryption 
-//! and compared with the received tag.
+//!   Encrypts using CTR mode and generates an authentication tag from the AAD + ciphertext. This tag is recomputed at decryption 
+//!   and compared with the received tag.
 //! - **Counter mode** ([CTR](crate::Cipher::encrypt_ctr)). A 16-byte counter is repeatedly incremented and encrypted. 
-//! The result is `XOR`'d with the plaintext to produce the ciphertext. 
-//! This turns AES into a stream cipher, which removes vulnerabilities present in modes such as ECB.  
+//!   The result is `XOR`'d with the plaintext to produce the ciphertext. 
+//!   This turns AES into a stream cipher, which removes vulnerabilities present in modes such as ECB.  
 //! - **Electronic codebook mode** ([ECB](crate::Cipher::encrypt_ecb)). Encrypts each block of plaintext seperately and appends to the output. 
-//! Vulnerable to pattern emergence in larger inputs. Use a stream cipher mode (CTR or GCM) if security is important. 
+//!   Vulnerable to pattern emergence in larger inputs. Use a stream cipher mode (CTR or GCM) if security is important. 
 //! 
 //! ## Examples
 //! Below is an example of a string being encrypted under a random key using AES-256-CTR, then decrypted back to plaintext.
@@ -37,7 +37,28 @@
 //! # Ok(())
 //! # }
 //! ```
+//!
+//! ## `no_std`
+//! The `std` feature is enabled by default. Without it, the crate is `no_std` and only requires `alloc`.
+//! Everything that needs the OS RNG ([Key::generate], [encrypt_gcm](crate::Cipher::encrypt_gcm), etc.) is
+//! unavailable; use [Key::generate_with_rng], [generate_iv_with_rng] and the `_with_iv` functions with your own entropy source
+//! instead.
+//! All processing is serial, and AES-NI/PCLMULQDQ are only used when enabled at compile time
+//! (e.g. `-C target-feature=+aes,+pclmulqdq`).
+
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
 
 mod aesp;
 
-pub use aesp::{Cipher, Error, Key, Result};
+pub use aesp::{
+    Aead, Cipher, CtrStream, Error, ErrorKind, Gcm, GcmDecryptor, GcmEncryptor, GcmFrame, GcmOutput, GcmSiv, Key, KeySize,
+    Mode, NonceSequence, Padding, Result, generate_iv_with_rng, looks_like_ecb, parse_gcm, self_test, xor_into,
+};
+#[cfg(feature = "base64")]
+pub use aesp::{decode_b64, encode_b64};
+#[cfg(feature = "bench")]
+pub use aesp::bench_block_throughput;
+#[cfg(feature = "std")]
+pub use aesp::{DecryptReader, EncryptWriter, RatchetCipher};
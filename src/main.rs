@@ -1,21 +1,36 @@
 mod args;
+mod password;
+mod stream;
 
 use args::{Cli, Commands};
 use clap::Parser;
 
 use std::fs;
+use std::io::{self, Read, Write};
 use std::time::Instant;
 
 use thiserror::Error;
 
 #[derive(Debug, Error)]
 pub enum CliError {
-    #[error("--aad is only valid with --mode gcm")]
+    #[error("--aad, --aad-file and --aad-out are only valid with --mode gcm")]
     AadInvalidMode,
 
     #[error("invalid --aad hex: {0}")]
     AadInvalidHex(#[from] std::num::ParseIntError),
 
+    #[error("input is not valid {0}")]
+    InvalidEncoding(&'static str),
+
+    #[error("passwords do not match")]
+    PasswordMismatch,
+
+    #[error("input is not password-encrypted")]
+    NotPasswordEncrypted,
+
+    #[error("random number generator failure: {0}")]
+    Rng(#[from] rand::rand_core::OsError),
+
     #[error(transparent)]
     Io(#[from] std::io::Error),
 
@@ -24,8 +39,11 @@ pub enum CliError {
 }
 
 fn main() {
-    if let Err(e) = aes_cli() {
-        eprintln!("error: {e}");
+    match aes_cli() {
+        Ok(()) => {}
+        // library errors carry internal detail intended for logs rather than users
+        Err(CliError::Aes(e)) => eprintln!("error: {}", e.user_message()),
+        Err(e) => eprintln!("error: {e}"),
     }
 }
 
@@ -37,107 +55,171 @@ fn aes_cli() -> Result<(), CliError> {
             // common args:
             let input_path = enc.common.input; // move ownership
             let output_path = enc.common.output;
-            let key_path = enc.common.key;
             let mode = enc.common.mode;
+            let format = enc.common.format;
 
-            // read plaintext from input_path
-            let plaintext = fs::read(input_path)?;
-
-            // read or generate key
-            let key = if enc.gen_key {
-                let rand_key = match enc.key_size {
-                    args::KeySize::Bits128 => aesp::Key::rand_key_128()?,
-                    args::KeySize::Bits192 => aesp::Key::rand_key_192()?,
-                    args::KeySize::Bits256 => aesp::Key::rand_key_256()?,
-                };
-                fs::write(key_path, &rand_key.as_bytes())?;
-                rand_key
+            // derive, generate, or read key
+            let (key, params) = if let Some(password) = enc.common.password {
+                let password = password::get_password(password, true)?;
+                let params = password::Params::generate(enc.iterations.unwrap_or(password::DEFAULT_ITERATIONS))?;
+                (params.derive_key(&password)?, Some(params))
             } else {
-                // read key from key_path
-                let key_bytes = fs::read(key_path)?;
-                aesp::Key::try_from_slice(&key_bytes)?
+                // clap requires --key when --password is absent
+                let key_path = enc.common.key.unwrap();
+                if enc.gen_key {
+                    let rand_key = aesp::Key::generate(enc.key_size.into())?;
+                    fs::write(key_path, rand_key.as_bytes())?;
+                    (rand_key, None)
+                } else {
+                    // read key from key_path
+                    let key_bytes = fs::read(key_path)?;
+                    (aesp::Key::try_from_slice(&key_bytes)?, None)
+                }
             };
 
             let cipher = aesp::Cipher::new(&key);
 
-            // parse AAD
-            let aad: Option<Vec<u8>> = match enc.aad {
-                Some(aad_str) => {
-                    if mode != args::Mode::ModeGCM {
-                        return Err(CliError::AadInvalidMode);
-                    }
-                    Some(parse_aad(&aad_str)?)
-                }
-                None => None,
+            // parse or read AAD
+            let aad: Option<Vec<u8>> = match (enc.aad, enc.aad_file) {
+                (None, None) => None,
+                _ if mode != args::Mode::ModeGCM => return Err(CliError::AadInvalidMode),
+                (Some(aad_str), _) => Some(parse_aad(&aad_str)?),
+                (None, Some(aad_path)) => Some(fs::read(aad_path)?),
             };
 
-            let start = Instant::now();
+            let mut input = stream::open_input(&input_path)?;
+            let mut output = stream::open_output(&output_path)?;
 
-            // encrypt plaintext and write output
-            let ciphertext = match mode {
-                args::Mode::ModeECB => cipher.encrypt_ecb(&plaintext),
-                args::Mode::ModeCTR => cipher.encrypt_ctr(&plaintext)?,
-                args::Mode::ModeGCM => cipher.encrypt_gcm(&plaintext, aad.as_deref())?,
+            // hex and base64 output is collected and encoded once encryption is complete
+            let mut collected = Vec::new();
+            let sink: &mut dyn Write = match format {
+                args::Format::Raw => &mut output,
+                _ => &mut collected,
             };
 
-            let duration = start.elapsed();
+            // salt and iteration count go in front of the ciphertext so decryption can re-derive the key
+            if let Some(params) = params {
+                params.write_header(sink)?;
+            }
 
-            fs::write(output_path, &ciphertext)?;
-            println!(
-                "Encrypted {} bytes in {} ms",
-                plaintext.len(),
-                duration.as_millis()
-            );
+            let start = Instant::now();
+
+            // stream plaintext from input, through the cipher, to output
+            let len = match mode {
+                args::Mode::ModeECB => stream::ecb_encrypt(&cipher, &mut input, sink)?,
+                args::Mode::ModeCTR => {
+                    let mut writer = aesp::EncryptWriter::ctr(&cipher, &mut *sink)?;
+                    let len = io::copy(&mut input, &mut writer)?;
+                    writer.finish()?;
+                    len
+                }
+                args::Mode::ModeGCM => {
+                    let mut writer = aesp::EncryptWriter::gcm(&cipher, &mut *sink, aad.as_deref())?;
+                    let len = io::copy(&mut input, &mut writer)?;
+                    writer.finish()?;
+                    len
+                }
+            };
+            if format != args::Format::Raw {
+                stream::write_encoded(format, &collected, &mut output)?;
+            }
+            output.flush()?;
+
+            eprintln!("Encrypted {}", throughput(len, start));
             Ok(())
         }
-        Commands::Decrypt(common) => {
+        Commands::Decrypt(dec) => {
+            let common = dec.common;
+            if dec.aad_out.is_some() && common.mode != args::Mode::ModeGCM {
+                return Err(CliError::AadInvalidMode);
+            }
+
             let input_path = common.input; // move ownership
             let output_path = common.output;
-            let key_path = common.key;
             let mode = common.mode;
 
-            // read inputs
-            let ciphertext = fs::read(input_path)?;
-            let key_bytes = fs::read(key_path)?;
-            let key = aesp::Key::try_from_slice(&key_bytes)?;
-
-            let cipher = aesp::Cipher::new(&key);
-
-            let start = Instant::now();
+            let mut input = stream::decode_input(common.format, stream::open_input(&input_path)?)?;
 
-            // decrypt ciphertext and write output
-            let (plaintext, aad) = match mode {
-                args::Mode::ModeECB => (cipher.decrypt_ecb(&ciphertext)?, None),
-                args::Mode::ModeCTR => (cipher.decrypt_ctr(&ciphertext)?, None),
-                args::Mode::ModeGCM => cipher.decrypt_gcm(&ciphertext)?,
+            // read key, or re-derive it from the password and the input header
+            let key = if let Some(password) = common.password {
+                let params = password::Params::read_header(&mut input)?;
+                params.derive_key(&password::get_password(password, false)?)?
+            } else {
+                // clap requires --key when --password is absent
+                let key_bytes = fs::read(common.key.unwrap())?;
+                aesp::Key::try_from_slice(&key_bytes)?
             };
 
-            let duration = start.elapsed();
+            let cipher = aesp::Cipher::new(&key);
 
-            fs::write(output_path, &plaintext)?;
+            let output = stream::open_output(&output_path)?;
 
-            match aad {
-                Some(aad) => {
-                    print!("AAD = ");
-                    for b in &aad {
-                        print!("{:02x}", b);
-                    }
-                    println!();
+            let start = Instant::now();
+
+            // GCM plaintext is written before the tag at the end of the input has been checked, so on any
+            // failure an output file is removed rather than left holding unauthenticated data
+            let result = decrypt(&cipher, mode, input, output);
+            if result.is_err()
+                && let Some(path) = stream::file_path(&output_path)
+            {
+                let _ = fs::remove_file(path);
+            }
+            let (len, aad) = result?;
+
+            // informational output goes to stderr, so it cannot corrupt piped output
+            if let Some(aad_path) = dec.aad_out {
+                fs::write(aad_path, aad.unwrap_or_default())?;
+            } else if let Some(aad) = aad {
+                eprint!("AAD = ");
+                for b in &aad {
+                    eprint!("{:02x}", b);
                 }
-                None => {}
+                eprintln!();
             }
 
-            println!(
-                "Decrypted {} bytes in {} ms",
-                plaintext.len(),
-                duration.as_millis()
-            );
+            eprintln!("Decrypted {}", throughput(len, start));
 
             Ok(())
         }
     }
 }
 
+/// Streams ciphertext from input, through the cipher, to output. Returns the plaintext length, and the AAD
+/// for GCM.
+fn decrypt(
+    cipher: &aesp::Cipher,
+    mode: args::Mode,
+    mut input: Box<dyn Read>,
+    mut output: Box<dyn Write>,
+) -> Result<(u64, Option<Vec<u8>>), CliError> {
+    let result = match mode {
+        args::Mode::ModeECB => (stream::ecb_decrypt(cipher, &mut input, &mut output)?, None),
+        args::Mode::ModeCTR => {
+            let mut reader = aesp::DecryptReader::ctr(cipher, input);
+            (io::copy(&mut reader, &mut output)?, None)
+        }
+        args::Mode::ModeGCM => {
+            let mut reader = aesp::DecryptReader::gcm(cipher, input);
+            let len = io::copy(&mut reader, &mut output)?;
+            (len, reader.aad()?.map(<[u8]>::to_vec))
+        }
+    };
+    output.flush()?;
+    Ok(result)
+}
+
+/// Formats a byte count with the time taken and throughput since `start`.
+fn throughput(len: u64, start: Instant) -> String {
+    let duration = start.elapsed();
+    let mib_per_sec = len as f64 / (1024.0 * 1024.0) / duration.as_secs_f64().max(1e-9);
+    format!(
+        "{} bytes in {} ms ({:.1} MiB/s)",
+        len,
+        duration.as_millis(),
+        mib_per_sec
+    )
+}
+
 // parse_aad written with LLM assistance:
 fn parse_aad(s: &str) -> Result<Vec<u8>, std::num::ParseIntError> {
     let mut hex: String = s.chars().filter(|c| !c.is_whitespace()).collect();
@@ -0,0 +1,77 @@
+//! Password-based encryption for the CLI.
+//!
+//! The key is derived with PBKDF2-HMAC-SHA256 from the password and a random salt. The salt and iteration
+//! count are written in front of the ciphertext so that decryption can derive the same key:
+//! `MAGIC (4 bytes) || Iterations (4 bytes) || Salt (16 bytes) || Ciphertext`
+
+use std::io::{Read, Write};
+
+use aesp::{Key, KeySize};
+use rand::TryRngCore;
+use rand::rngs::OsRng;
+
+use crate::CliError;
+
+/// Identifies password-encrypted output.
+const MAGIC: [u8; 4] = *b"APW1";
+const SALT_LEN: usize = 16;
+
+/// Default PBKDF2 iteration count, following the OWASP recommendation for PBKDF2-HMAC-SHA256.
+pub const DEFAULT_ITERATIONS: u32 = 600_000;
+
+/// Salt and iteration count stored in the output header.
+pub struct Params {
+    salt: [u8; SALT_LEN],
+    iterations: u32,
+}
+
+impl Params {
+    /// Fresh parameters with a random salt.
+    pub fn generate(iterations: u32) -> Result<Self, CliError> {
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.try_fill_bytes(&mut salt)?;
+        Ok(Self { salt, iterations })
+    }
+
+    /// Reads the header written by [write_header](Params::write_header).
+    pub fn read_header(input: &mut dyn Read) -> Result<Self, CliError> {
+        let mut header = [0u8; 8 + SALT_LEN];
+        input.read_exact(&mut header)?;
+        if header[..4] != MAGIC {
+            return Err(CliError::NotPasswordEncrypted);
+        }
+        Ok(Self {
+            iterations: u32::from_be_bytes(header[4..8].try_into().unwrap()),
+            salt: header[8..].try_into().unwrap(),
+        })
+    }
+
+    pub fn write_header(&self, output: &mut dyn Write) -> std::io::Result<()> {
+        output.write_all(&MAGIC)?;
+        output.write_all(&self.iterations.to_be_bytes())?;
+        output.write_all(&self.salt)
+    }
+
+    /// Derives a 256-bit key from `password`.
+    pub fn derive_key(&self, password: &str) -> Result<Key, CliError> {
+        Ok(Key::from_password_pbkdf2(
+            password.as_bytes(),
+            &self.salt,
+            self.iterations,
+            KeySize::Bits256,
+        )?)
+    }
+}
+
+/// Returns the password given on the command line, or prompts for it on the terminal. When encrypting,
+/// the prompt asks twice to catch typos.
+pub fn get_password(arg: Option<String>, confirm: bool) -> Result<String, CliError> {
+    if let Some(password) = arg {
+        return Ok(password);
+    }
+    let password = rpassword::prompt_password("Password: ")?;
+    if confirm && rpassword::prompt_password("Confirm password: ")? != password {
+        return Err(CliError::PasswordMismatch);
+    }
+    Ok(password)
+}
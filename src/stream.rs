@@ -0,0 +1,139 @@
+//! Streaming input/output for the CLI, so memory use stays bounded regardless of file size.
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+use aesp::Cipher;
+use base64::{Engine, engine::general_purpose::STANDARD};
+
+use crate::CliError;
+use crate::args::Format;
+
+/// ECB is processed in chunks of this many bytes. Must be a multiple of 16.
+const CHUNK: usize = 64 * 1024;
+
+/// Returns the path, or None if the path is omitted or `-` (meaning stdin/stdout).
+pub fn file_path(path: &Option<PathBuf>) -> Option<&Path> {
+    path.as_deref().filter(|p| *p != Path::new("-"))
+}
+
+/// Opens the input file, or stdin.
+pub fn open_input(path: &Option<PathBuf>) -> io::Result<Box<dyn Read>> {
+    Ok(match file_path(path) {
+        Some(path) => Box::new(BufReader::new(File::open(path)?)),
+        None => Box::new(io::stdin().lock()),
+    })
+}
+
+/// Creates the output file, or opens stdout.
+pub fn open_output(path: &Option<PathBuf>) -> io::Result<Box<dyn Write>> {
+    Ok(match file_path(path) {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    })
+}
+
+/// Writes `data` hex or base64 encoded, followed by a newline. Raw data is written unchanged.
+pub fn write_encoded(format: Format, data: &[u8], output: &mut dyn Write) -> io::Result<()> {
+    let text = match format {
+        Format::Raw => return output.write_all(data),
+        Format::Hex => data.iter().map(|b| format!("{b:02x}")).collect(),
+        Format::Base64 => STANDARD.encode(data),
+    };
+    output.write_all(text.as_bytes())?;
+    output.write_all(b"\n")
+}
+
+/// Decodes hex or base64 input, ignoring whitespace. Text formats must be read in full before decoding, while
+/// raw input is returned unchanged and still streams.
+pub fn decode_input(format: Format, mut input: Box<dyn Read>) -> Result<Box<dyn Read>, CliError> {
+    let data = match format {
+        Format::Raw => return Ok(input),
+        Format::Hex => {
+            let text = read_text(&mut input, "hex")?;
+            if !text.len().is_multiple_of(2) || !text.bytes().all(|c| c.is_ascii_hexdigit()) {
+                return Err(CliError::InvalidEncoding("hex"));
+            }
+            (0..text.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&text[i..i + 2], 16).unwrap()) // digits checked above
+                .collect()
+        }
+        Format::Base64 => STANDARD
+            .decode(read_text(&mut input, "base64")?)
+            .map_err(|_| CliError::InvalidEncoding("base64"))?,
+    };
+    Ok(Box::new(io::Cursor::new(data)))
+}
+
+/// Reads all of `input` as text, with whitespace removed.
+fn read_text(input: &mut dyn Read, format: &'static str) -> Result<String, CliError> {
+    let mut text = String::new();
+    input.read_to_string(&mut text).map_err(|e| match e.kind() {
+        io::ErrorKind::InvalidData => CliError::InvalidEncoding(format), // not UTF-8
+        _ => CliError::Io(e),
+    })?;
+    text.retain(|c| !c.is_ascii_whitespace());
+    Ok(text)
+}
+
+/// Reads until `buf` is full or EOF, returning the number of bytes read.
+fn read_full(input: &mut dyn Read, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match input.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+/// ECB encrypts `input` into `output` chunk by chunk, returning the number of plaintext bytes.
+pub fn ecb_encrypt(cipher: &Cipher, input: &mut dyn Read, output: &mut dyn Write) -> io::Result<u64> {
+    let mut buf = vec![0u8; CHUNK];
+    let mut total = 0;
+    loop {
+        let n = read_full(input, &mut buf)?;
+        total += n as u64;
+        let ciphertext = cipher.encrypt_ecb(&buf[..n]);
+        if n < CHUNK {
+            // final chunk, keep the padding
+            output.write_all(&ciphertext)?;
+            return Ok(total);
+        }
+        // more input may follow, so drop the padding block that encrypt_ecb appends to a full chunk
+        output.write_all(&ciphertext[..n])?;
+    }
+}
+
+/// ECB decrypts `input` into `output` chunk by chunk, returning the number of plaintext bytes.
+///
+/// Only the final chunk is padded. Each earlier chunk has a padding-only block appended before decryption,
+/// which decrypt_ecb's unpadding then strips again, leaving the plain decryption of the chunk.
+pub fn ecb_decrypt(cipher: &Cipher, input: &mut dyn Read, output: &mut dyn Write) -> Result<u64, CliError> {
+    let pad_block = cipher.encrypt_ecb(&[]);
+    let mut chunk = vec![0u8; CHUNK + 16];
+    let mut next = vec![0u8; CHUNK];
+    let mut n = read_full(input, &mut chunk[..CHUNK])?;
+    let mut total = 0;
+    loop {
+        // a full chunk is only known not to be the last once more input has been read
+        let m = if n == CHUNK { read_full(input, &mut next)? } else { 0 };
+        if m == 0 {
+            let plaintext = cipher.decrypt_ecb(&chunk[..n])?;
+            output.write_all(&plaintext)?;
+            return Ok(total + plaintext.len() as u64);
+        }
+
+        chunk[CHUNK..].copy_from_slice(&pad_block);
+        output.write_all(&cipher.decrypt_ecb(&chunk)?)?;
+        total += CHUNK as u64;
+
+        chunk[..m].copy_from_slice(&next[..m]);
+        n = m;
+    }
+}
@@ -0,0 +1,237 @@
+#![cfg(feature = "cli")]
+
+use std::{
+    error::Error,
+    fs,
+    io::Write,
+    path::PathBuf,
+    process::{Command, Output, Stdio},
+    thread,
+};
+
+/// Runs the CLI with `args`, piping `stdin` in and capturing stdout and stderr.
+fn run(args: &[&str], stdin: &[u8]) -> Result<Output, Box<dyn Error>> {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_aesp"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    // the CLI streams, so stdin must be fed while stdout is drained or both pipes can fill up
+    let mut child_stdin = child.stdin.take().unwrap();
+    let stdin = stdin.to_vec();
+    let writer = thread::spawn(move || child_stdin.write_all(&stdin));
+    let output = child.wait_with_output()?;
+    writer.join().unwrap()?;
+    Ok(output)
+}
+
+/// Unique scratch directory per test, removed by the caller.
+fn scratch_dir(name: &str) -> Result<PathBuf, Box<dyn Error>> {
+    let dir = std::env::temp_dir().join(format!("aesp-cli-{name}-{}", std::process::id()));
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+#[test]
+fn pipes_stdin_to_stdout() -> Result<(), Box<dyn Error>> {
+    let dir = scratch_dir("pipe")?;
+    let key = dir.join("key");
+    let key = key.to_str().unwrap();
+    let plaintext: Vec<u8> = (0..100_000u32).map(|i| i as u8).collect();
+
+    for mode in ["ecb", "ctr", "gcm"] {
+        // input and output omitted
+        let enc = run(&["encrypt", "-m", mode, "-k", key, "--gen-key"], &plaintext)?;
+        assert!(enc.status.success(), "{}", String::from_utf8_lossy(&enc.stderr));
+        assert_ne!(enc.stdout, plaintext);
+        assert!(String::from_utf8_lossy(&enc.stderr).contains("Encrypted 100000 bytes"));
+
+        // explicit `-`
+        let dec = run(&["decrypt", "-m", mode, "-k", key, "-i", "-", "-o", "-"], &enc.stdout)?;
+        assert!(dec.status.success(), "{}", String::from_utf8_lossy(&dec.stderr));
+        assert_eq!(dec.stdout, plaintext, "{mode}");
+        assert!(String::from_utf8_lossy(&dec.stderr).contains("Decrypted 100000 bytes"));
+    }
+
+    fs::remove_dir_all(dir)?;
+    Ok(())
+}
+
+#[test]
+fn mixes_files_and_pipes() -> Result<(), Box<dyn Error>> {
+    let dir = scratch_dir("mixed")?;
+    let key = dir.join("key");
+    let ct_path = dir.join("ct");
+
+    let enc = run(
+        &["encrypt", "-k", key.to_str().unwrap(), "--gen-key", "-o", ct_path.to_str().unwrap()],
+        b"file output",
+    )?;
+    assert!(enc.status.success());
+    assert!(enc.stdout.is_empty());
+
+    let dec = run(&["decrypt", "-k", key.to_str().unwrap(), "-i", ct_path.to_str().unwrap()], b"")?;
+    assert_eq!(dec.stdout, b"file output");
+
+    fs::remove_dir_all(dir)?;
+    Ok(())
+}
+
+#[test]
+fn streams_across_chunk_boundaries() -> Result<(), Box<dyn Error>> {
+    let dir = scratch_dir("chunks")?;
+    let key = dir.join("key");
+    let key = key.to_str().unwrap();
+    run(&["encrypt", "-k", key, "--gen-key", "-o", "/dev/null"], b"")?;
+
+    // the CLI processes ECB in 64 KiB chunks
+    for len in [0, 15, 16, 65_535, 65_536, 65_537, 131_072, 300_001] {
+        let plaintext: Vec<u8> = (0..len).map(|i| (i % 251) as u8).collect();
+        for mode in ["ecb", "ctr", "gcm"] {
+            let enc = run(&["encrypt", "-m", mode, "-k", key], &plaintext)?;
+            let dec = run(&["decrypt", "-m", mode, "-k", key], &enc.stdout)?;
+            assert_eq!(dec.stdout, plaintext, "{mode}, {len} bytes");
+        }
+    }
+
+    fs::remove_dir_all(dir)?;
+    Ok(())
+}
+
+#[test]
+fn failed_decryption_removes_output_file() -> Result<(), Box<dyn Error>> {
+    let dir = scratch_dir("tamper")?;
+    let key = dir.join("key");
+    let out = dir.join("out");
+
+    let mut ciphertext = run(&["encrypt", "-k", key.to_str().unwrap(), "--gen-key"], &[7u8; 200_000])?.stdout;
+    let last = ciphertext.len() - 1;
+    ciphertext[last] ^= 1; // corrupt the tag
+
+    let dec = run(&["decrypt", "-k", key.to_str().unwrap(), "-o", out.to_str().unwrap()], &ciphertext)?;
+    assert!(String::from_utf8_lossy(&dec.stderr).contains("authentication failed"));
+    assert!(!out.exists());
+
+    fs::remove_dir_all(dir)?;
+    Ok(())
+}
+
+#[test]
+fn password_round_trip() -> Result<(), Box<dyn Error>> {
+    let plaintext = b"password protected";
+    for mode in ["ecb", "ctr", "gcm"] {
+        let enc = run(&["encrypt", "-m", mode, "-p", "secret", "--iterations", "1000"], plaintext)?;
+        assert!(enc.status.success(), "{}", String::from_utf8_lossy(&enc.stderr));
+        assert_eq!(enc.stdout[..4], *b"APW1");
+        assert_eq!(enc.stdout[4..8], 1000u32.to_be_bytes());
+
+        let dec = run(&["decrypt", "-m", mode, "-p", "secret"], &enc.stdout)?;
+        assert_eq!(dec.stdout, plaintext, "{mode}");
+    }
+
+    // fresh salt each time
+    let a = run(&["encrypt", "-p", "secret", "--iterations", "1000"], plaintext)?.stdout;
+    let b = run(&["encrypt", "-p", "secret", "--iterations", "1000"], plaintext)?.stdout;
+    assert_ne!(a[8..24], b[8..24]);
+
+    let dec = run(&["decrypt", "-p", "wrong"], &a)?;
+    assert!(String::from_utf8_lossy(&dec.stderr).contains("authentication failed"));
+
+    let dec = run(&["decrypt", "-p", "secret"], b"not a password-encrypted input")?;
+    assert!(String::from_utf8_lossy(&dec.stderr).contains("not password-encrypted"));
+    Ok(())
+}
+
+#[test]
+fn password_conflicts_with_key() -> Result<(), Box<dyn Error>> {
+    for args in [
+        &["encrypt", "-p", "secret", "-k", "key"][..],
+        &["encrypt", "-p", "secret", "--gen-key"],
+        &["decrypt", "-p", "secret", "-k", "key"],
+    ] {
+        let out = run(args, b"")?;
+        assert!(!out.status.success(), "{args:?}");
+        assert!(String::from_utf8_lossy(&out.stderr).contains("cannot be used with"), "{args:?}");
+    }
+
+    for args in [&["encrypt", "-k", "key", "--iterations", "1000"][..], &["encrypt", "--iterations", "1000"]] {
+        let out = run(args, b"")?;
+        assert!(!out.status.success(), "{args:?}");
+    }
+    Ok(())
+}
+
+#[test]
+fn aad_from_and_to_files() -> Result<(), Box<dyn Error>> {
+    let dir = scratch_dir("aad")?;
+    let key = dir.join("key");
+    let key = key.to_str().unwrap();
+    let aad_in = dir.join("aad-in");
+    let aad_out = dir.join("aad-out");
+    let aad: Vec<u8> = (0..10_000u32).map(|i| (i * 7) as u8).collect();
+    fs::write(&aad_in, &aad)?;
+
+    let enc = run(&["encrypt", "-k", key, "--gen-key", "--aad-file", aad_in.to_str().unwrap()], b"body")?;
+    assert!(enc.status.success(), "{}", String::from_utf8_lossy(&enc.stderr));
+
+    let dec = run(&["decrypt", "-k", key, "--aad-out", aad_out.to_str().unwrap()], &enc.stdout)?;
+    assert_eq!(dec.stdout, b"body");
+    assert_eq!(fs::read(&aad_out)?, aad);
+    assert!(!String::from_utf8_lossy(&dec.stderr).contains("AAD ="));
+
+    // matches the inline hex form
+    let enc = run(&["encrypt", "-k", key, "--aad", "0a0b"], b"body")?;
+    run(&["decrypt", "-k", key, "--aad-out", aad_out.to_str().unwrap()], &enc.stdout)?;
+    assert_eq!(fs::read(&aad_out)?, [0x0a, 0x0b]);
+
+    let out = run(&["encrypt", "-k", key, "--aad", "00", "--aad-file", aad_in.to_str().unwrap()], b"")?;
+    assert!(String::from_utf8_lossy(&out.stderr).contains("cannot be used with"));
+
+    for args in [
+        &["encrypt", "-m", "ctr", "-k", key, "--aad-file", aad_in.to_str().unwrap()][..],
+        &["decrypt", "-m", "ecb", "-k", key, "--aad-out", aad_out.to_str().unwrap()],
+    ] {
+        let out = run(args, b"")?;
+        assert!(String::from_utf8_lossy(&out.stderr).contains("only valid with --mode gcm"), "{args:?}");
+    }
+
+    fs::remove_dir_all(dir)?;
+    Ok(())
+}
+
+#[test]
+fn text_output_formats() -> Result<(), Box<dyn Error>> {
+    let dir = scratch_dir("format")?;
+    let key = dir.join("key");
+    let key = key.to_str().unwrap();
+    let plaintext: Vec<u8> = (0..1000u32).map(|i| i as u8).collect();
+    run(&["encrypt", "-k", key, "--gen-key"], b"")?;
+
+    for mode in ["ecb", "ctr", "gcm"] {
+        let raw = run(&["encrypt", "-m", mode, "-k", key], &plaintext)?;
+        for format in ["hex", "base64"] {
+            let enc = run(&["encrypt", "-m", mode, "-k", key, "--format", format], &plaintext)?;
+            assert!(enc.status.success(), "{}", String::from_utf8_lossy(&enc.stderr));
+            assert!(enc.stdout.is_ascii() && enc.stdout.ends_with(b"\n"), "{mode} {format}");
+            // hex is twice the raw length, base64 four thirds (rounded up), plus the trailing newline
+            let expected_len = if format == "hex" { raw.stdout.len() * 2 } else { raw.stdout.len().div_ceil(3) * 4 };
+            assert_eq!(enc.stdout.len(), expected_len + 1, "{mode} {format}");
+
+            let dec = run(&["decrypt", "-m", mode, "-k", key, "--format", format], &enc.stdout)?;
+            assert_eq!(dec.stdout, plaintext, "{mode} {format}");
+        }
+    }
+
+    // malformed text input is reported
+    for (format, input) in [("hex", &b"0g"[..]), ("hex", b"abc"), ("base64", b"!!!!"), ("hex", &[0xff, 0xfe])] {
+        let dec = run(&["decrypt", "-k", key, "--format", format], input)?;
+        assert!(dec.stdout.is_empty());
+        let stderr = String::from_utf8_lossy(&dec.stderr);
+        assert!(stderr.contains(&format!("input is not valid {format}")), "{stderr}");
+    }
+
+    fs::remove_dir_all(dir)?;
+    Ok(())
+}
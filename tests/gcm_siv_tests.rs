@@ -0,0 +1,79 @@
+#![cfg(feature = "test-vectors")]
+
+/// Test vectors for GCM-SIV, in the same layout as the GCM vectors.
+#[derive(Debug)]
+pub struct TestVector<K: 'static, N: 'static> {
+    pub key: &'static K,
+    pub nonce: &'static N,
+    pub aad: &'static [u8],
+    pub plaintext: &'static [u8],
+    pub ciphertext: &'static [u8],
+    pub tag: &'static [u8; 16],
+}
+
+/// Same wire format as GCM: nonce || aad_len || aad || ciphertext || tag
+pub fn pack_message(nonce: &[u8; 12], aad: &[u8], ciphertext: &[u8], tag: &[u8; 16]) -> Vec<u8> {
+    let mut msg = Vec::with_capacity(12 + 8 + aad.len() + ciphertext.len() + 16);
+    msg.extend_from_slice(nonce);
+    msg.extend_from_slice(&(aad.len() as u64).to_be_bytes());
+    msg.extend_from_slice(aad);
+    msg.extend_from_slice(ciphertext);
+    msg.extend_from_slice(tag);
+    msg
+}
+
+/// Generate encrypt/decrypt tests over the given RFC 8452 vectors.
+#[macro_export]
+macro_rules! gcm_siv_tests {
+    ($vectors:expr) => {
+        #[test]
+        fn rfc8452_vectors_encrypt_matches() {
+            for vector in $vectors {
+                let cipher = Cipher::new(&Key::try_from_slice(vector.key).unwrap());
+                let got = cipher
+                    .encrypt_gcm_siv_with_nonce(vector.plaintext, Some(vector.aad), vector.nonce)
+                    .expect("encrypt should succeed");
+
+                let expected =
+                    $crate::gcm_siv_tests::pack_message(vector.nonce, vector.aad, vector.ciphertext, vector.tag);
+                assert_eq!(expected, got);
+            }
+        }
+
+        #[test]
+        fn rfc8452_vectors_decrypt_ok() {
+            for vector in $vectors {
+                let cipher = Cipher::new(&Key::try_from_slice(vector.key).unwrap());
+                let msg = $crate::gcm_siv_tests::pack_message(vector.nonce, vector.aad, vector.ciphertext, vector.tag);
+
+                let (pt, aad_out) = cipher
+                    .decrypt_gcm_siv(&msg)
+                    .expect("valid RFC 8452 vector should decrypt");
+                assert_eq!(vector.plaintext, pt.as_slice());
+                assert_eq!((!vector.aad.is_empty()).then(|| vector.aad.to_vec()), aad_out);
+            }
+        }
+
+        #[test]
+        fn rfc8452_vectors_reject_tampering() {
+            for vector in $vectors {
+                let cipher = Cipher::new(&Key::try_from_slice(vector.key).unwrap());
+                let msg = $crate::gcm_siv_tests::pack_message(vector.nonce, vector.aad, vector.ciphertext, vector.tag);
+
+                // nonce, AAD (if any), ciphertext (if any), and tag
+                let mut offsets = vec![0, msg.len() - 1];
+                if !vector.aad.is_empty() {
+                    offsets.push(20);
+                }
+                if !vector.ciphertext.is_empty() {
+                    offsets.push(20 + vector.aad.len());
+                }
+                for offset in offsets {
+                    let mut tampered = msg.clone();
+                    tampered[offset] ^= 0x01;
+                    assert!(matches!(cipher.decrypt_gcm_siv(&tampered), Err(aesp::Error::AuthFailed)));
+                }
+            }
+        }
+    };
+}
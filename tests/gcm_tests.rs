@@ -14,9 +14,9 @@ pub struct TestVector<K: 'static, N: 'static> {
 }
 
 pub fn pack_message(iv: &[u8; 12], aad: &[u8], ciphertext: &[u8], tag: &[u8; 16]) -> Vec<u8> {
-    let mut msg = Vec::with_capacity(12 + 4 + aad.len() + ciphertext.len() + 16);
+    let mut msg = Vec::with_capacity(12 + 8 + aad.len() + ciphertext.len() + 16);
     msg.extend_from_slice(iv);
-    msg.extend_from_slice(&(aad.len() as u32).to_be_bytes());
+    msg.extend_from_slice(&(aad.len() as u64).to_be_bytes());
     msg.extend_from_slice(aad);
     msg.extend_from_slice(ciphertext);
     msg.extend_from_slice(tag);
@@ -36,7 +36,7 @@ macro_rules! gcm_tests {
                 let cipher = Cipher::new(&key);
 
                 // Build message format: iv || aad_len || aad || ciphertext || tag
-                let msg = crate::gcm_tests::pack_message(vector.nonce, vector.aad, vector.ciphertext, vector.tag);
+                let msg = $crate::gcm_tests::pack_message(vector.nonce, vector.aad, vector.ciphertext, vector.tag);
 
                 let (pt, aad_out) = cipher
                     .decrypt_gcm(&msg)
@@ -53,6 +53,28 @@ macro_rules! gcm_tests {
             }
         }
 
+        #[test]
+        fn nist_vectors_decrypt_with_external_aad() {
+            for vector in $vectors {
+                let key = Key::try_from_slice(vector.key)
+                    .expect("invalid test key bytes for this implementation");
+                let cipher = Cipher::new(&key);
+
+                let pt = cipher
+                    .decrypt_gcm_with_aad(vector.nonce, vector.ciphertext, vector.tag, vector.aad)
+                    .expect("valid NIST vector should decrypt");
+                assert_eq!(vector.plaintext, pt.as_slice());
+
+                // AAD is authenticated even though it is not part of the ciphertext
+                let mut aad = vector.aad.to_vec();
+                aad.push(0);
+                assert!(matches!(
+                    cipher.decrypt_gcm_with_aad(vector.nonce, vector.ciphertext, vector.tag, &aad),
+                    Err(aesp::Error::AuthFailed)
+                ));
+            }
+        }
+
         #[test]
         fn nist_vectors_reject_bad_tag() {
             for vector in $vectors {
@@ -60,7 +82,7 @@ macro_rules! gcm_tests {
                     .expect("invalid test key bytes for this implementation");
                 let cipher = Cipher::new(&key);
 
-                let mut msg = crate::gcm_tests::pack_message(vector.nonce, vector.aad, vector.ciphertext, vector.tag);
+                let mut msg = $crate::gcm_tests::pack_message(vector.nonce, vector.aad, vector.ciphertext, vector.tag);
 
                 // Flip a bit in the tag (last byte)
                 let last = msg.len() - 1;
@@ -77,11 +99,11 @@ macro_rules! gcm_tests {
                     .expect("invalid test key bytes for this implementation");
                 let cipher = Cipher::new(&key);
 
-                let mut msg = crate::gcm_tests::pack_message(vector.nonce, vector.aad, vector.ciphertext, vector.tag);
+                let mut msg = $crate::gcm_tests::pack_message(vector.nonce, vector.aad, vector.ciphertext, vector.tag);
 
                 // If ciphertext is non-empty, flip first ciphertext byte.
                 // Otherwise flip IV[0] (still should fail tag check).
-                let ct_offset = 12 + 4 + vector.aad.len();
+                let ct_offset = 12 + 8 + vector.aad.len();
                 if !vector.ciphertext.is_empty() {
                     msg[ct_offset] ^= 0x01;
                 } else {
@@ -101,7 +123,7 @@ macro_rules! gcm_tests {
 
                 let got = cipher.encrypt_gcm_with_iv(vector.plaintext, Some(vector.aad), vector.nonce).expect("encrypt should succeed");
 
-                let expected = crate::gcm_tests::pack_message(vector.nonce, vector.aad, vector.ciphertext, vector.tag);
+                let expected = $crate::gcm_tests::pack_message(vector.nonce, vector.aad, vector.ciphertext, vector.tag);
                 assert_eq!(expected, got);
             }
         }
@@ -0,0 +1,381 @@
+#![cfg(feature = "test-vectors")]
+
+//! NIST AESAVS Monte Carlo tests (MCT) for ECB and CBC.
+//!
+//! Each MCT case chains 1000 block operations, feeding every output back in as a later input, then derives the
+//! next case's key (and IV) from the last outputs. A file holds 100 such cases per direction, so a single wrong bit
+//! anywhere in the core transform or the chaining is carried through to every later case.
+//!
+//! Response files are picked up from `tests/vectors` by name: `*mct*.rsp`, with `ecb` or `cbc` in the name to
+//! select the mode (e.g. `ECBMCT128.rsp`, `CBCMCT256.rsp` from the AESAVS `aesmct.zip`). Each record's KEY, IV and
+//! input are checked against the values derived from the previous record, as well as its output. The vendored
+//! files are excerpts holding only the first case, so the published final (COUNT = 99) outputs are also checked
+//! below, chaining all 100 cases from the first.
+//!
+//! AESAVS defines no Monte Carlo test for CTR, so CTR is covered by the SP 800-38A vectors in the unit tests.
+
+use std::{
+    error::Error,
+    fs,
+    io::{BufRead, BufReader},
+    path::{Path, PathBuf},
+};
+
+use aesp::{Cipher, Key, xor_into};
+
+const INNER_ITERATIONS: usize = 1000;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Mode {
+    Ecb,
+    Cbc,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Dir {
+    Encrypt,
+    Decrypt,
+}
+
+/// One COUNT record. `input` is the PLAINTEXT when encrypting and the CIPHERTEXT when decrypting.
+#[derive(Clone, Debug, Default)]
+struct Record {
+    count: Option<u32>,
+    key: Option<Vec<u8>>,
+    iv: Option<[u8; 16]>,
+    input: Option<[u8; 16]>,
+    output: Option<[u8; 16]>,
+}
+
+/// Chaining state carried from one outer iteration to the next.
+#[derive(Clone, Debug, PartialEq)]
+struct State {
+    key: Vec<u8>,
+    iv: [u8; 16],
+    input: [u8; 16],
+}
+
+#[test]
+fn nist_mct_rsp() -> Result<(), Box<dyn Error>> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("vectors");
+    let mut paths: Vec<PathBuf> = fs::read_dir(&dir)?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| is_mct_rsp(p))
+        .collect();
+    paths.sort();
+
+    let mut total = 0usize;
+    for path in paths {
+        let mode = mode_from_path(&path).ok_or_else(|| format!("{}: name has no ECB or CBC", path.display()))?;
+        let reader = BufReader::new(fs::File::open(&path)?);
+        total += run_mct(reader, mode).map_err(|e| format!("{}: {e}", path.display()))?;
+    }
+
+    eprintln!("MCT: executed {total} cases");
+    assert!(total > 0, "no MCT cases found in {}", dir.display());
+    Ok(())
+}
+
+/// Chains all 100 outer iterations from the published COUNT = 0 inputs, checking the published COUNT = 99
+/// output of the AESAVS encrypt sections.
+#[test]
+fn aesavs_mct_final_outputs() {
+    // (mode, COUNT = 0 key, IV, plaintext, COUNT = 0 ciphertext, COUNT = 99 ciphertext)
+    let cases = [
+        (
+            Mode::Ecb,
+            "139a35422f1d61de3c91787fe0507afd",
+            None,
+            "b9145a768b7dc489a096b546f43b231f",
+            Some("d7c3ffac9031238650901e157364c386"),
+            "fb2649694783b551eacd9d5db6126d47",
+        ),
+        (
+            Mode::Ecb,
+            "b9a63e09e1dfc42e93a90d9bad739e5967aef672eedd5da9",
+            None,
+            "85a1f7a58167b389cddc8a9ff175ee26",
+            None,
+            "5d1196da8f184975e240949a25104554",
+        ),
+        (
+            Mode::Cbc,
+            "8809e7dd3a959ee5d8dbb13f501f2274",
+            Some("e5c0bb535d7d54572ad06d170a0e58ae"),
+            "1fd4ee65603e6130cfc2a82ab3d56c24",
+            Some("b127a5b4c4692d87483db0c3b0d11e64"),
+            "7bed7671c8913aa1330f193761523e67",
+        ),
+    ];
+
+    for (mode, key, iv, plaintext, first, last) in cases {
+        let mut state = State {
+            key: decode_hex(key).unwrap(),
+            iv: iv.map_or([0; 16], |iv| decode_hex(iv).unwrap().try_into().unwrap()),
+            input: decode_hex(plaintext).unwrap().try_into().unwrap(),
+        };
+        let outputs: Vec<String> = (0..100).map(|_| hex(&outer_iteration(mode, Dir::Encrypt, &mut state))).collect();
+        if let Some(first) = first {
+            assert_eq!(outputs[0], first, "{mode:?} key {key}: COUNT = 0");
+        }
+        assert_eq!(outputs[99], last, "{mode:?} key {key}: COUNT = 99");
+    }
+}
+
+/// Decrypting the output of an ECB encryption case must lead back to its plaintext, as each direction applies
+/// the block transform 1000 times.
+#[test]
+fn ecb_mct_directions_are_inverse() {
+    for key in [vec![0x2b; 16], vec![0x8e; 24], vec![0x60; 32]] {
+        let start = State { key, iv: [0; 16], input: *b"Monte Carlo test" };
+
+        let mut state = start.clone();
+        let ciphertext = outer_iteration(Mode::Ecb, Dir::Encrypt, &mut state);
+
+        let mut state = State { input: ciphertext, ..start.clone() };
+        assert_eq!(outer_iteration(Mode::Ecb, Dir::Decrypt, &mut state), start.input);
+    }
+}
+
+/// Runs the parser over a response file written from the chaining engine itself, then checks that a corrupted
+/// output and a broken key chain are both reported.
+#[test]
+fn mct_parser_checks_outputs_and_chaining() -> Result<(), Box<dyn Error>> {
+    for mode in [Mode::Ecb, Mode::Cbc] {
+        let rsp = write_rsp(mode, 3);
+        assert_eq!(run_mct(rsp.as_bytes(), mode)?, 6);
+
+        let wrong_output = replace_nth(&rsp, "CIPHERTEXT = ", 1);
+        assert!(run_mct(wrong_output.as_bytes(), mode).is_err());
+
+        let wrong_key = replace_nth(&rsp, "KEY = ", 2);
+        assert!(run_mct(wrong_key.as_bytes(), mode).is_err());
+    }
+    Ok(())
+}
+
+fn is_mct_rsp(path: &Path) -> bool {
+    let name = path.file_name().and_then(|s| s.to_str()).unwrap_or_default().to_ascii_lowercase();
+    name.ends_with(".rsp") && name.contains("mct")
+}
+
+fn mode_from_path(path: &Path) -> Option<Mode> {
+    let name = path.file_name()?.to_str()?.to_ascii_lowercase();
+    if name.contains("ecb") {
+        Some(Mode::Ecb)
+    } else if name.contains("cbc") {
+        Some(Mode::Cbc)
+    } else {
+        None
+    }
+}
+
+/// Parses an MCT response file, checking every section's records in order. Returns the number of records checked.
+fn run_mct(reader: impl BufRead, mode: Mode) -> Result<usize, Box<dyn Error>> {
+    let mut dir: Option<Dir> = None;
+    let mut records: Vec<Record> = Vec::new();
+    let mut current = Record::default();
+    let mut executed = 0usize;
+
+    for (lineno, line) in reader.lines().enumerate() {
+        let line = line?;
+        let s = line.trim();
+        if s.is_empty() || s.starts_with('#') {
+            continue;
+        }
+
+        // a new section ends the previous one, whose chain can now be checked as a whole
+        let section = if s.eq_ignore_ascii_case("[ENCRYPT]") {
+            Some(Dir::Encrypt)
+        } else if s.eq_ignore_ascii_case("[DECRYPT]") {
+            Some(Dir::Decrypt)
+        } else {
+            None
+        };
+        if let Some(next) = section {
+            if let Some(d) = dir {
+                executed += check_section(mode, d, &records)?;
+            }
+            dir = Some(next);
+            records.clear();
+            current = Record::default();
+            continue;
+        }
+
+        let Some((k, v)) = s.split_once('=') else {
+            continue;
+        };
+        let d = dir.ok_or_else(|| format!("line {}: record before [ENCRYPT] or [DECRYPT]", lineno + 1))?;
+        let (name, val) = (k.trim().to_ascii_uppercase(), v.trim());
+        let block = || -> Result<[u8; 16], Box<dyn Error>> {
+            let bytes = decode_hex(val).map_err(|e| format!("line {}: {e}", lineno + 1))?;
+            <[u8; 16]>::try_from(bytes).map_err(|_| format!("line {}: {name} is not one block", lineno + 1).into())
+        };
+
+        match (name.as_str(), d) {
+            ("COUNT", _) => current.count = Some(val.parse()?),
+            ("KEY", _) => current.key = Some(decode_hex(val).map_err(|e| format!("line {}: {e}", lineno + 1))?),
+            ("IV", _) => current.iv = Some(block()?),
+            ("PLAINTEXT", Dir::Encrypt) | ("CIPHERTEXT", Dir::Decrypt) => current.input = Some(block()?),
+            ("PLAINTEXT", Dir::Decrypt) | ("CIPHERTEXT", Dir::Encrypt) => current.output = Some(block()?),
+            _ => {}
+        }
+
+        let complete = current.count.is_some()
+            && current.key.is_some()
+            && current.input.is_some()
+            && current.output.is_some()
+            && (mode == Mode::Ecb || current.iv.is_some());
+        if complete {
+            records.push(core::mem::take(&mut current));
+        }
+    }
+
+    if let Some(d) = dir {
+        executed += check_section(mode, d, &records)?;
+    }
+    Ok(executed)
+}
+
+/// Runs the chain from the first record, comparing the derived state and output against every record in turn.
+fn check_section(mode: Mode, dir: Dir, records: &[Record]) -> Result<usize, Box<dyn Error>> {
+    let Some(first) = records.first() else {
+        return Ok(0);
+    };
+    let mut state = State {
+        key: first.key.clone().unwrap_or_default(),
+        iv: first.iv.unwrap_or_default(),
+        input: first.input.unwrap_or_default(),
+    };
+
+    for (i, record) in records.iter().enumerate() {
+        let count = record.count;
+        let expected = State {
+            key: record.key.clone().unwrap_or_default(),
+            iv: record.iv.unwrap_or_default(),
+            input: record.input.unwrap_or_default(),
+        };
+        if state != expected {
+            return Err(format!(
+                "{mode:?} {dir:?} COUNT={count:?}: chained state does not match the record\n  \
+                 KEY={} / {}\n  IV ={} / {}\n  IN ={} / {}",
+                hex(&state.key),
+                hex(&expected.key),
+                hex(&state.iv),
+                hex(&expected.iv),
+                hex(&state.input),
+                hex(&expected.input),
+            )
+            .into());
+        }
+
+        let got = outer_iteration(mode, dir, &mut state);
+        let want = record.output.unwrap_or_default();
+        if got != want {
+            return Err(format!(
+                "{mode:?} {dir:?} mismatch at record {i} COUNT={count:?}\n  EXP={}\n  GOT={}",
+                hex(&want),
+                hex(&got),
+            )
+            .into());
+        }
+    }
+    Ok(records.len())
+}
+
+/// Runs one outer MCT iteration (AESAVS section 6.4): 1000 chained block operations under `state`, returning the
+/// final output and advancing `state` to the next iteration's key, IV and input.
+fn outer_iteration(mode: Mode, dir: Dir, state: &mut State) -> [u8; 16] {
+    let cipher = Cipher::new(&Key::try_from_slice(&state.key).unwrap());
+    let block = |input: [u8; 16]| -> [u8; 16] {
+        let out = match dir {
+            Dir::Encrypt => cipher.encrypt_ecb_raw(&input),
+            Dir::Decrypt => cipher.decrypt_ecb_raw(&input),
+        };
+        out.unwrap().try_into().unwrap()
+    };
+    let xor = |mut a: [u8; 16], b: [u8; 16]| {
+        xor_into(&mut a, &b);
+        a
+    };
+
+    let mut input = state.input;
+    // the CBC chaining value: the previous ciphertext block, starting from the IV
+    let mut chain = state.iv;
+    let (mut out, mut prev) = ([0u8; 16], [0u8; 16]);
+    for j in 0..INNER_ITERATIONS {
+        prev = out;
+        out = match (mode, dir) {
+            (Mode::Ecb, _) => block(input),
+            (Mode::Cbc, Dir::Encrypt) => block(xor(input, chain)),
+            (Mode::Cbc, Dir::Decrypt) => xor(block(input), chain),
+        };
+        match mode {
+            Mode::Ecb => input = out,
+            Mode::Cbc => {
+                chain = if dir == Dir::Encrypt { out } else { input };
+                input = if j == 0 { state.iv } else { prev };
+            }
+        }
+    }
+
+    // the next key absorbs as many of the final output bits as it has
+    let mut tail = prev.to_vec();
+    tail.extend_from_slice(&out);
+    let key_len = state.key.len();
+    xor_into(&mut state.key, &tail[32 - key_len..]);
+    match mode {
+        Mode::Ecb => state.input = out,
+        Mode::Cbc => {
+            state.iv = out;
+            state.input = prev;
+        }
+    }
+    out
+}
+
+/// Writes an MCT response file with `cases` records per direction, from the chaining engine itself.
+fn write_rsp(mode: Mode, cases: u32) -> String {
+    let mut rsp = String::from("# generated by the MCT harness\n");
+    for dir in [Dir::Encrypt, Dir::Decrypt] {
+        let (input_name, output_name) = match dir {
+            Dir::Encrypt => ("PLAINTEXT", "CIPHERTEXT"),
+            Dir::Decrypt => ("CIPHERTEXT", "PLAINTEXT"),
+        };
+        rsp.push_str(if dir == Dir::Encrypt { "\n[ENCRYPT]\n" } else { "\n[DECRYPT]\n" });
+
+        let mut state = State { key: vec![0x42; 16], iv: [0x24; 16], input: [0x00; 16] };
+        for count in 0..cases {
+            rsp.push_str(&format!("\nCOUNT = {count}\nKEY = {}\n", hex(&state.key)));
+            if mode == Mode::Cbc {
+                rsp.push_str(&format!("IV = {}\n", hex(&state.iv)));
+            }
+            rsp.push_str(&format!("{input_name} = {}\n", hex(&state.input)));
+            let out = outer_iteration(mode, dir, &mut state);
+            rsp.push_str(&format!("{output_name} = {}\n", hex(&out)));
+        }
+    }
+    rsp
+}
+
+/// Flips the first hex digit after the `n`th occurrence of `field`.
+fn replace_nth(rsp: &str, field: &str, n: usize) -> String {
+    let (pos, _) = rsp.match_indices(field).nth(n).unwrap();
+    let at = pos + field.len();
+    let flipped = if &rsp[at..at + 1] == "0" { "1" } else { "0" };
+    format!("{}{flipped}{}", &rsp[..at], &rsp[at + 1..])
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn decode_hex(s: &str) -> Result<Vec<u8>, String> {
+    if !s.len().is_multiple_of(2) {
+        return Err(format!("odd-length hex string: len={}", s.len()));
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).map_err(|e| format!("invalid hex {:?}: {e}", &s[i..i + 2])))
+        .collect()
+}
@@ -0,0 +1,209 @@
+#[macro_use]
+#[path = "../gcm_siv_tests.rs"]
+mod gcm_siv_tests;
+
+use self::gcm_siv_tests::TestVector;
+use hex_literal::hex;
+
+use aesp::{Cipher, Key};
+
+gcm_siv_tests!(TEST_VECTORS);
+
+/// RFC 8452 appendix C.1: AEAD_AES_128_GCM_SIV
+///
+/// <https://www.rfc-editor.org/rfc/rfc8452#appendix-C.1>
+/// Copied from https://github.com/RustCrypto/AEADs/tree/master/aes-gcm-siv/tests
+const TEST_VECTORS: &[TestVector<[u8; 16], [u8; 12]>] = &[
+    TestVector {
+        key: &hex!("01000000000000000000000000000000"),
+        nonce: &hex!("030000000000000000000000"),
+        plaintext: &hex!(""),
+        aad: &hex!(""),
+        ciphertext: &hex!(""),
+        tag: &hex!("dc20e2d83f25705bb49e439eca56de25"),
+    },
+    TestVector {
+        key: &hex!("01000000000000000000000000000000"),
+        nonce: &hex!("030000000000000000000000"),
+        plaintext: &hex!("0100000000000000"),
+        aad: &hex!(""),
+        ciphertext: &hex!("b5d839330ac7b786"),
+        tag: &hex!("578782fff6013b815b287c22493a364c"),
+    },
+    TestVector {
+        key: &hex!("01000000000000000000000000000000"),
+        nonce: &hex!("030000000000000000000000"),
+        plaintext: &hex!("010000000000000000000000"),
+        aad: &hex!(""),
+        ciphertext: &hex!("7323ea61d05932260047d942"),
+        tag: &hex!("a4978db357391a0bc4fdec8b0d106639"),
+    },
+    TestVector {
+        key: &hex!("01000000000000000000000000000000"),
+        nonce: &hex!("030000000000000000000000"),
+        plaintext: &hex!("01000000000000000000000000000000"),
+        aad: &hex!(""),
+        ciphertext: &hex!("743f7c8077ab25f8624e2e948579cf77"),
+        tag: &hex!("303aaf90f6fe21199c6068577437a0c4"),
+    },
+    TestVector {
+        key: &hex!("01000000000000000000000000000000"),
+        nonce: &hex!("030000000000000000000000"),
+        plaintext: &hex!("0100000000000000000000000000000002000000000000000000000000000000"),
+        aad: &hex!(""),
+        ciphertext: &hex!("84e07e62ba83a6585417245d7ec413a9fe427d6315c09b57ce45f2e3936a9445"),
+        tag: &hex!("1a8e45dcd4578c667cd86847bf6155ff"),
+    },
+    TestVector {
+        key: &hex!("01000000000000000000000000000000"),
+        nonce: &hex!("030000000000000000000000"),
+        plaintext: &hex!("010000000000000000000000000000000200000000000000000000000000000003000000000000000000000000000000"),
+        aad: &hex!(""),
+        ciphertext: &hex!("3fd24ce1f5a67b75bf2351f181a475c7b800a5b4d3dcf70106b1eea82fa1d64df42bf7226122fa92e17a40eeaac1201b"),
+        tag: &hex!("5e6e311dbf395d35b0fe39c2714388f8"),
+    },
+    TestVector {
+        key: &hex!("01000000000000000000000000000000"),
+        nonce: &hex!("030000000000000000000000"),
+        plaintext: &hex!("01000000000000000000000000000000020000000000000000000000000000000300000000000000000000000000000004000000000000000000000000000000"),
+        aad: &hex!(""),
+        ciphertext: &hex!("2433668f1058190f6d43e360f4f35cd8e475127cfca7028ea8ab5c20f7ab2af02516a2bdcbc08d521be37ff28c152bba36697f25b4cd169c6590d1dd39566d3f"),
+        tag: &hex!("8a263dd317aa88d56bdf3936dba75bb8"),
+    },
+    TestVector {
+        key: &hex!("01000000000000000000000000000000"),
+        nonce: &hex!("030000000000000000000000"),
+        plaintext: &hex!("0200000000000000"),
+        aad: &hex!("01"),
+        ciphertext: &hex!("1e6daba35669f427"),
+        tag: &hex!("3b0a1a2560969cdf790d99759abd1508"),
+    },
+    TestVector {
+        key: &hex!("01000000000000000000000000000000"),
+        nonce: &hex!("030000000000000000000000"),
+        plaintext: &hex!("020000000000000000000000"),
+        aad: &hex!("01"),
+        ciphertext: &hex!("296c7889fd99f41917f44620"),
+        tag: &hex!("08299c5102745aaa3a0c469fad9e075a"),
+    },
+    TestVector {
+        key: &hex!("01000000000000000000000000000000"),
+        nonce: &hex!("030000000000000000000000"),
+        plaintext: &hex!("02000000000000000000000000000000"),
+        aad: &hex!("01"),
+        ciphertext: &hex!("e2b0c5da79a901c1745f700525cb335b"),
+        tag: &hex!("8f8936ec039e4e4bb97ebd8c4457441f"),
+    },
+    TestVector {
+        key: &hex!("01000000000000000000000000000000"),
+        nonce: &hex!("030000000000000000000000"),
+        plaintext: &hex!("0200000000000000000000000000000003000000000000000000000000000000"),
+        aad: &hex!("01"),
+        ciphertext: &hex!("620048ef3c1e73e57e02bb8562c416a319e73e4caac8e96a1ecb2933145a1d71"),
+        tag: &hex!("e6af6a7f87287da059a71684ed3498e1"),
+    },
+    TestVector {
+        key: &hex!("01000000000000000000000000000000"),
+        nonce: &hex!("030000000000000000000000"),
+        plaintext: &hex!("020000000000000000000000000000000300000000000000000000000000000004000000000000000000000000000000"),
+        aad: &hex!("01"),
+        ciphertext: &hex!("50c8303ea93925d64090d07bd109dfd9515a5a33431019c17d93465999a8b0053201d723120a8562b838cdff25bf9d1e"),
+        tag: &hex!("6a8cc3865f76897c2e4b245cf31c51f2"),
+    },
+    TestVector {
+        key: &hex!("01000000000000000000000000000000"),
+        nonce: &hex!("030000000000000000000000"),
+        plaintext: &hex!("02000000000000000000000000000000030000000000000000000000000000000400000000000000000000000000000005000000000000000000000000000000"),
+        aad: &hex!("01"),
+        ciphertext: &hex!("2f5c64059db55ee0fb847ed513003746aca4e61c711b5de2e7a77ffd02da42feec601910d3467bb8b36ebbaebce5fba30d36c95f48a3e7980f0e7ac299332a80"),
+        tag: &hex!("cdc46ae475563de037001ef84ae21744"),
+    },
+    TestVector {
+        key: &hex!("01000000000000000000000000000000"),
+        nonce: &hex!("030000000000000000000000"),
+        plaintext: &hex!("02000000"),
+        aad: &hex!("010000000000000000000000"),
+        ciphertext: &hex!("a8fe3e87"),
+        tag: &hex!("07eb1f84fb28f8cb73de8e99e2f48a14"),
+    },
+    TestVector {
+        key: &hex!("01000000000000000000000000000000"),
+        nonce: &hex!("030000000000000000000000"),
+        plaintext: &hex!("0300000000000000000000000000000004000000"),
+        aad: &hex!("010000000000000000000000000000000200"),
+        ciphertext: &hex!("6bb0fecf5ded9b77f902c7d5da236a4391dd0297"),
+        tag: &hex!("24afc9805e976f451e6d87f6fe106514"),
+    },
+    TestVector {
+        key: &hex!("01000000000000000000000000000000"),
+        nonce: &hex!("030000000000000000000000"),
+        plaintext: &hex!("030000000000000000000000000000000400"),
+        aad: &hex!("0100000000000000000000000000000002000000"),
+        ciphertext: &hex!("44d0aaf6fb2f1f34add5e8064e83e12a2ada"),
+        tag: &hex!("bff9b2ef00fb47920cc72a0c0f13b9fd"),
+    },
+    TestVector {
+        key: &hex!("e66021d5eb8e4f4066d4adb9c33560e4"),
+        nonce: &hex!("f46e44bb3da0015c94f70887"),
+        plaintext: &hex!(""),
+        aad: &hex!(""),
+        ciphertext: &hex!(""),
+        tag: &hex!("a4194b79071b01a87d65f706e3949578"),
+    },
+    TestVector {
+        key: &hex!("36864200e0eaf5284d884a0e77d31646"),
+        nonce: &hex!("bae8e37fc83441b16034566b"),
+        plaintext: &hex!("7a806c"),
+        aad: &hex!("46bb91c3c5"),
+        ciphertext: &hex!("af60eb"),
+        tag: &hex!("711bd85bc1e4d3e0a462e074eea428a8"),
+    },
+    TestVector {
+        key: &hex!("aedb64a6c590bc84d1a5e269e4b47801"),
+        nonce: &hex!("afc0577e34699b9e671fdd4f"),
+        plaintext: &hex!("bdc66f146545"),
+        aad: &hex!("fc880c94a95198874296"),
+        ciphertext: &hex!("bb93a3e34d3c"),
+        tag: &hex!("d6a9c45545cfc11f03ad743dba20f966"),
+    },
+    TestVector {
+        key: &hex!("d5cc1fd161320b6920ce07787f86743b"),
+        nonce: &hex!("275d1ab32f6d1f0434d8848c"),
+        plaintext: &hex!("1177441f195495860f"),
+        aad: &hex!("046787f3ea22c127aaf195d1894728"),
+        ciphertext: &hex!("4f37281f7ad12949d0"),
+        tag: &hex!("1d02fd0cd174c84fc5dae2f60f52fd2b"),
+    },
+    TestVector {
+        key: &hex!("b3fed1473c528b8426a582995929a149"),
+        nonce: &hex!("9e9ad8780c8d63d0ab4149c0"),
+        plaintext: &hex!("9f572c614b4745914474e7c7"),
+        aad: &hex!("c9882e5386fd9f92ec489c8fde2be2cf97e74e93"),
+        ciphertext: &hex!("f54673c5ddf710c745641c8b"),
+        tag: &hex!("c1dc2f871fb7561da1286e655e24b7b0"),
+    },
+    TestVector {
+        key: &hex!("2d4ed87da44102952ef94b02b805249b"),
+        nonce: &hex!("ac80e6f61455bfac8308a2d4"),
+        plaintext: &hex!("0d8c8451178082355c9e940fea2f58"),
+        aad: &hex!("2950a70d5a1db2316fd568378da107b52b0da55210cc1c1b0a"),
+        ciphertext: &hex!("c9ff545e07b88a015f05b274540aa1"),
+        tag: &hex!("83b3449b9f39552de99dc214a1190b0b"),
+    },
+    TestVector {
+        key: &hex!("bde3b2f204d1e9f8b06bc47f9745b3d1"),
+        nonce: &hex!("ae06556fb6aa7890bebc18fe"),
+        plaintext: &hex!("6b3db4da3d57aa94842b9803a96e07fb6de7"),
+        aad: &hex!("1860f762ebfbd08284e421702de0de18baa9c9596291b08466f37de21c7f"),
+        ciphertext: &hex!("6298b296e24e8cc35dce0bed484b7f30d580"),
+        tag: &hex!("3e377094f04709f64d7b985310a4db84"),
+    },
+    TestVector {
+        key: &hex!("f901cfe8a69615a93fdf7a98cad48179"),
+        nonce: &hex!("6245709fb18853f68d833640"),
+        plaintext: &hex!("e42a3c02c25b64869e146d7b233987bddfc240871d"),
+        aad: &hex!("7576f7028ec6eb5ea7e298342a94d4b202b370ef9768ec6561c4fe6b7e7296fa859c21"),
+        ciphertext: &hex!("391cc328d484a4f46406181bcd62efd9b3ee197d05"),
+        tag: &hex!("2d15506c84a9edd65e13e9d24a2a6e70"),
+    },
+];
@@ -0,0 +1,225 @@
+#[macro_use]
+#[path = "../gcm_siv_tests.rs"]
+mod gcm_siv_tests;
+
+use self::gcm_siv_tests::TestVector;
+use hex_literal::hex;
+
+use aesp::{Cipher, Key};
+
+gcm_siv_tests!(TEST_VECTORS);
+
+/// RFC 8452 appendix C.2: AEAD_AES_256_GCM_SIV, followed by the counter wrap tests from appendix C.3
+///
+/// <https://www.rfc-editor.org/rfc/rfc8452#appendix-C.2>
+/// Copied from https://github.com/RustCrypto/AEADs/tree/master/aes-gcm-siv/tests
+const TEST_VECTORS: &[TestVector<[u8; 32], [u8; 12]>] = &[
+    TestVector {
+        key: &hex!("0100000000000000000000000000000000000000000000000000000000000000"),
+        nonce: &hex!("030000000000000000000000"),
+        plaintext: &hex!(""),
+        aad: &hex!(""),
+        ciphertext: &hex!(""),
+        tag: &hex!("07f5f4169bbf55a8400cd47ea6fd400f"),
+    },
+    TestVector {
+        key: &hex!("0100000000000000000000000000000000000000000000000000000000000000"),
+        nonce: &hex!("030000000000000000000000"),
+        plaintext: &hex!("0100000000000000"),
+        aad: &hex!(""),
+        ciphertext: &hex!("c2ef328e5c71c83b"),
+        tag: &hex!("843122130f7364b761e0b97427e3df28"),
+    },
+    TestVector {
+        key: &hex!("0100000000000000000000000000000000000000000000000000000000000000"),
+        nonce: &hex!("030000000000000000000000"),
+        plaintext: &hex!("010000000000000000000000"),
+        aad: &hex!(""),
+        ciphertext: &hex!("9aab2aeb3faa0a34aea8e2b1"),
+        tag: &hex!("8ca50da9ae6559e48fd10f6e5c9ca17e"),
+    },
+    TestVector {
+        key: &hex!("0100000000000000000000000000000000000000000000000000000000000000"),
+        nonce: &hex!("030000000000000000000000"),
+        plaintext: &hex!("01000000000000000000000000000000"),
+        aad: &hex!(""),
+        ciphertext: &hex!("85a01b63025ba19b7fd3ddfc033b3e76"),
+        tag: &hex!("c9eac6fa700942702e90862383c6c366"),
+    },
+    TestVector {
+        key: &hex!("0100000000000000000000000000000000000000000000000000000000000000"),
+        nonce: &hex!("030000000000000000000000"),
+        plaintext: &hex!("0100000000000000000000000000000002000000000000000000000000000000"),
+        aad: &hex!(""),
+        ciphertext: &hex!("4a6a9db4c8c6549201b9edb53006cba821ec9cf850948a7c86c68ac7539d027f"),
+        tag: &hex!("e819e63abcd020b006a976397632eb5d"),
+    },
+    TestVector {
+        key: &hex!("0100000000000000000000000000000000000000000000000000000000000000"),
+        nonce: &hex!("030000000000000000000000"),
+        plaintext: &hex!("010000000000000000000000000000000200000000000000000000000000000003000000000000000000000000000000"),
+        aad: &hex!(""),
+        ciphertext: &hex!("c00d121893a9fa603f48ccc1ca3c57ce7499245ea0046db16c53c7c66fe717e39cf6c748837b61f6ee3adcee17534ed5"),
+        tag: &hex!("790bc96880a99ba804bd12c0e6a22cc4"),
+    },
+    TestVector {
+        key: &hex!("0100000000000000000000000000000000000000000000000000000000000000"),
+        nonce: &hex!("030000000000000000000000"),
+        plaintext: &hex!("01000000000000000000000000000000020000000000000000000000000000000300000000000000000000000000000004000000000000000000000000000000"),
+        aad: &hex!(""),
+        ciphertext: &hex!("c2d5160a1f8683834910acdafc41fbb1632d4a353e8b905ec9a5499ac34f96c7e1049eb080883891a4db8caaa1f99dd004d80487540735234e3744512c6f90ce"),
+        tag: &hex!("112864c269fc0d9d88c61fa47e39aa08"),
+    },
+    TestVector {
+        key: &hex!("0100000000000000000000000000000000000000000000000000000000000000"),
+        nonce: &hex!("030000000000000000000000"),
+        plaintext: &hex!("0200000000000000"),
+        aad: &hex!("01"),
+        ciphertext: &hex!("1de22967237a8132"),
+        tag: &hex!("91213f267e3b452f02d01ae33e4ec854"),
+    },
+    TestVector {
+        key: &hex!("0100000000000000000000000000000000000000000000000000000000000000"),
+        nonce: &hex!("030000000000000000000000"),
+        plaintext: &hex!("020000000000000000000000"),
+        aad: &hex!("01"),
+        ciphertext: &hex!("163d6f9cc1b346cd453a2e4c"),
+        tag: &hex!("c1a4a19ae800941ccdc57cc8413c277f"),
+    },
+    TestVector {
+        key: &hex!("0100000000000000000000000000000000000000000000000000000000000000"),
+        nonce: &hex!("030000000000000000000000"),
+        plaintext: &hex!("02000000000000000000000000000000"),
+        aad: &hex!("01"),
+        ciphertext: &hex!("c91545823cc24f17dbb0e9e807d5ec17"),
+        tag: &hex!("b292d28ff61189e8e49f3875ef91aff7"),
+    },
+    TestVector {
+        key: &hex!("0100000000000000000000000000000000000000000000000000000000000000"),
+        nonce: &hex!("030000000000000000000000"),
+        plaintext: &hex!("0200000000000000000000000000000003000000000000000000000000000000"),
+        aad: &hex!("01"),
+        ciphertext: &hex!("07dad364bfc2b9da89116d7bef6daaaf6f255510aa654f920ac81b94e8bad365"),
+        tag: &hex!("aea1bad12702e1965604374aab96dbbc"),
+    },
+    TestVector {
+        key: &hex!("0100000000000000000000000000000000000000000000000000000000000000"),
+        nonce: &hex!("030000000000000000000000"),
+        plaintext: &hex!("020000000000000000000000000000000300000000000000000000000000000004000000000000000000000000000000"),
+        aad: &hex!("01"),
+        ciphertext: &hex!("c67a1f0f567a5198aa1fcc8e3f21314336f7f51ca8b1af61feac35a86416fa47fbca3b5f749cdf564527f2314f42fe25"),
+        tag: &hex!("03332742b228c647173616cfd44c54eb"),
+    },
+    TestVector {
+        key: &hex!("0100000000000000000000000000000000000000000000000000000000000000"),
+        nonce: &hex!("030000000000000000000000"),
+        plaintext: &hex!("02000000000000000000000000000000030000000000000000000000000000000400000000000000000000000000000005000000000000000000000000000000"),
+        aad: &hex!("01"),
+        ciphertext: &hex!("67fd45e126bfb9a79930c43aad2d36967d3f0e4d217c1e551f59727870beefc98cb933a8fce9de887b1e40799988db1fc3f91880ed405b2dd298318858467c89"),
+        tag: &hex!("5bde0285037c5de81e5b570a049b62a0"),
+    },
+    TestVector {
+        key: &hex!("0100000000000000000000000000000000000000000000000000000000000000"),
+        nonce: &hex!("030000000000000000000000"),
+        plaintext: &hex!("02000000"),
+        aad: &hex!("010000000000000000000000"),
+        ciphertext: &hex!("22b3f4cd"),
+        tag: &hex!("1835e517741dfddccfa07fa4661b74cf"),
+    },
+    TestVector {
+        key: &hex!("0100000000000000000000000000000000000000000000000000000000000000"),
+        nonce: &hex!("030000000000000000000000"),
+        plaintext: &hex!("0300000000000000000000000000000004000000"),
+        aad: &hex!("010000000000000000000000000000000200"),
+        ciphertext: &hex!("43dd0163cdb48f9fe3212bf61b201976067f342b"),
+        tag: &hex!("b879ad976d8242acc188ab59cabfe307"),
+    },
+    TestVector {
+        key: &hex!("0100000000000000000000000000000000000000000000000000000000000000"),
+        nonce: &hex!("030000000000000000000000"),
+        plaintext: &hex!("030000000000000000000000000000000400"),
+        aad: &hex!("0100000000000000000000000000000002000000"),
+        ciphertext: &hex!("462401724b5ce6588d5a54aae5375513a075"),
+        tag: &hex!("cfcdf5042112aa29685c912fc2056543"),
+    },
+    TestVector {
+        key: &hex!("e66021d5eb8e4f4066d4adb9c33560e4f46e44bb3da0015c94f7088736864200"),
+        nonce: &hex!("e0eaf5284d884a0e77d31646"),
+        plaintext: &hex!(""),
+        aad: &hex!(""),
+        ciphertext: &hex!(""),
+        tag: &hex!("169fbb2fbf389a995f6390af22228a62"),
+    },
+    TestVector {
+        key: &hex!("bae8e37fc83441b16034566b7a806c46bb91c3c5aedb64a6c590bc84d1a5e269"),
+        nonce: &hex!("e4b47801afc0577e34699b9e"),
+        plaintext: &hex!("671fdd"),
+        aad: &hex!("4fbdc66f14"),
+        ciphertext: &hex!("0eaccb"),
+        tag: &hex!("93da9bb81333aee0c785b240d319719d"),
+    },
+    TestVector {
+        key: &hex!("6545fc880c94a95198874296d5cc1fd161320b6920ce07787f86743b275d1ab3"),
+        nonce: &hex!("2f6d1f0434d8848c1177441f"),
+        plaintext: &hex!("195495860f04"),
+        aad: &hex!("6787f3ea22c127aaf195"),
+        ciphertext: &hex!("a254dad4f3f9"),
+        tag: &hex!("6b62b84dc40c84636a5ec12020ec8c2c"),
+    },
+    TestVector {
+        key: &hex!("d1894728b3fed1473c528b8426a582995929a1499e9ad8780c8d63d0ab4149c0"),
+        nonce: &hex!("9f572c614b4745914474e7c7"),
+        plaintext: &hex!("c9882e5386fd9f92ec"),
+        aad: &hex!("489c8fde2be2cf97e74e932d4ed87d"),
+        ciphertext: &hex!("0df9e308678244c44b"),
+        tag: &hex!("c0fd3dc6628dfe55ebb0b9fb2295c8c2"),
+    },
+    TestVector {
+        key: &hex!("a44102952ef94b02b805249bac80e6f61455bfac8308a2d40d8c845117808235"),
+        nonce: &hex!("5c9e940fea2f582950a70d5a"),
+        plaintext: &hex!("1db2316fd568378da107b52b"),
+        aad: &hex!("0da55210cc1c1b0abde3b2f204d1e9f8b06bc47f"),
+        ciphertext: &hex!("8dbeb9f7255bf5769dd56692"),
+        tag: &hex!("404099c2587f64979f21826706d497d5"),
+    },
+    TestVector {
+        key: &hex!("9745b3d1ae06556fb6aa7890bebc18fe6b3db4da3d57aa94842b9803a96e07fb"),
+        nonce: &hex!("6de71860f762ebfbd08284e4"),
+        plaintext: &hex!("21702de0de18baa9c9596291b08466"),
+        aad: &hex!("f37de21c7ff901cfe8a69615a93fdf7a98cad481796245709f"),
+        ciphertext: &hex!("793576dfa5c0f88729a7ed3c2f1bff"),
+        tag: &hex!("b3080d28f6ebb5d3648ce97bd5ba67fd"),
+    },
+    TestVector {
+        key: &hex!("b18853f68d833640e42a3c02c25b64869e146d7b233987bddfc240871d7576f7"),
+        nonce: &hex!("028ec6eb5ea7e298342a94d4"),
+        plaintext: &hex!("b202b370ef9768ec6561c4fe6b7e7296fa85"),
+        aad: &hex!("9c2159058b1f0fe91433a5bdc20e214eab7fecef4454a10ef0657df21ac7"),
+        ciphertext: &hex!("857e16a64915a787637687db4a9519635cdd"),
+        tag: &hex!("454fc2a154fea91f8363a39fec7d0a49"),
+    },
+    TestVector {
+        key: &hex!("3c535de192eaed3822a2fbbe2ca9dfc88255e14a661b8aa82cc54236093bbc23"),
+        nonce: &hex!("688089e55540db1872504e1c"),
+        plaintext: &hex!("ced532ce4159b035277d4dfbb7db62968b13cd4eec"),
+        aad: &hex!("734320ccc9d9bbbb19cb81b2af4ecbc3e72834321f7aa0f70b7282b4f33df23f167541"),
+        ciphertext: &hex!("626660c26ea6612fb17ad91e8e767639edd6c9faee"),
+        tag: &hex!("9d6c7029675b89eaf4ba1ded1a286594"),
+    },
+    TestVector {
+        key: &hex!("0000000000000000000000000000000000000000000000000000000000000000"),
+        nonce: &hex!("000000000000000000000000"),
+        plaintext: &hex!("000000000000000000000000000000004db923dc793ee6497c76dcc03a98e108"),
+        aad: &hex!(""),
+        ciphertext: &hex!("f3f80f2cf0cb2dd9c5984fcda908456cc537703b5ba70324a6793a7bf218d3ea"),
+        tag: &hex!("ffffffff000000000000000000000000"),
+    },
+    TestVector {
+        key: &hex!("0000000000000000000000000000000000000000000000000000000000000000"),
+        nonce: &hex!("000000000000000000000000"),
+        plaintext: &hex!("eb3640277c7ffd1303c7a542d02d3e4c0000000000000000"),
+        aad: &hex!(""),
+        ciphertext: &hex!("18ce4f0b8cb4d0cac65fea8f79257b20888e53e72299e56d"),
+        tag: &hex!("ffffffff000000000000000000000000"),
+    },
+];